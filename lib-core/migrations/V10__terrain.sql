@@ -0,0 +1,6 @@
+CREATE TABLE terrain (
+    x INTEGER NOT NULL,
+    y INTEGER NOT NULL,
+    terrain_type TEXT NOT NULL,
+    PRIMARY KEY(x, y)
+);
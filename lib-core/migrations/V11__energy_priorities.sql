@@ -0,0 +1,5 @@
+CREATE TABLE energy_priorities (
+    entity_id INTEGER PRIMARY KEY,
+    priority TEXT NOT NULL,
+    FOREIGN KEY(entity_id) REFERENCES entities(id)
+);
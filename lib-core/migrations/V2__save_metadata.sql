@@ -0,0 +1,9 @@
+CREATE TABLE save_metadata (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    saved_at INTEGER NOT NULL, -- Unix timestamp in seconds
+    map_name TEXT NOT NULL,
+    playtime REAL NOT NULL, -- Seconds spent in GameState::Running
+    stock_snapshot TEXT NOT NULL, -- "resource=amount" pairs separated by ';'
+    objectives_completed INTEGER NOT NULL,
+    objectives_total INTEGER NOT NULL
+);
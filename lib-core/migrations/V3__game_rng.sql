@@ -0,0 +1,9 @@
+CREATE TABLE game_rng (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    seed INTEGER NOT NULL -- u64 stored as its i64 bit pattern
+);
+
+CREATE TABLE game_rng_streams (
+    stream_name TEXT PRIMARY KEY,
+    state INTEGER NOT NULL -- u64 stored as its i64 bit pattern
+);
@@ -0,0 +1,10 @@
+CREATE TABLE replay_ticks (
+    tick INTEGER PRIMARY KEY,
+    delta_nanos INTEGER NOT NULL -- Time delta the tick was simulated with
+);
+
+CREATE TABLE replay_commands (
+    id INTEGER PRIMARY KEY, -- Issue order
+    tick INTEGER NOT NULL,
+    command_json TEXT NOT NULL
+);
@@ -0,0 +1,13 @@
+CREATE TABLE grid_paths (
+    entity_id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL, -- "x,y" steps separated by ';', next step first
+    is_current INTEGER NOT NULL, -- Whether the path was found on the obstacle grid as it was when saved
+    FOREIGN KEY(entity_id) REFERENCES entities(id)
+);
+
+CREATE TABLE wisp_states (
+    id INTEGER PRIMARY KEY,
+    state TEXT NOT NULL,
+    charge_attack TEXT NOT NULL,
+    FOREIGN KEY(id) REFERENCES wisps(id)
+);
@@ -0,0 +1,19 @@
+CREATE TABLE timers (
+    entity_id INTEGER NOT NULL,
+    timer_name TEXT NOT NULL,
+    elapsed REAL NOT NULL, -- Seconds, the duration comes from the building itself
+    PRIMARY KEY(entity_id, timer_name),
+    FOREIGN KEY(entity_id) REFERENCES entities(id)
+);
+
+CREATE TABLE tower_top_rotations (
+    entity_id INTEGER PRIMARY KEY,
+    current_angle REAL NOT NULL,
+    FOREIGN KEY(entity_id) REFERENCES entities(id)
+);
+
+CREATE TABLE tower_wisp_targets (
+    entity_id INTEGER PRIMARY KEY,
+    target_wisp_id INTEGER NOT NULL,
+    FOREIGN KEY(entity_id) REFERENCES entities(id)
+);
@@ -0,0 +1,4 @@
+CREATE TABLE almanach_snapshot (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    data TEXT NOT NULL -- YAML in the format of assets/data.yaml
+);
@@ -0,0 +1,3 @@
+ALTER TABLE grid_imprints ADD COLUMN radius INTEGER; -- Circle
+ALTER TABLE grid_imprints ADD COLUMN thickness INTEGER; -- LShape
+ALTER TABLE grid_imprints ADD COLUMN cells INTEGER; -- Mask, bits of covered cells row by row
@@ -40,6 +40,18 @@ pub enum TowerType {
     Emitter,
 }
 
+/// Marker tables of buildings in the save database, paired with the type used to look them up in the almanach.
+pub const BUILDING_TABLES: [(&str, BuildingType); 8] = [
+    ("main_bases", BuildingType::MainBase),
+    ("energy_relays", BuildingType::EnergyRelay),
+    ("exploration_centers", BuildingType::ExplorationCenter),
+    ("mining_complexes", BuildingType::MiningComplex),
+    ("tower_blasters", BuildingType::Tower(TowerType::Blaster)),
+    ("tower_cannons", BuildingType::Tower(TowerType::Cannon)),
+    ("tower_rocket_launchers", BuildingType::Tower(TowerType::RocketLauncher)),
+    ("tower_emitters", BuildingType::Tower(TowerType::Emitter)),
+];
+
 #[derive(Component, Clone, Debug, Default)]
 #[require(AutoGridTransformSync, ZDepth = Z_BUILDING, MaxHealth, MapBound, ObstacleGridObject = ObstacleGridObject::Building)]
 pub struct Building;
@@ -64,22 +76,159 @@ pub struct ExplorationCenter;
 pub struct Tower;
 
 #[derive(Component)]
-#[require(Building, BuildingType = BuildingType::Tower(TowerType::Blaster), AttackRange, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget)]
+#[require(Building, BuildingType = BuildingType::Tower(TowerType::Blaster), AttackRange, TowerRangeShape, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget, TargetingPriority)]
 pub struct TowerBlaster;
 
 #[derive(Component)]
-#[require(Building, BuildingType = BuildingType::Tower(TowerType::Cannon), AttackRange, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget)]
+#[require(Building, BuildingType = BuildingType::Tower(TowerType::Cannon), AttackRange, TowerRangeShape, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget, TargetingPriority)]
 pub struct TowerCannon;
 
 #[derive(Component)]
-#[require(Building, BuildingType = BuildingType::Tower(TowerType::RocketLauncher), AttackRange, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget)]
+#[require(Building, BuildingType = BuildingType::Tower(TowerType::RocketLauncher), AttackRange, TowerRangeShape, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget, TargetingPriority)]
 pub struct TowerRocketLauncher;
 
 #[derive(Component)]
-#[require(Building, BuildingType = BuildingType::Tower(TowerType::Emitter), AttackRange, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget)]
+#[require(Building, BuildingType = BuildingType::Tower(TowerType::Emitter), AttackRange, TowerRangeShape, AttackSpeed, AttackDamage, TowerShootingTimer, TowerWispTarget, TargetingPriority)]
 pub struct TowerEmitter;
 
 
+/// How the `AttackRange` of a tower spreads around its imprint. Distances are measured from the closest imprint cell.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum TowerRangeShape {
+    /// Counted in horizontal and vertical steps, which gives a diamond.
+    Manhattan,
+    /// Straight-line distance.
+    #[default]
+    Circle,
+    /// Like `Circle`, but wisps closer than `min_range` can't be targeted.
+    Ring { min_range: f32 },
+}
+impl TowerRangeShape {
+    /// Whether `coords` is in range of a tower at `imprint_coords`.
+    pub fn covers(&self, imprint_coords: GridCoords, imprint: GridImprint, range: f32, coords: GridCoords) -> bool {
+        let distance = imprint.iter_covered_coords(imprint_coords)
+            .map(|imprint_cell| self.distance(imprint_cell, coords))
+            .fold(f32::INFINITY, f32::min);
+        if distance > range { return false; }
+        match self {
+            TowerRangeShape::Manhattan | TowerRangeShape::Circle => true,
+            TowerRangeShape::Ring { min_range } => distance >= *min_range,
+        }
+    }
+    /// All fields within the map `bounds` that are in range of a tower at `imprint_coords`.
+    pub fn covered_coords(&self, imprint_coords: GridCoords, imprint: GridImprint, range: f32, bounds: (i32, i32)) -> Vec<GridCoords> {
+        let reach = range.ceil() as i32;
+        let (width, height) = imprint.bounds();
+        (imprint_coords.y - reach..imprint_coords.y + height + reach)
+            .flat_map(|y| (imprint_coords.x - reach..imprint_coords.x + width + reach).map(move |x| GridCoords { x, y }))
+            .filter(|coords| coords.is_in_bounds(bounds) && self.covers(imprint_coords, imprint, range, *coords))
+            .collect()
+    }
+    fn distance(&self, from: GridCoords, to: GridCoords) -> f32 {
+        match self {
+            TowerRangeShape::Manhattan => from.manhattan_distance(&to) as f32,
+            _ => Vec2::new((to.x - from.x) as f32, (to.y - from.y) as f32).length(),
+        }
+    }
+}
+
+/// Which wisp in range a tower picks when it looks for a new target.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TargetingPriority {
+    #[default]
+    Closest,
+    /// Cheapest remaining way to an energy supplier along its flow field.
+    First,
+    /// Most current health.
+    Strongest,
+    /// Least current health.
+    Weakest,
+    /// Highest speed on the terrain it currently stands on.
+    Fastest,
+    ClosestToMainBase,
+}
+impl TargetingPriority {
+    pub const ALL: [TargetingPriority; 6] = [
+        TargetingPriority::Closest,
+        TargetingPriority::First,
+        TargetingPriority::Strongest,
+        TargetingPriority::Weakest,
+        TargetingPriority::Fastest,
+        TargetingPriority::ClosestToMainBase,
+    ];
+
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            TargetingPriority::Closest => "Closest",
+            TargetingPriority::First => "First",
+            TargetingPriority::Strongest => "Strongest",
+            TargetingPriority::Weakest => "Weakest",
+            TargetingPriority::Fastest => "Fastest",
+            TargetingPriority::ClosestToMainBase => "ClosestToMainBase",
+        }
+    }
+
+    pub fn from_db_str(priority: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| candidate.as_db_str() == priority)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TargetingPriority::Closest => "Closest",
+            TargetingPriority::First => "First",
+            TargetingPriority::Strongest => "Strongest",
+            TargetingPriority::Weakest => "Weakest",
+            TargetingPriority::Fastest => "Fastest",
+            TargetingPriority::ClosestToMainBase => "Near Main Base",
+        }
+    }
+
+    /// The following option, wrapping around. Used to cycle through them from the info panel.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|candidate| candidate == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Order in which a network's consumers are served when it produces less energy than they demand.
+/// The lowest priorities are browned out first.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum EnergyPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+impl EnergyPriority {
+    pub const ALL: [EnergyPriority; 3] = [EnergyPriority::Low, EnergyPriority::Normal, EnergyPriority::High];
+
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            EnergyPriority::Low => "Low",
+            EnergyPriority::Normal => "Normal",
+            EnergyPriority::High => "High",
+        }
+    }
+
+    pub fn from_db_str(priority: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| candidate.as_db_str() == priority)
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.as_db_str()
+    }
+
+    /// The following option, wrapping around. Used to cycle through them from the info panel.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|candidate| candidate == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Marks towers that only target wisps they can see. See `has_line_of_sight()`.
+#[derive(Component, Default)]
+pub struct RequiresLineOfSight;
+
 #[derive(Component, Default)]
 #[require(AttackSpeed)]
 pub struct TowerShootingTimer(pub Timer);
@@ -93,6 +242,13 @@ impl TowerShootingTimer {
         if attack_speed.0 == 0. { return; }
         timer.0.set_duration(Duration::from_secs_f32(1. / attack_speed.0));
     }
+
+    /// Restores a saved timer. The duration follows once the tower gets its `AttackSpeed`.
+    pub fn from_elapsed_secs(elapsed: f32) -> Self {
+        let mut timer = Timer::default();
+        timer.set_elapsed(Duration::from_secs_f32(elapsed));
+        Self(timer)
+    }
 }
 
 #[derive(Component, Default)]
@@ -102,6 +258,79 @@ pub enum TowerWispTarget {
     Wisp(Entity),
     NoValidTargets(GridVersion),
 }
+impl TowerWispTarget {
+    pub fn wisp(&self) -> Option<Entity> {
+        match self {
+            TowerWispTarget::Wisp(wisp) => Some(*wisp),
+            _ => None,
+        }
+    }
+}
+impl From<Option<Entity>> for TowerWispTarget {
+    fn from(wisp: Option<Entity>) -> Self {
+        wisp.map_or(TowerWispTarget::SearchForNewTarget, TowerWispTarget::Wisp)
+    }
+}
 
 #[derive(Component, Default)]
-pub struct DisabledByPlayer;
\ No newline at end of file
+pub struct DisabledByPlayer;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: (i32, i32) = (20, 20);
+    const SINGLE_FIELD: GridImprint = GridImprint::Rectangle { width: 1, height: 1 };
+
+    /// Checks `covered_coords()` against `covers()` for every field of the map and returns the covered ones.
+    fn covered(shape: TowerRangeShape, imprint_coords: GridCoords, imprint: GridImprint, range: f32) -> Vec<GridCoords> {
+        let covered = shape.covered_coords(imprint_coords, imprint, range, BOUNDS);
+        for y in 0..BOUNDS.1 {
+            for x in 0..BOUNDS.0 {
+                let coords = GridCoords { x, y };
+                assert_eq!(covered.contains(&coords), shape.covers(imprint_coords, imprint, range, coords), "{:?} disagrees at {:?}", shape, coords);
+            }
+        }
+        covered
+    }
+
+    #[test]
+    fn circle_range() {
+        let tower = GridCoords { x: 10, y: 10 };
+        let covered = covered(TowerRangeShape::Circle, tower, SINGLE_FIELD, 2.);
+        assert_eq!(covered.len(), 13);
+        assert!(covered.contains(&GridCoords { x: 12, y: 10 }));
+        assert!(covered.contains(&GridCoords { x: 11, y: 11 }));
+        assert!(!covered.contains(&GridCoords { x: 12, y: 11 }));
+    }
+
+    #[test]
+    fn range_is_measured_from_the_closest_imprint_field() {
+        let covered = covered(TowerRangeShape::Circle, GridCoords { x: 10, y: 10 }, GridImprint::Rectangle { width: 2, height: 2 }, 1.);
+        // The imprint itself and the fields touching its sides, not the diagonal ones
+        assert_eq!(covered.len(), 4 + 8);
+        assert!(covered.contains(&GridCoords { x: 12, y: 11 }));
+        assert!(!covered.contains(&GridCoords { x: 12, y: 12 }));
+    }
+
+    #[test]
+    fn ring_range_excludes_fields_closer_than_min_range() {
+        let tower = GridCoords { x: 10, y: 10 };
+        let covered = covered(TowerRangeShape::Ring { min_range: 2. }, tower, SINGLE_FIELD, 3.);
+        assert_eq!(covered.len(), 20);
+        assert!(!covered.contains(&tower));
+        assert!(!covered.contains(&GridCoords { x: 11, y: 11 }));
+        assert!(covered.contains(&GridCoords { x: 12, y: 10 }), "Exactly `min_range` away is covered");
+        assert!(covered.contains(&GridCoords { x: 13, y: 10 }));
+        assert!(!covered.contains(&GridCoords { x: 14, y: 10 }));
+    }
+
+    #[test]
+    fn manhattan_range_and_map_bounds() {
+        let covered = covered(TowerRangeShape::Manhattan, GridCoords { x: 10, y: 10 }, SINGLE_FIELD, 2.);
+        assert_eq!(covered.len(), 13);
+        assert!(!covered.contains(&GridCoords { x: 11, y: 12 }));
+        let in_corner = covered(TowerRangeShape::Circle, GridCoords { x: 0, y: 0 }, SINGLE_FIELD, 2.);
+        assert_eq!(in_corner.len(), 6);
+    }
+}
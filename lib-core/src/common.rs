@@ -102,6 +102,14 @@ pub struct AttackRange(pub f32);
 #[derive(Component, Default, Clone, Copy, Property)]
 #[component(immutable)]
 pub struct EnergySupplyRange(pub f32);
+/// Energy units a generator feeds into its network.
+#[derive(Component, Default, Clone, Copy, Property)]
+#[component(immutable)]
+pub struct EnergyProduction(pub f32);
+/// Energy units a building draws from its network while working.
+#[derive(Component, Default, Clone, Copy, Property)]
+#[component(immutable)]
+pub struct EnergyConsumption(pub f32);
 
 
 #[derive(Component, Default)]
@@ -247,6 +255,7 @@ macro_rules! define_z_indexes {
 }
 
 define_z_indexes!(
+    Z_TERRAIN,
     Z_OBSTACLE,
     Z_OVERLAY_ENERGY_SUPPLY,
     Z_BUILDING,
@@ -84,38 +84,78 @@ impl Into<(i32, i32)> for GridCoords {
 #[derive(Component, Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
 pub enum GridImprint {
     Rectangle{width: i32, height: i32},
+    /// Cells whose centers are within `radius` of the middle cell. Spans `2 * radius + 1` cells in both directions.
+    Circle{radius: i32},
+    /// Arbitrary shape with at most `GridImprint::MAX_MASK_CELLS` cells in its bounds.
+    /// Bit `y * width + x` of `cells` marks the cell at offset (x, y) as covered.
+    Mask{width: i32, height: i32, cells: u64},
+    /// Two arms of the given thickness running along the left and the bottom side of the bounds.
+    LShape{width: i32, height: i32, thickness: i32},
 }
 impl GridImprint {
-    /// Return all the GridCoords covered by the imprint. Does not check for map bounds.
-    pub fn covered_coords(&self, coords: GridCoords) -> Vec<GridCoords> {
+    /// Width times height of a `Mask` can't exceed the bits of `cells`.
+    pub const MAX_MASK_CELLS: i32 = u64::BITS as i32;
+
+    /// Rejects shapes without any area, and masks too big to fit their cells.
+    pub fn validate(&self) -> Result<(), String> {
+        let (width, height) = self.bounds();
+        if width <= 0 || height <= 0 {
+            return Err(format!("{:?} has no area", self));
+        }
         match self {
-            GridImprint::Rectangle{width, height} => {
-                 (0..*height)
-                     .flat_map(|y| (0..*width).map(move |x| coords.shifted((x, y))))
-                     .collect()
+            GridImprint::Mask { width, height, .. } if width * height > Self::MAX_MASK_CELLS => {
+                Err(format!("Mask of {}x{} has more than {} cells", width, height, Self::MAX_MASK_CELLS))
             }
+            GridImprint::LShape { thickness, .. } if *thickness <= 0 => Err(format!("{:?} has no thickness", self)),
+            _ => Ok(()),
         }
     }
-    pub fn covers_coords(&self, imprint_coords: GridCoords, query_coords: GridCoords) -> bool {
+    /// Return all the GridCoords covered by the imprint. Does not check for map bounds.
+    pub fn covered_coords(&self, coords: GridCoords) -> Vec<GridCoords> {
+        self.iter_covered_coords(coords).collect()
+    }
+    /// Same as `covered_coords()` without collecting.
+    pub fn iter_covered_coords(self, coords: GridCoords) -> impl Iterator<Item = GridCoords> {
+        let (width, height) = self.bounds();
+        (0..height)
+            .flat_map(move |y| (0..width).map(move |x| (x, y)))
+            .filter(move |offset| self.covers_offset(*offset))
+            .map(move |offset| coords.shifted(offset))
+    }
+    /// Whether the cell at the given offset from the imprint's bottom-left corner is part of the shape.
+    pub fn covers_offset(&self, (x, y): (i32, i32)) -> bool {
+        let (width, height) = self.bounds();
+        if x < 0 || x >= width || y < 0 || y >= height { return false; }
         match self {
-            GridImprint::Rectangle{width, height} => {
-                query_coords.x >= imprint_coords.x && query_coords.x < imprint_coords.x + *width && query_coords.y >= imprint_coords.y && query_coords.y < imprint_coords.y + *height
+            GridImprint::Rectangle{..} => true,
+            GridImprint::Circle{radius} => {
+                let (dx, dy) = (x - radius, y - radius);
+                dx * dx + dy * dy <= radius * radius
             }
+            GridImprint::Mask{width, cells, ..} => {
+                let bit = y * width + x;
+                bit < 64 && *cells & (1u64 << bit) != 0
+            }
+            GridImprint::LShape{thickness, ..} => x < *thickness || y < *thickness,
         }
     }
+    pub fn covers_coords(&self, imprint_coords: GridCoords, query_coords: GridCoords) -> bool {
+        self.covers_offset((query_coords.x - imprint_coords.x, query_coords.y - imprint_coords.y))
+    }
+    /// Size of the rectangle enclosing the shape.
     pub fn bounds(&self) -> (i32, i32) {
         match self {
-            GridImprint::Rectangle{width, height} => {
+            GridImprint::Rectangle{width, height}
+            | GridImprint::Mask{width, height, ..}
+            | GridImprint::LShape{width, height, ..} => {
                 (*width, *height)
             }
+            GridImprint::Circle{radius} => (2 * radius + 1, 2 * radius + 1),
         }
     }
     pub fn world_size(&self) -> Vec2 {
-        match self {
-            GridImprint::Rectangle{width, height} => {
-                Vec2::new(*width as f32 * CELL_SIZE, *height as f32 * CELL_SIZE)
-            }
-        }
+        let (width, height) = self.bounds();
+        Vec2::new(width as f32 * CELL_SIZE, height as f32 * CELL_SIZE)
     }
     pub fn world_center(&self) -> Vec2 {
         self.world_size() / 2.
@@ -178,4 +218,118 @@ pub enum ObstacleGridObject {
 
 /// Adding or removing entities with this component causes full recalculation of the emission grid
 #[derive(Component, Default)]
-pub struct EmissionsGridSpreadAffector;
\ No newline at end of file
+pub struct EmissionsGridSpreadAffector;
+/// Ground of a field. Changes how fast wisps walk over it and how eager they are to path through it, without blocking them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum TerrainType {
+    #[default]
+    Plain,
+    Road,
+    Mud,
+    Swamp,
+    /// Crossable only by slowly climbing through, so wisps avoid it whenever there is another way.
+    Chasm,
+}
+impl TerrainType {
+    pub const ALL: [TerrainType; 5] = [TerrainType::Plain, TerrainType::Road, TerrainType::Mud, TerrainType::Swamp, TerrainType::Chasm];
+
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            TerrainType::Plain => 1.,
+            TerrainType::Road => 1.5,
+            TerrainType::Mud => 0.6,
+            TerrainType::Swamp => 0.4,
+            TerrainType::Chasm => 0.2,
+        }
+    }
+
+    /// Cost of entering the field relative to plain ground.
+    pub fn path_cost(&self) -> f32 {
+        match self {
+            TerrainType::Plain => 1.,
+            TerrainType::Road => 0.6,
+            TerrainType::Mud => 2.,
+            TerrainType::Swamp => 3.,
+            TerrainType::Chasm => 10.,
+        }
+    }
+
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            TerrainType::Plain => "Plain",
+            TerrainType::Road => "Road",
+            TerrainType::Mud => "Mud",
+            TerrainType::Swamp => "Swamp",
+            TerrainType::Chasm => "Chasm",
+        }
+    }
+
+    pub fn from_db_str(terrain_type: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| candidate.as_db_str() == terrain_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: GridCoords = GridCoords { x: 10, y: 20 };
+
+    /// Checks that the covered fields lie within `bounds()` and agree with `covers_coords()`, and returns their offsets.
+    fn covered_offsets(imprint: GridImprint) -> Vec<(i32, i32)> {
+        let (width, height) = imprint.bounds();
+        let covered = imprint.covered_coords(ORIGIN);
+        for y in -1..=height {
+            for x in -1..=width {
+                let coords = ORIGIN.shifted((x, y));
+                assert_eq!(covered.contains(&coords), imprint.covers_coords(ORIGIN, coords), "{:?} disagrees at {:?}", imprint, (x, y));
+            }
+        }
+        covered.into_iter().map(|coords| (coords.x - ORIGIN.x, coords.y - ORIGIN.y)).collect()
+    }
+
+    #[test]
+    fn circle_imprint() {
+        let imprint = GridImprint::Circle { radius: 2 };
+        assert_eq!(imprint.bounds(), (5, 5));
+        let covered = covered_offsets(imprint);
+        assert_eq!(covered.len(), 13);
+        assert!(covered.contains(&(2, 0)));
+        assert!(!covered.contains(&(1, 0)), "Corners are cut off");
+        assert_eq!(imprint.validate(), Ok(()));
+    }
+
+    #[test]
+    fn mask_imprint() {
+        // Bottom row: left and right cell, top row: middle cell
+        let imprint = GridImprint::Mask { width: 3, height: 2, cells: 0b010_101 };
+        assert_eq!(imprint.bounds(), (3, 2));
+        assert_eq!(covered_offsets(imprint), vec![(0, 0), (2, 0), (1, 1)]);
+        assert_eq!(imprint.validate(), Ok(()));
+    }
+
+    #[test]
+    fn mask_with_more_cells_than_bits_is_rejected() {
+        assert_eq!(GridImprint::Mask { width: 8, height: 8, cells: u64::MAX }.validate(), Ok(()));
+        assert!(GridImprint::Mask { width: 9, height: 8, cells: u64::MAX }.validate().is_err());
+    }
+
+    #[test]
+    fn lshape_imprint() {
+        let imprint = GridImprint::LShape { width: 3, height: 4, thickness: 1 };
+        assert_eq!(imprint.bounds(), (3, 4));
+        let covered = covered_offsets(imprint);
+        assert_eq!(covered.len(), 4 + 3 - 1);
+        assert!(covered.contains(&(0, 3)));
+        assert!(covered.contains(&(2, 0)));
+        assert!(!covered.contains(&(1, 1)));
+        assert_eq!(imprint.validate(), Ok(()));
+        assert!(GridImprint::LShape { width: 3, height: 4, thickness: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn shapes_without_area_are_rejected() {
+        assert!(GridImprint::Rectangle { width: 0, height: 2 }.validate().is_err());
+        assert!(GridImprint::Circle { radius: -1 }.validate().is_err());
+    }
+}
@@ -8,6 +8,7 @@ pub mod states;
 pub mod common;
 pub mod utils;
 pub mod persistence;
+pub mod rng;
 
 pub struct LibCorePlugin;
 impl Plugin for LibCorePlugin {
@@ -20,6 +21,7 @@ impl Plugin for LibCorePlugin {
             grids::GridPlugin,
             buildings::BuildingsPlugin,
             persistence::LoadSavePlugin,
+            rng::GameRngPlugin,
         ));
     }
 }
@@ -32,8 +34,9 @@ pub mod prelude {
     pub use crate::states::states_prelude::*;
     pub use crate::common::common_prelude::*;
     pub use crate::persistence::load_save_prelude::*;
+    pub use crate::rng::rng_prelude::*;
     // Re-export the derive macros
-    pub use lib_derive::{Property, SSS};
+    pub use lib_derive::{Persist, PersistSaveData, Property, SSS};
 }
 
 pub mod lib_prelude {
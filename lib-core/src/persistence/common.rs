@@ -1,4 +1,6 @@
 use bevy::ecs::system::ScheduleSystem;
+use rusqlite::OptionalExtension;
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::lib_prelude::*;
@@ -68,34 +70,100 @@ impl GameDbConnection {
     }
 }
 
+pub fn backup_db_path(path: &str) -> String {
+    format!("{path}.bak")
+}
+pub fn temp_db_path(path: &str) -> String {
+    format!("{path}.tmp")
+}
+
+/// Flushes the content of the file at `path` to disk.
+pub fn sync_file(path: &str) -> std::io::Result<()> {
+    std::fs::File::open(path)?.sync_all()
+}
+
+/// Flushes the directory entry of `path`, so a rename or a new file survives a crash.
+/// Directories can't be opened as files on Windows, there it is a no-op.
+pub fn sync_parent_dir(path: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = std::path::Path::new(path).parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(std::path::Path::new("."));
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Runs `PRAGMA integrity_check` on the file at `path`. Any failure to open or query counts as corruption.
+pub fn is_db_intact(path: &str) -> bool {
+    if !std::path::Path::new(path).is_file() { return false; }
+    let result = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(Box::<dyn std::error::Error>::from)
+        .and_then(|conn| conn.check_integrity());
+    if let Err(e) = &result {
+        eprintln!("Database '{}' failed integrity check: {}", path, e);
+    }
+    result.is_ok()
+}
+
 pub mod db_migrations {
     use refinery::embed_migrations;
     embed_migrations!("./migrations");
 }
 
 pub trait GameDbHelpers {
+    fn check_integrity(&self) -> Result<(), Box<dyn std::error::Error>>;
+
     fn register_entity(&self, entity_id: i64) -> rusqlite::Result<usize>;
     fn save_grid_coords(&self, entity_id: i64, pos: GridCoords) -> rusqlite::Result<usize>;
     fn save_world_position(&self, entity_id: i64, pos: Vec2) -> rusqlite::Result<usize>;
     fn save_health(&self, entity_id: i64, current: f32) -> rusqlite::Result<usize>;    
     fn save_grid_imprint(&self, entity_id: i64, imprint: GridImprint) -> rusqlite::Result<usize>;
+    fn save_grid_path(&self, entity_id: i64, path: &VecDeque<GridCoords>, is_current: bool) -> rusqlite::Result<usize>;
     
     fn save_marker(&self, table_name: &str, entity_id: i64) -> rusqlite::Result<usize>;
     fn save_disabled_by_player(&self, entity_id: i64) -> rusqlite::Result<usize>;
     fn save_stat(&self, stat_name: &str, stat_value: f32) -> rusqlite::Result<usize>;
     fn save_stock_resource(&self, resource_name: &str, amount: i32) -> rusqlite::Result<usize>;
     fn save_upgrade_level(&self, entity_id: i64, upgrade_type: &str, level: usize) -> rusqlite::Result<usize>;
+    fn save_timer_elapsed(&self, entity_id: i64, timer_name: &str, elapsed: f32) -> rusqlite::Result<usize>;
+    fn save_tower_top_rotation(&self, entity_id: i64, current_angle: f32) -> rusqlite::Result<usize>;
+    fn save_tower_wisp_target(&self, entity_id: i64, target_wisp_id: i64) -> rusqlite::Result<usize>;
+    fn save_tower_targeting_priority(&self, entity_id: i64, priority: &str) -> rusqlite::Result<usize>;
+    fn save_energy_priority(&self, entity_id: i64, priority: &str) -> rusqlite::Result<usize>;
     
     fn get_grid_coords(&self, entity_id: i64) -> rusqlite::Result<GridCoords>;
     fn get_disabled_by_player(&self, entity_id: i64) -> rusqlite::Result<bool>;
     fn get_world_position(&self, entity_id: i64) -> rusqlite::Result<Vec2>;
     fn get_health(&self, entity_id: i64) -> rusqlite::Result<f32>;
     fn get_grid_imprint(&self, entity_id: i64) -> rusqlite::Result<GridImprint>;
+    fn get_grid_path(&self, entity_id: i64) -> rusqlite::Result<(VecDeque<GridCoords>, bool)>;
     fn get_stat(&self, stat_name: &str) -> rusqlite::Result<f32>;
     fn get_stock_resource(&self, resource_name: &str) -> rusqlite::Result<i32>;
     fn get_upgrade_levels_raw(&self, entity_id: i64) -> rusqlite::Result<Vec<(String, usize)>>;
+    fn get_timer_elapsed(&self, entity_id: i64, timer_name: &str) -> rusqlite::Result<f32>;
+    fn get_tower_top_rotation(&self, entity_id: i64) -> rusqlite::Result<f32>;
+    fn get_tower_wisp_target(&self, entity_id: i64) -> rusqlite::Result<Option<i64>>;
+    fn get_tower_targeting_priority(&self, entity_id: i64) -> rusqlite::Result<Option<String>>;
+    fn get_energy_priority(&self, entity_id: i64) -> rusqlite::Result<Option<String>>;
+    fn count_rows(&self, table_name: &str) -> rusqlite::Result<usize>;
 }
 impl GameDbHelpers for rusqlite::Connection {
+    fn check_integrity(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut stmt = self.prepare("PRAGMA integrity_check")?;
+        let problems = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if problems.len() == 1 && problems[0] == "ok" {
+            Ok(())
+        } else {
+            Err(format!("Integrity check failed: {}", problems.join("; ")).into())
+        }
+    }
+
     fn register_entity(&self, entity_id: i64) -> rusqlite::Result<usize> {
         self.execute(
             "INSERT OR IGNORE INTO entities (id) VALUES (?1)",
@@ -125,16 +193,61 @@ impl GameDbHelpers for rusqlite::Connection {
     }
 
     fn save_grid_imprint(&self, entity_id: i64, imprint: GridImprint) -> rusqlite::Result<usize> {
-        let (shape, width, height) = match imprint {
-            GridImprint::Rectangle { width, height } => ("Rectangle", Some(width), Some(height)),
+        let (shape, width, height, radius, thickness, cells) = match imprint {
+            GridImprint::Rectangle { width, height } => ("Rectangle", Some(width), Some(height), None, None, None),
+            GridImprint::Circle { radius } => ("Circle", None, None, Some(radius), None, None),
+            GridImprint::Mask { width, height, cells } => ("Mask", Some(width), Some(height), None, None, Some(cells as i64)),
+            GridImprint::LShape { width, height, thickness } => ("LShape", Some(width), Some(height), None, Some(thickness), None),
         };
         
         self.execute(
-            "INSERT OR REPLACE INTO grid_imprints (id, shape, width, height) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![entity_id, shape, width, height],
+            "INSERT OR REPLACE INTO grid_imprints (id, shape, width, height, radius, thickness, cells) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![entity_id, shape, width, height, radius, thickness, cells],
+        )
+    }
+
+    fn save_grid_path(&self, entity_id: i64, path: &VecDeque<GridCoords>, is_current: bool) -> rusqlite::Result<usize> {
+        let path_str = path.iter().map(|coords| format!("{},{}", coords.x, coords.y)).collect::<Vec<_>>().join(";");
+        self.execute(
+            "INSERT OR REPLACE INTO grid_paths (entity_id, path, is_current) VALUES (?1, ?2, ?3)",
+            (entity_id, path_str, is_current),
         )
     }
 
+    fn save_timer_elapsed(&self, entity_id: i64, timer_name: &str, elapsed: f32) -> rusqlite::Result<usize> {
+        self.execute(
+            "INSERT OR REPLACE INTO timers (entity_id, timer_name, elapsed) VALUES (?1, ?2, ?3)",
+            (entity_id, timer_name, elapsed),
+        )
+    }
+
+    fn save_tower_top_rotation(&self, entity_id: i64, current_angle: f32) -> rusqlite::Result<usize> {
+        self.execute(
+            "INSERT OR REPLACE INTO tower_top_rotations (entity_id, current_angle) VALUES (?1, ?2)",
+            (entity_id, current_angle),
+        )
+    }
+
+    fn save_tower_wisp_target(&self, entity_id: i64, target_wisp_id: i64) -> rusqlite::Result<usize> {
+        self.execute(
+            "INSERT OR REPLACE INTO tower_wisp_targets (entity_id, target_wisp_id) VALUES (?1, ?2)",
+            (entity_id, target_wisp_id),
+        )
+    }
+
+    fn save_tower_targeting_priority(&self, entity_id: i64, priority: &str) -> rusqlite::Result<usize> {
+        self.execute(
+            "INSERT OR REPLACE INTO tower_targeting_priorities (entity_id, priority) VALUES (?1, ?2)",
+            (entity_id, priority),
+        )
+    }
+
+    fn save_energy_priority(&self, entity_id: i64, priority: &str) -> rusqlite::Result<usize> {
+        self.execute(
+            "INSERT OR REPLACE INTO energy_priorities (entity_id, priority) VALUES (?1, ?2)",
+            (entity_id, priority),
+        )
+    }
 
     /// Save entity of the object in its dedicated table. Calls register_entity()
     fn save_marker(&self, table_name: &str, entity_id: i64) -> rusqlite::Result<usize> {
@@ -214,19 +327,57 @@ impl GameDbHelpers for rusqlite::Connection {
     }
 
     fn get_grid_imprint(&self, entity_id: i64) -> rusqlite::Result<GridImprint> {
-        let mut stmt = self.prepare("SELECT shape, width, height FROM grid_imprints WHERE id = ?1")?;
+        let mut stmt = self.prepare("SELECT shape, width, height, radius, thickness, cells FROM grid_imprints WHERE id = ?1")?;
         let mut rows = stmt.query([entity_id])?;
         let row = rows.next()?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
         
         let shape: String = row.get(0)?;
-        match shape.as_str() {
+        let imprint = match shape.as_str() {
             "Rectangle" => {
                 let width: i32 = row.get(1)?;
                 let height: i32 = row.get(2)?;
                 Ok(GridImprint::Rectangle { width, height })
             },
+            "Circle" => {
+                let radius: i32 = row.get(3)?;
+                Ok(GridImprint::Circle { radius })
+            },
+            "Mask" => {
+                let width: i32 = row.get(1)?;
+                let height: i32 = row.get(2)?;
+                let cells: i64 = row.get(5)?;
+                Ok(GridImprint::Mask { width, height, cells: cells as u64 })
+            },
+            "LShape" => {
+                let width: i32 = row.get(1)?;
+                let height: i32 = row.get(2)?;
+                let thickness: i32 = row.get(4)?;
+                Ok(GridImprint::LShape { width, height, thickness })
+            },
             _ => Err(rusqlite::Error::InvalidColumnType(0, "Unknown shape type".into(), rusqlite::types::Type::Text)),
-        }
+        }?;
+        // E.g. a mask whose bounds hold more cells than its bits
+        imprint.validate().map_err(|e| rusqlite::Error::InvalidColumnType(0, e, rusqlite::types::Type::Text))?;
+        Ok(imprint)
+    }
+
+    /// Returns the path, next step first, and whether it was current when saved.
+    fn get_grid_path(&self, entity_id: i64) -> rusqlite::Result<(VecDeque<GridCoords>, bool)> {
+        let mut stmt = self.prepare("SELECT path, is_current FROM grid_paths WHERE entity_id = ?1")?;
+        let mut rows = stmt.query([entity_id])?;
+        let row = rows.next()?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+        let path_str: String = row.get(0)?;
+        let is_current: bool = row.get(1)?;
+        let malformed = || rusqlite::Error::InvalidColumnType(0, "Malformed grid path".into(), rusqlite::types::Type::Text);
+        let path = path_str.split(';')
+            .filter(|step| !step.is_empty())
+            .map(|step| {
+                let (x, y) = step.split_once(',').ok_or_else(malformed)?;
+                Ok(GridCoords { x: x.parse().map_err(|_| malformed())?, y: y.parse().map_err(|_| malformed())? })
+            })
+            .collect::<rusqlite::Result<VecDeque<_>>>()?;
+        Ok((path, is_current))
     }
 
     fn get_upgrade_levels_raw(&self, entity_id: i64) -> rusqlite::Result<Vec<(String, usize)>> {
@@ -240,6 +391,61 @@ impl GameDbHelpers for rusqlite::Connection {
         }
         Ok(levels)
     }
+
+    /// Timers missing from older saves start from zero.
+    fn get_timer_elapsed(&self, entity_id: i64, timer_name: &str) -> rusqlite::Result<f32> {
+        self.query_row(
+            "SELECT elapsed FROM timers WHERE entity_id = ?1 AND timer_name = ?2",
+            (entity_id, timer_name),
+            |row| row.get(0),
+        ).optional().map(|elapsed| elapsed.unwrap_or(0.))
+    }
+
+    /// Towers missing from older saves start aiming at angle 0.
+    fn get_tower_top_rotation(&self, entity_id: i64) -> rusqlite::Result<f32> {
+        self.query_row(
+            "SELECT current_angle FROM tower_top_rotations WHERE entity_id = ?1",
+            [entity_id],
+            |row| row.get(0),
+        ).optional().map(|angle| angle.unwrap_or(0.))
+    }
+
+    /// Returns the old id of the targeted wisp, still to be remapped through `DbEntityMap`.
+    fn get_tower_wisp_target(&self, entity_id: i64) -> rusqlite::Result<Option<i64>> {
+        self.query_row(
+            "SELECT target_wisp_id FROM tower_wisp_targets WHERE entity_id = ?1",
+            [entity_id],
+            |row| row.get(0),
+        ).optional()
+    }
+
+    fn get_tower_targeting_priority(&self, entity_id: i64) -> rusqlite::Result<Option<String>> {
+        self.query_row(
+            "SELECT priority FROM tower_targeting_priorities WHERE entity_id = ?1",
+            [entity_id],
+            |row| row.get(0),
+        ).optional()
+    }
+
+    fn get_energy_priority(&self, entity_id: i64) -> rusqlite::Result<Option<String>> {
+        self.query_row(
+            "SELECT priority FROM energy_priorities WHERE entity_id = ?1",
+            [entity_id],
+            |row| row.get(0),
+        ).optional()
+    }
+
+    fn count_rows(&self, table_name: &str) -> rusqlite::Result<usize> {
+        let query = format!("SELECT COUNT(*) FROM {}", table_name);
+        self.query_row(&query, [], |row| row.get::<_, i64>(0)).map(|count| count as usize)
+    }
+}
+
+/// Save data of a Builder persisted with `#[derive(Persist)]`. Implemented with `#[derive(PersistSaveData)]`.
+pub trait PersistSaveData: Sized {
+    fn entity(&self) -> Entity;
+    fn save(&self, tx: &rusqlite::Transaction, entity_id: i64) -> rusqlite::Result<()>;
+    fn load(conn: &rusqlite::Connection, entity_map: &DbEntityMap, old_id: i64, new_entity: Entity) -> rusqlite::Result<Self>;
 }
 
 pub trait AppGameLoadSaveExtension {
@@ -257,8 +463,48 @@ impl AppGameLoadSaveExtension for App {
         self
     }
     fn register_db_saver<M>(&mut self, save_system: impl IntoScheduleConfigs<ScheduleSystem, M>) -> &mut Self {
-        self.add_systems(PostUpdate, save_system.run_if(on_message::<SaveGameSignal>));
+        self.add_systems(GameSaveSnapshot, save_system);
         
         self
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrated_db() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        db_migrations::migrations::runner().run(&mut conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn grid_imprint_round_trip() {
+        let conn = migrated_db();
+        let imprints = [
+            GridImprint::Rectangle { width: 2, height: 3 },
+            GridImprint::Circle { radius: 2 },
+            // The top bit turns the stored integer negative
+            GridImprint::Mask { width: 8, height: 8, cells: 1 | 1 << 63 },
+            GridImprint::LShape { width: 3, height: 4, thickness: 1 },
+        ];
+        for (entity_id, imprint) in imprints.into_iter().enumerate() {
+            let entity_id = entity_id as i64;
+            conn.register_entity(entity_id).unwrap();
+            conn.save_grid_imprint(entity_id, imprint).unwrap();
+            let loaded = conn.get_grid_imprint(entity_id).unwrap();
+            assert_eq!(loaded, imprint);
+            assert_eq!(loaded.bounds(), imprint.bounds());
+            assert_eq!(loaded.covered_coords(GridCoords::default()), imprint.covered_coords(GridCoords::default()));
+        }
+    }
+
+    #[test]
+    fn loading_mask_with_more_cells_than_bits_fails() {
+        let conn = migrated_db();
+        conn.register_entity(0).unwrap();
+        conn.save_grid_imprint(0, GridImprint::Mask { width: 9, height: 8, cells: u64::MAX }).unwrap();
+        assert!(conn.get_grid_imprint(0).is_err());
+    }
+}
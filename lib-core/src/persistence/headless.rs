@@ -0,0 +1,99 @@
+//! Synchronous save/load entry points that don't depend on frames, input or rendering.
+//!
+//! The World is expected to come from an App built with `MinimalPlugins`, `bevy::state::app::StatesPlugin`
+//! and the game plugins that register loaders/savers. Only the `StateTransition` and save snapshot schedules are run,
+//! so systems living in `Update` (input, rendering, gameplay) never execute.
+use bevy::ecs::world::CommandQueue;
+
+use crate::lib_prelude::*;
+
+/// Snapshots all registered savers and writes them to `path` before returning.
+/// Meant for tools and tests, so no `SaveCompleted` is sent.
+pub fn save_world_to_path(world: &mut World, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    {
+        let mut save_executor = world.resource_mut::<GameSaveExecutor>();
+        save_executor.save_name = path.into();
+        save_executor.objects_to_save.clear();
+    }
+    world.run_schedule(GameSaveSnapshot);
+
+    let objects = std::mem::take(&mut world.resource_mut::<GameSaveExecutor>().objects_to_save);
+    GameSaveExecutor::write_save(path, objects).result.map_err(|e| e.into())
+}
+
+/// Loads `path` into the world, walking through all `MapLoadingStage`s until the game is `Running`.
+/// Unlike the regular flow, the first loader error aborts the load and is returned.
+/// Issues with single rows don't stop the loaders, but the game is left in `MapLoadingStage::Ready` and an error
+/// is returned; the details are in `LoadReport`, and triggering `LoadReportDecision::Continue` starts the game anyway.
+pub fn load_world_from_path(world: &mut World, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    world.trigger(LoadGameSignal(path.into()));
+    world.flush();
+    world.run_schedule(StateTransition);
+
+    loop {
+        run_loading_tasks_blocking(world)?;
+        let stage = world.resource::<State<MapLoadingStage>>().get().clone();
+        if stage == MapLoadingStage::Ready { break; }
+        world.resource_mut::<NextState<MapLoadingStage>>().set(stage.next());
+        world.run_schedule(StateTransition);
+    }
+    // Apply GameState::Running requested on entering MapLoadingStage::Ready
+    world.run_schedule(StateTransition);
+
+    let issue_count = world.resource::<LoadReport>().issues.len();
+    if issue_count > 0 {
+        return Err(format!("Loading '{}' reported {} issue(s)", path, issue_count).into());
+    }
+    Ok(())
+}
+
+/// Runs all pending `DbLoadingTask`s to completion, applying their commands after each task.
+fn run_loading_tasks_blocking(world: &mut World) -> Result<(), Box<dyn std::error::Error>> {
+    let save_name = world.resource::<GameSaveExecutor>().save_name.clone();
+    loop {
+        let tasks = world.query::<(Entity, &DbLoadingTask)>()
+            .iter(world)
+            .map(|(entity, task)| (entity, task.clone()))
+            .collect::<Vec<_>>();
+        if tasks.is_empty() { return Ok(()); }
+
+        for (task_entity, mut task) in tasks {
+            let mut queue = CommandQueue::default();
+            let mut loaded = 0;
+            let result = {
+                let mut commands = Commands::new(&mut queue, world);
+                let entity_map = world.resource::<DbEntityMap>();
+                GameDbConnection::with_db_connection(&save_name, |conn| {
+                    loop {
+                        let mut ctx = LoadContext {
+                            conn,
+                            commands: &mut commands,
+                            entity_map,
+                            pagination: task.pagination,
+                            loader_name: task.loader_name,
+                            stage: task.stage.clone(),
+                        };
+                        let load_result = (task.loader)(&mut ctx)?;
+                        loaded += task.progress_for(&load_result);
+                        match load_result {
+                            LoadResult::Finished => return Ok(()),
+                            LoadResult::Progressed(count) => task.pagination.offset += count,
+                        }
+                    }
+                })
+            };
+            queue.apply(world);
+            world.despawn(task_entity);
+            world.resource_mut::<LoadingProgress>().add_loaded(&task.stage, loaded);
+            if let Err(e) = result {
+                world.resource_mut::<LoadReport>().push(LoadIssue {
+                    loader: task.loader_name,
+                    stage: task.stage.clone(),
+                    row_id: None,
+                    error: e.to_string(),
+                });
+                return Err(e);
+            }
+        }
+    }
+}
@@ -7,25 +7,33 @@ impl Plugin for MapLoadPlugin {
         app
             .init_resource::<DbEntityMap>()
             .init_resource::<GameLoadRegistry>()
+            .init_resource::<LoadReport>()
+            .init_resource::<LoadingProgress>()
+            .add_systems(OnEnter(MapLoadingStage::Init), LoadingProgress::reset)
             .add_systems(OnEnter(MapLoadingStage::LoadMapInfo), spawn_loading_tasks)
             .add_systems(OnEnter(MapLoadingStage::LoadResources), spawn_loading_tasks)
             .add_systems(OnEnter(MapLoadingStage::SpawnMapElements), spawn_loading_tasks)
-            .add_systems(OnEnter(MapLoadingStage::Ready), |mut commands: Commands, mut next_game_state: ResMut<NextState<GameState>>| { 
-                commands.trigger(DynamicGameEvent::game_started());
-                next_game_state.set(GameState::Running); 
-            })
+            .add_systems(OnEnter(MapLoadingStage::Ready), LoadReport::on_ready)
             .add_systems(Update, (
                 progress_map_loading_state.run_if(in_state(GameState::Loading)),
                 process_loading_tasks_system,
+                LoadReport::on_save_completed,
                 LoadGameSignal::emit.run_if(input_just_released(KeyCode::KeyA)),
             ))
             .add_observer(LoadGameSignal::on_trigger)
+            .add_observer(LoadReportDecision::on_trigger)
             .register_db_loader::<PopulateDbEntityMapTask>(MapLoadingStage::LoadMapInfo);
     }
 }
 
 pub trait Loadable {
     fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult>;
+
+    /// Number of rows `load` is going to page through, used for `LoadingProgress`.
+    /// Loaders returning `None` count as a single row finished at once.
+    fn total_rows(_conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        Ok(None)
+    }
 }
 
 #[derive(Event)]
@@ -35,34 +43,70 @@ impl LoadGameSignal {
         mut commands: Commands,
     ) {
         println!("Loading game");
-        commands.trigger(LoadGameSignal("test_save.dwd".into()));
+        commands.trigger(LoadGameSignal(slot_path(QUICKSAVE_SLOT)));
     }
     fn on_trigger(
         trigger: On<LoadGameSignal>,
         mut commands: Commands,
+        game_state: Res<State<GameState>>,
         mut next_game_state: ResMut<NextState<GameState>>,
         mut next_ui_state: ResMut<NextState<UiInteraction>>,
         mut next_map_loading_stage: ResMut<NextState<MapLoadingStage>>,
         mut save_executor: ResMut<GameSaveExecutor>,
+        mut load_report: ResMut<LoadReport>,
         map_bound_entities: Query<Entity, With<MapBound>>,
     ) {
-        save_executor.save_name = trigger.event().0.clone();
+        // Snapshot the map being left so a broken load can be aborted. Queued before the despawns below.
+        // Only collecting the snapshot happens here, the file is written in the background while the new map loads.
+        // The very first load has nothing to snapshot, its `Abort` goes back to the main menu instead.
+        if matches!(game_state.get(), GameState::Running | GameState::Paused) {
+            commands.queue(LoadReport::write_restore_point);
+        }
+
+        save_executor.save_name = resolve_load_path(&trigger.event().0);
+        load_report.path = save_executor.save_name.clone();
+        load_report.issues.clear();
+        load_report.is_abort_pending = false;
         
         // Run migrations synchronously on main thread before parallel loading starts
-        GameDbConnection::with_db_connection(&save_executor.save_name, |conn| {
+        let migration_result = GameDbConnection::with_db_connection(&save_executor.save_name, |conn| {
             db_migrations::migrations::runner().run(conn)?;
             Ok(())
-        }).expect("Failed to run migrations on load");
+        });
         
         next_game_state.set(GameState::Loading);
-        next_map_loading_stage.set(MapLoadingStage::Init);
         next_ui_state.set(UiInteraction::Free);
+        match migration_result {
+            Ok(()) => next_map_loading_stage.set(MapLoadingStage::Init),
+            Err(e) => {
+                // Loaders can't make sense of a database that isn't migrated, go straight to the load report
+                load_report.push(LoadIssue {
+                    loader: "Migrations",
+                    stage: MapLoadingStage::Init,
+                    row_id: None,
+                    error: e.to_string(),
+                });
+                next_map_loading_stage.set(MapLoadingStage::Ready);
+            }
+        }
 
         // Despawn all existing map elements
         map_bound_entities.iter().for_each(|entity| commands.entity(entity).despawn());
     }
 }
 
+/// Falls back to the backup written by the previous save when the requested file is corrupted.
+pub fn resolve_load_path(path: &str) -> String {
+    if is_db_intact(path) { return path.to_string(); }
+    let backup = backup_db_path(path);
+    if is_db_intact(&backup) {
+        println!("Loading backup '{}' instead of '{}'", backup, path);
+        backup
+    } else {
+        path.to_string()
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct DbEntityMap {
     pub map: HashMap<i64, Entity>,
@@ -98,30 +142,284 @@ pub struct LoadContext<'a, 'w, 's> {
     pub commands: &'a mut Commands<'w, 's>,
     pub entity_map: &'a DbEntityMap,
     pub pagination: Pagination,
+    pub loader_name: &'static str,
+    pub stage: MapLoadingStage,
 }
 impl<'a, 'w, 's> LoadContext<'a, 'w, 's> {
     pub fn get_new_entity_for_old(&self, old_id: i64) -> Option<Entity> {
         self.entity_map.map.get(&old_id).copied()
     }
+
+    /// Like `get_new_entity_for_old`, but reports ids that are missing from the `entities` table.
+    pub fn expect_new_entity_for_old(&mut self, old_id: i64) -> Option<Entity> {
+        let new_entity = self.get_new_entity_for_old(old_id);
+        if new_entity.is_none() {
+            self.report_issue(Some(old_id), "Entity id has no corresponding entry in the entities table");
+        }
+        new_entity
+    }
+
+    /// Turns a missing row of a per-entity table (e.g. `grid_coords`) into a reported issue instead of failing the whole loader.
+    pub fn expect_row<T>(&mut self, old_id: i64, table: &str, result: rusqlite::Result<T>) -> rusqlite::Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                self.report_issue(Some(old_id), format!("Entity id has no row in '{}'", table));
+                Ok(None)
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Records inconsistent data in the `LoadReport`. The loader is expected to skip the row and carry on.
+    pub fn report_issue(&mut self, row_id: Option<i64>, error: impl Into<String>) {
+        let issue = LoadIssue {
+            loader: self.loader_name,
+            stage: self.stage.clone(),
+            row_id,
+            error: error.into(),
+        };
+        self.commands.queue(move |world: &mut World| world.resource_mut::<LoadReport>().push(issue));
+    }
 }
 
 pub type LoaderFn = fn(&mut LoadContext) -> rusqlite::Result<LoadResult>;
+pub type TotalRowsFn = fn(&rusqlite::Connection) -> rusqlite::Result<Option<usize>>;
+
+#[derive(Clone, Copy)]
+pub struct RegisteredLoader {
+    pub name: &'static str,
+    pub load: LoaderFn,
+    pub total_rows: TotalRowsFn,
+}
+impl RegisteredLoader {
+    /// Rows the loader is expected to report as loaded, falling back to a single row when unknown.
+    pub fn expected_rows(&self, conn: &rusqlite::Connection) -> usize {
+        match (self.total_rows)(conn) {
+            Ok(Some(total)) => total,
+            Ok(None) => 1,
+            Err(e) => {
+                eprintln!("Failed to count rows for {}: {}", self.name, e);
+                1
+            }
+        }
+    }
+}
 
 #[derive(Resource, Default)]
 pub struct GameLoadRegistry {
-    pub loaders: HashMap<MapLoadingStage, Vec<LoaderFn>>,
+    pub loaders: HashMap<MapLoadingStage, Vec<RegisteredLoader>>,
 }
 
 impl GameLoadRegistry {
     pub fn register<T: Loadable>(&mut self, phase: MapLoadingStage) {
-        self.loaders.entry(phase).or_default().push(T::load);
+        let name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+        self.loaders.entry(phase).or_default().push(RegisteredLoader { name, load: T::load, total_rows: T::total_rows });
     }
 }
 
 #[derive(Component, Clone)]
 pub struct DbLoadingTask {
+    pub loader_name: &'static str,
+    pub stage: MapLoadingStage,
     pub loader: LoaderFn,
     pub pagination: Pagination,
+    pub expected_rows: usize,
+}
+impl DbLoadingTask {
+    /// Rows to add to `LoadingProgress` for `result`. Must be called before advancing the pagination.
+    pub fn progress_for(&self, result: &LoadResult) -> usize {
+        let remaining = self.expected_rows.saturating_sub(self.pagination.offset);
+        match result {
+            LoadResult::Progressed(count) => remaining.min(*count),
+            LoadResult::Finished => remaining,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StageProgress {
+    pub loaded: usize,
+    pub total: usize,
+}
+impl StageProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 { return 1.; }
+        self.loaded.min(self.total) as f32 / self.total as f32
+    }
+}
+
+/// Loaded and total rows of every `MapLoadingStage`. Totals of all stages are counted when loading starts.
+#[derive(Resource, Default, Debug)]
+pub struct LoadingProgress {
+    pub stages: HashMap<MapLoadingStage, StageProgress>,
+}
+impl LoadingProgress {
+    pub fn stage(&self, stage: &MapLoadingStage) -> StageProgress {
+        self.stages.get(stage).copied().unwrap_or_default()
+    }
+
+    pub fn overall(&self) -> StageProgress {
+        self.stages.values().fold(StageProgress::default(), |acc, stage| StageProgress {
+            loaded: acc.loaded + stage.loaded.min(stage.total),
+            total: acc.total + stage.total,
+        })
+    }
+
+    pub fn add_loaded(&mut self, stage: &MapLoadingStage, count: usize) {
+        if let Some(progress) = self.stages.get_mut(stage) {
+            progress.loaded += count;
+        }
+    }
+
+    fn reset(
+        mut progress: ResMut<LoadingProgress>,
+        registry: Res<GameLoadRegistry>,
+        save_executor: Res<GameSaveExecutor>,
+    ) {
+        progress.stages.clear();
+        let result = GameDbConnection::with_db_connection(&save_executor.save_name, |conn| {
+            for (stage, loaders) in registry.loaders.iter() {
+                let total = loaders.iter().map(|loader| loader.expected_rows(conn)).sum();
+                progress.stages.insert(stage.clone(), StageProgress { loaded: 0, total });
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to count rows to load: {}", e);
+        }
+    }
+}
+
+/// Problem found while loading a save, either a failed loader or a row with inconsistent data.
+#[derive(Clone, Debug)]
+pub struct LoadIssue {
+    pub loader: &'static str,
+    pub stage: MapLoadingStage,
+    pub row_id: Option<i64>,
+    pub error: String,
+}
+impl std::fmt::Display for LoadIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.row_id {
+            Some(row_id) => write!(f, "[{:?}] {} (row {}): {}", self.stage, self.loader, row_id, self.error),
+            None => write!(f, "[{:?}] {}: {}", self.stage, self.loader, self.error),
+        }
+    }
+}
+
+/// All issues of the current load. When it isn't empty, loading stops at `MapLoadingStage::Ready`
+/// until a `LoadReportDecision` is triggered.
+#[derive(Resource, Default)]
+pub struct LoadReport {
+    pub path: String,
+    pub issues: Vec<LoadIssue>,
+    /// Snapshot of the map played before the current load, if there was any.
+    pub restore_point: Option<String>,
+    /// `Abort` was chosen while the restore point was still being written.
+    is_abort_pending: bool,
+}
+impl LoadReport {
+    pub const RESTORE_POINT_PATH: &'static str = "saves/restore_point.db";
+
+    pub fn push(&mut self, issue: LoadIssue) {
+        eprintln!("Load issue: {}", issue);
+        self.issues.push(issue);
+    }
+
+    /// Collects a snapshot of the current map and hands it to the `GameSaveExecutor` for writing.
+    fn write_restore_point(world: &mut World) {
+        // Keep a regular save that may be collected but not yet handed over
+        let pending_objects = std::mem::take(&mut world.resource_mut::<GameSaveExecutor>().objects_to_save);
+        world.run_schedule(GameSaveSnapshot);
+        let mut save_executor = world.resource_mut::<GameSaveExecutor>();
+        let objects = std::mem::replace(&mut save_executor.objects_to_save, pending_objects);
+        let completed = save_executor.submit(Self::RESTORE_POINT_PATH.to_string(), objects);
+
+        let mut load_report = world.resource_mut::<LoadReport>();
+        load_report.restore_point = Some(Self::RESTORE_POINT_PATH.to_string());
+        if let Some(completed) = completed {
+            load_report.on_restore_point_written(&completed);
+        }
+    }
+
+    fn on_restore_point_written(&mut self, completed: &SaveCompleted) {
+        if completed.path != Self::RESTORE_POINT_PATH { return; }
+        if let Err(e) = &completed.result {
+            eprintln!("Failed to write restore point: {}", e);
+            self.restore_point = None;
+        }
+    }
+
+    /// Tracks the background write of the restore point and carries out an `Abort` that waited for it.
+    fn on_save_completed(
+        mut commands: Commands,
+        mut load_report: ResMut<LoadReport>,
+        mut completed_reader: MessageReader<SaveCompleted>,
+        save_executor: Res<GameSaveExecutor>,
+    ) {
+        for completed in completed_reader.read() {
+            load_report.on_restore_point_written(completed);
+        }
+        if load_report.is_abort_pending && !save_executor.is_writing(Self::RESTORE_POINT_PATH) {
+            load_report.is_abort_pending = false;
+            commands.trigger(LoadReportDecision::Abort);
+        }
+    }
+
+    fn on_ready(
+        mut commands: Commands,
+        load_report: Res<LoadReport>,
+    ) {
+        if load_report.issues.is_empty() {
+            commands.trigger(LoadReportDecision::Continue);
+        } else {
+            println!("Loading '{}' finished with {} issue(s), waiting for a decision", load_report.path, load_report.issues.len());
+        }
+    }
+}
+
+/// How to proceed with a load that reported issues.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum LoadReportDecision {
+    /// Start the game with whatever got loaded.
+    Continue,
+    /// Discard the loaded map and load the restore point instead.
+    /// Without a restore point (the first load of the session) the main menu is opened over an empty map.
+    Abort,
+}
+impl LoadReportDecision {
+    fn on_trigger(
+        trigger: On<LoadReportDecision>,
+        mut commands: Commands,
+        mut load_report: ResMut<LoadReport>,
+        save_executor: Res<GameSaveExecutor>,
+        mut next_game_state: ResMut<NextState<GameState>>,
+        mut next_ui_state: ResMut<NextState<UiInteraction>>,
+        map_bound_entities: Query<Entity, With<MapBound>>,
+    ) {
+        match trigger.event() {
+            LoadReportDecision::Continue => {
+                commands.trigger(DynamicGameEvent::game_started());
+                next_game_state.set(GameState::Running);
+            },
+            LoadReportDecision::Abort => {
+                if load_report.restore_point.is_some() && save_executor.is_writing(LoadReport::RESTORE_POINT_PATH) {
+                    println!("Aborting load of '{}' once the restore point is written", load_report.path);
+                    load_report.is_abort_pending = true;
+                    return;
+                }
+                let Some(restore_point) = &load_report.restore_point else {
+                    println!("Aborting load of '{}', there is no previous map to restore", load_report.path);
+                    map_bound_entities.iter().for_each(|entity| commands.entity(entity).despawn());
+                    next_ui_state.set(UiInteraction::MainMenu);
+                    return;
+                };
+                println!("Aborting load of '{}', restoring previous map", load_report.path);
+                commands.trigger(LoadGameSignal(restore_point.clone()));
+            },
+        }
+    }
 }
 
 struct PopulateDbEntityMapTask;
@@ -143,6 +441,10 @@ impl Loadable for PopulateDbEntityMapTask {
         ctx.commands.insert_resource(DbEntityMap { map });
         Ok(LoadResult::Finished)
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("entities").map(Some)
+    }
 }
 
 pub fn process_loading_tasks_system(
@@ -156,6 +458,7 @@ pub fn process_loading_tasks_system(
 
     tasks.par_iter_mut().for_each(|(entity, mut task)| {
         par_commands.command_scope(|mut cmd| {
+             let mut loaded = 0;
              let _ = GameDbConnection::with_db_connection(&save_executor.save_name, |conn| {
                  loop {
                      // Check global system budget
@@ -168,9 +471,15 @@ pub fn process_loading_tasks_system(
                          commands: &mut cmd,
                          entity_map: &entity_map,
                          pagination: task.pagination,
+                         loader_name: task.loader_name,
+                         stage: task.stage.clone(),
                      };
                      
-                     match (task.loader)(&mut ctx) {
+                     let result = (task.loader)(&mut ctx);
+                     if let Ok(load_result) = &result {
+                         loaded += task.progress_for(load_result);
+                     }
+                     match result {
                          Ok(LoadResult::Finished) => {
                              cmd.entity(entity).despawn();
                              break; // Task done
@@ -180,7 +489,13 @@ pub fn process_loading_tasks_system(
                              // Continue loop to process more if time permits
                          },
                          Err(e) => {
-                             eprintln!("Loading task failed: {}", e);
+                             let issue = LoadIssue {
+                                 loader: task.loader_name,
+                                 stage: task.stage.clone(),
+                                 row_id: None,
+                                 error: e.to_string(),
+                             };
+                             cmd.queue(move |world: &mut World| world.resource_mut::<LoadReport>().push(issue));
                              cmd.entity(entity).despawn(); // Stop on error
                              break;
                          }
@@ -188,6 +503,10 @@ pub fn process_loading_tasks_system(
                  }
                  Ok(())
              });
+             if loaded > 0 {
+                 let stage = task.stage.clone();
+                 cmd.queue(move |world: &mut World| world.resource_mut::<LoadingProgress>().add_loaded(&stage, loaded));
+             }
         });
     });
 }
@@ -198,7 +517,8 @@ fn progress_map_loading_state(
     mut next_stage: ResMut<NextState<MapLoadingStage>>,
     loading_tasks: Query<(), With<DbLoadingTask>>,
 ) {
-    if !loading_tasks.is_empty() { return; }
+    // Stays in Ready while the player decides what to do with the LoadReport
+    if !loading_tasks.is_empty() || *stage.get() == MapLoadingStage::Ready { return; }
     let next = stage.get().next();
     println!("All loading tasks completed, moving to next stage: {:?}", next);
     next_stage.set(next);
@@ -207,16 +527,24 @@ fn progress_map_loading_state(
 fn spawn_loading_tasks(
     mut commands: Commands,
     registry: Res<GameLoadRegistry>,
+    save_executor: Res<GameSaveExecutor>,
     stage: ResMut<State<MapLoadingStage>>,
 ) {
     println!("Spawning loading tasks for phase: {:?}", stage.get());
     let target_phase = stage.get();
-    if let Some(loaders) = registry.loaders.get(target_phase) {
-        for loader in loaders {
-            commands.spawn(DbLoadingTask {
-                loader: *loader,
-                pagination: Pagination { limit: 100, offset: 0 },
-            });
-        }
+    let Some(loaders) = registry.loaders.get(target_phase) else { return; };
+    let mut expected_rows = vec![1; loaders.len()];
+    let _ = GameDbConnection::with_db_connection(&save_executor.save_name, |conn| {
+        expected_rows = loaders.iter().map(|loader| loader.expected_rows(conn)).collect();
+        Ok(())
+    });
+    for (loader, expected_rows) in loaders.iter().zip(expected_rows) {
+        commands.spawn(DbLoadingTask {
+            loader_name: loader.name,
+            stage: target_phase.clone(),
+            loader: loader.load,
+            pagination: Pagination { limit: 100, offset: 0 },
+            expected_rows,
+        });
     }
 }
@@ -1,10 +1,13 @@
 use bevy::app::{App, Plugin};
 
 pub mod common;
+pub mod headless;
 pub mod load;
 pub mod save;
+pub mod slots;
 
 pub use rusqlite;
+pub use headless::{load_world_from_path, save_world_to_path};
 
 pub struct LoadSavePlugin;
 impl Plugin for LoadSavePlugin {
@@ -12,6 +15,7 @@ impl Plugin for LoadSavePlugin {
         app.add_plugins((
             load::MapLoadPlugin,
             save::MapSavePlugin,
+            slots::SaveSlotsPlugin,
         ));
     }
 }
@@ -19,5 +23,6 @@ pub mod load_save_prelude {
     pub use super::common::*;
     pub use super::load::*;
     pub use super::save::*;
+    pub use super::slots::*;
     pub use super::rusqlite;
 }
@@ -1,5 +1,9 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
 use crate::lib_prelude::*;
-use super::common::increment_db_generation;
+use super::common::{backup_db_path, increment_db_generation, sync_file, sync_parent_dir, temp_db_path};
+use bevy::ecs::schedule::ScheduleLabel;
 use bevy::input::common_conditions::input_just_released;
 
 pub struct MapSavePlugin;
@@ -8,16 +12,35 @@ impl Plugin for MapSavePlugin {
         app
             .init_resource::<GameSaveExecutor>()
             .add_message::<SaveGameSignal>()
+            .add_message::<SaveCompleted>()
+            .init_schedule(GameSaveSnapshot)
             .add_systems(Update, (
                 SaveGameSignal::emit.run_if(input_just_released(KeyCode::KeyZ)),
             ))
+            .add_systems(PostUpdate, (
+                run_game_save_snapshot.run_if(on_message::<SaveGameSignal>),
+            ))
             .add_systems(Last, (
                 GameSaveExecutor::on_game_save.run_if(on_message::<SaveGameSignal>),
-            ))
+                GameSaveExecutor::poll_background_save,
+            ).chain())
             ;
     }
 }
 
+/// Schedule holding all systems registered via `register_db_saver`.
+/// Runs in PostUpdate on `SaveGameSignal`, or directly from `save_world_to_path`.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameSaveSnapshot;
+
+fn run_game_save_snapshot(world: &mut World) {
+    let Some(SaveGameSignal(save_name)) = world.resource::<Messages<SaveGameSignal>>().iter_current_update_messages().last().cloned() else { return; };
+    let mut save_executor = world.resource_mut::<GameSaveExecutor>();
+    save_executor.save_name = save_name;
+    save_executor.objects_to_save.clear();
+    world.run_schedule(GameSaveSnapshot);
+}
+
 pub trait Saveable: SSS {
     fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()>;
 }
@@ -26,16 +49,14 @@ pub trait SaveableBatch: SSS {
 }
 
 
-#[derive(Message)]
-pub struct SaveGameSignal;
+/// Requests a save into the given path. When several are sent within a frame, the last one wins.
+#[derive(Message, Clone)]
+pub struct SaveGameSignal(pub String);
 impl SaveGameSignal {
     fn emit(
-        mut save_executor: ResMut<GameSaveExecutor>,
         mut writer: MessageWriter<SaveGameSignal>,
     ) {
-        save_executor.save_name = "test_save.dwd".into();
-        save_executor.objects_to_save.clear();
-        writer.write(SaveGameSignal);
+        writer.write(SaveGameSignal(slot_path(QUICKSAVE_SLOT)));
     }
 }
 
@@ -71,56 +92,189 @@ impl<T: Saveable> Command for SaveableBatchCommand<T> {
         buffer.objects_to_save.push(Box::new(self));
     }
 }
+/// Where `GameSaveExecutor::on_game_save` writes the database.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SaveExecution {
+    /// Write on a separate thread to avoid frame hitches.
+    #[default]
+    Background,
+    /// Write on the calling thread and finish before the system returns. Used by tests and tools.
+    Blocking,
+}
+
+/// Outcome of a save, delivered on the main thread once the database is written (or writing failed).
+#[derive(Message, Clone, Debug)]
+pub struct SaveCompleted {
+    pub path: String,
+    pub result: Result<(), String>,
+    pub duration: Duration,
+    /// Number of rows in the `entities` table of the written save
+    pub entity_count: usize,
+}
+
+/// Snapshot waiting for the in-flight save to finish, so two saves never write the same file concurrently.
+/// At most one is queued per path.
+struct QueuedSave {
+    save_name: String,
+    objects: Vec<Box<dyn SaveableBatch>>,
+}
+
+struct InFlightSave {
+    save_name: String,
+    started_at: Instant,
+    handle: std::thread::JoinHandle<SaveCompleted>,
+}
+
 #[derive(Resource, Default)]
 pub struct GameSaveExecutor {
     pub save_name: String,
+    pub execution: SaveExecution,
     pub objects_to_save: Vec<Box<dyn SaveableBatch>>,
+    in_flight: Option<InFlightSave>,
+    queued: VecDeque<QueuedSave>,
 }
 impl GameSaveExecutor {
     pub fn on_game_save(
         mut save_executor: ResMut<GameSaveExecutor>,
+        mut completed_writer: MessageWriter<SaveCompleted>,
     ) {
         if save_executor.objects_to_save.is_empty() {
             return;
         }
-        
-        let objects = std::mem::take(&mut save_executor.objects_to_save);
+
         let save_name = save_executor.save_name.clone();
+        let objects = std::mem::take(&mut save_executor.objects_to_save);
+        if let Some(completed) = save_executor.submit(save_name, objects) {
+            completed_writer.write(completed);
+        }
+    }
 
-        std::thread::spawn(move || {
-            fn save_process(save_name: &str, objects: Vec<Box<dyn SaveableBatch>>) -> Result<(), Box<dyn std::error::Error>> {
-                if std::path::Path::new(save_name).exists() {
-                    println!("Removing existing save file '{}'", save_name);
-                    std::fs::remove_file(save_name)?;
+    /// Writes a snapshot into `save_name` the way `execution` says. Blocking writes return their outcome,
+    /// background ones report it through `SaveCompleted` once they are done.
+    pub fn submit(&mut self, save_name: String, objects: Vec<Box<dyn SaveableBatch>>) -> Option<SaveCompleted> {
+        let save = QueuedSave { save_name, objects };
+        match self.execution {
+            SaveExecution::Background => {
+                if self.is_save_in_flight() {
+                    // A newer snapshot for the same path replaces the waiting one, so the queue holds one save per path
+                    if let Some(queued) = self.queued.iter_mut().find(|queued| queued.save_name == save.save_name) {
+                        println!("Save to '{}' replaced the one already queued", save.save_name);
+                        queued.objects = save.objects;
+                    } else {
+                        println!("Save to '{}' queued, another save is still in progress", save.save_name);
+                        self.queued.push_back(save);
+                    }
+                } else {
+                    self.start_background_save(save);
                 }
-                
-                GameDbConnection::with_db_connection(save_name, |conn| {
-                    // Run migrations
-                    db_migrations::migrations::runner().run(conn)?;
+                None
+            }
+            SaveExecution::Blocking => Some(Self::write_save(&save.save_name, save.objects)),
+        }
+    }
 
-                    // Start transaction
-                    let tx = conn.transaction()?;
+    pub fn is_save_in_flight(&self) -> bool {
+        self.in_flight.is_some()
+    }
 
-                    // Save all objects
-                    for batch in objects {
-                        batch.save_batch(&tx)?;
-                    }
+    /// A background save into `save_name` is running or waiting in the queue.
+    pub fn is_writing(&self, save_name: &str) -> bool {
+        self.in_flight.as_ref().is_some_and(|in_flight| in_flight.save_name == save_name)
+            || self.queued.iter().any(|queued| queued.save_name == save_name)
+    }
+
+    fn start_background_save(&mut self, save: QueuedSave) {
+        let save_name = save.save_name.clone();
+        let handle = std::thread::spawn(move || Self::write_save(&save.save_name, save.objects));
+        self.in_flight = Some(InFlightSave { save_name, started_at: Instant::now(), handle });
+    }
+
+    /// Reports the finished background save and starts the next queued one.
+    fn poll_background_save(
+        mut save_executor: ResMut<GameSaveExecutor>,
+        mut completed_writer: MessageWriter<SaveCompleted>,
+    ) {
+        let Some(in_flight) = &save_executor.in_flight else { return; };
+        if !in_flight.handle.is_finished() { return; }
+
+        let in_flight = save_executor.in_flight.take().unwrap();
+        let completed = in_flight.handle.join().unwrap_or_else(|_| SaveCompleted {
+            path: in_flight.save_name,
+            result: Err("Save thread panicked".to_string()),
+            duration: in_flight.started_at.elapsed(),
+            entity_count: 0,
+        });
+        completed_writer.write(completed);
+
+        if let Some(next_save) = save_executor.queued.pop_front() {
+            save_executor.start_background_save(next_save);
+        }
+    }
+
+    /// Writes given batches into a fresh database at `save_name`, replacing any existing file.
+    /// The database is built in a temporary file and only renamed over `save_name` once it passes the integrity check.
+    /// The replaced save is kept as a backup next to it. Both files and the rename are synced to disk before returning.
+    pub fn write_save(save_name: &str, objects: Vec<Box<dyn SaveableBatch>>) -> SaveCompleted {
+        fn save_process(save_name: &str, objects: Vec<Box<dyn SaveableBatch>>) -> Result<usize, Box<dyn std::error::Error>> {
+            if let Some(parent) = std::path::Path::new(save_name).parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let temp_name = temp_db_path(save_name);
+            if std::path::Path::new(&temp_name).exists() {
+                println!("Removing leftover temporary save file '{}'", temp_name);
+                std::fs::remove_file(&temp_name)?;
+            }
+
+            let entity_count = {
+                let mut conn = rusqlite::Connection::open(&temp_name)?;
+
+                // Run migrations
+                db_migrations::migrations::runner().run(&mut conn)?;
+
+                // Start transaction
+                let tx = conn.transaction()?;
+
+                // Save all objects
+                for batch in objects {
+                    batch.save_batch(&tx)?;
+                }
+                SaveMetadata::write(&tx)?;
+
+                // Commit transaction
+                tx.commit()?;
 
-                    // Commit transaction
-                    tx.commit()?;
-                    Ok(())
-                })?;
-                
-                Ok(())
+                conn.check_integrity()?;
+                conn.query_row("SELECT COUNT(*) FROM entities", [], |row| row.get::<_, i64>(0))? as usize
+            };
+
+            // The new database has to be on disk before it replaces the old one
+            sync_file(&temp_name)?;
+
+            // Keep the previous save as a backup, then atomically swap in the new one
+            if std::path::Path::new(save_name).exists() {
+                let backup_name = backup_db_path(save_name);
+                std::fs::copy(save_name, &backup_name)?;
+                sync_file(&backup_name)?;
             }
+            std::fs::rename(&temp_name, save_name)?;
+            sync_parent_dir(save_name)?;
+            Ok(entity_count)
+        }
 
-            if let Err(e) = save_process(&save_name, objects) {
+        let started_at = Instant::now();
+        let result = save_process(save_name, objects);
+        let duration = started_at.elapsed();
+        match result {
+            Err(e) => {
                 eprintln!("Failed to save game: {}", e);
-            } else {
-                println!("Game saved successfully to '{}'", save_name);
+                let _ = std::fs::remove_file(temp_db_path(save_name));
+                SaveCompleted { path: save_name.to_string(), result: Err(e.to_string()), duration, entity_count: 0 }
+            },
+            Ok(entity_count) => {
+                println!("Game saved successfully to '{}' ({} entities in {:?})", save_name, entity_count, duration);
                 increment_db_generation();
+                SaveCompleted { path: save_name.to_string(), result: Ok(()), duration, entity_count }
             }
-        });
+        }
     }
 }
-
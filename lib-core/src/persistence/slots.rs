@@ -0,0 +1,231 @@
+//! Named save slots living in `SAVES_DIR`, the rotating autosave and the metadata stored with every save.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::lib_prelude::*;
+use super::common::{backup_db_path, increment_db_generation};
+
+pub struct SaveSlotsPlugin;
+impl Plugin for SaveSlotsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Playtime>()
+            .init_resource::<Autosave>()
+            .add_systems(OnEnter(MapLoadingStage::Init), |mut commands: Commands, mut autosave: ResMut<Autosave>| {
+                commands.insert_resource(Playtime::default());
+                autosave.elapsed = Duration::ZERO;
+            })
+            .add_systems(Update, (
+                (
+                    Playtime::tick,
+                    Autosave::tick,
+                ).run_if(in_state(GameState::Running)),
+            ))
+            .register_db_loader::<Playtime>(MapLoadingStage::LoadResources)
+            .register_db_saver(Playtime::on_game_save)
+            ;
+    }
+}
+
+pub const SAVES_DIR: &str = "saves";
+pub const SAVE_EXTENSION: &str = "dwd";
+pub const QUICKSAVE_SLOT: &str = "quicksave";
+pub const AUTOSAVE_SLOT_PREFIX: &str = "autosave_";
+
+pub fn slot_path(slot_name: &str) -> String {
+    format!("{SAVES_DIR}/{slot_name}.{SAVE_EXTENSION}")
+}
+
+/// Slot names end up as file names, so only a conservative set of characters is allowed.
+pub fn is_valid_slot_name(slot_name: &str) -> bool {
+    !slot_name.trim().is_empty()
+        && slot_name.len() <= 64
+        && slot_name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ' '))
+}
+
+#[derive(Clone, Debug)]
+pub struct SaveSlot {
+    pub name: String,
+    pub path: String,
+    pub metadata: Option<SaveMetadata>,
+}
+
+/// Lists all slots in `SAVES_DIR`, most recent first. Slots without metadata go last.
+pub fn list_save_slots() -> Vec<SaveSlot> {
+    let mut slots = std::fs::read_dir(SAVES_DIR)
+        .ok()
+        .into_iter()
+        .flat_map(|rd| rd.filter_map(|e| e.ok()))
+        .filter_map(|e| {
+            let path = e.path();
+            if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some(SAVE_EXTENSION) { return None; }
+            let name = path.file_stem()?.to_str()?.to_string();
+            let path = path.to_str()?.to_string();
+            let metadata = SaveMetadata::read(&path).unwrap_or_else(|e| {
+                eprintln!("Failed to read metadata of save '{}': {}", path, e);
+                None
+            });
+            Some(SaveSlot { name, path, metadata })
+        })
+        .collect::<Vec<_>>();
+    slots.sort_by(|a, b| {
+        let a_time = a.metadata.as_ref().map(|m| m.saved_at);
+        let b_time = b.metadata.as_ref().map(|m| m.saved_at);
+        b_time.cmp(&a_time).then_with(|| a.name.cmp(&b.name))
+    });
+    slots
+}
+
+pub fn delete_save_slot(slot_name: &str) -> std::io::Result<()> {
+    let path = slot_path(slot_name);
+    std::fs::remove_file(&path)?;
+    let _ = std::fs::remove_file(backup_db_path(&path));
+    // Drop cached connections that may still point to the removed file
+    increment_db_generation();
+    Ok(())
+}
+
+pub fn rename_save_slot(slot_name: &str, new_slot_name: &str) -> std::io::Result<()> {
+    if !is_valid_slot_name(new_slot_name) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid save slot name '{}'", new_slot_name)));
+    }
+    let new_path = slot_path(new_slot_name);
+    if std::path::Path::new(&new_path).exists() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("Save slot '{}' already exists", new_slot_name)));
+    }
+    let path = slot_path(slot_name);
+    std::fs::rename(&path, &new_path)?;
+    let _ = std::fs::rename(backup_db_path(&path), backup_db_path(&new_path));
+    increment_db_generation();
+    Ok(())
+}
+
+/// Summary of a save, readable without loading the game.
+#[derive(Clone, Debug)]
+pub struct SaveMetadata {
+    pub saved_at: u64,
+    pub map_name: String,
+    pub playtime: f32,
+    pub stock_snapshot: String,
+    pub objectives_completed: usize,
+    pub objectives_total: usize,
+}
+impl SaveMetadata {
+    /// Derives the metadata from tables already written in the transaction, so it must run after all batches.
+    pub fn write(tx: &rusqlite::Transaction) -> rusqlite::Result<usize> {
+        let saved_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+        tx.execute(
+            "INSERT OR REPLACE INTO save_metadata (id, saved_at, map_name, playtime, stock_snapshot, objectives_completed, objectives_total)
+             SELECT 1, ?1,
+                 COALESCE((SELECT name FROM map_info WHERE id = 1), ''),
+                 COALESCE((SELECT stat_value FROM stats WHERE stat_name = 'playtime'), 0),
+                 COALESCE((SELECT group_concat(resource_name || '=' || amount, ';') FROM stock), ''),
+                 (SELECT COUNT(*) FROM objectives WHERE state = 'Completed'),
+                 (SELECT COUNT(*) FROM objectives)",
+            [saved_at],
+        )
+    }
+
+    /// Returns `None` for files saved before the metadata table existed.
+    pub fn read(path: &str) -> rusqlite::Result<Option<Self>> {
+        let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let has_table = conn
+            .prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'save_metadata'")?
+            .exists([])?;
+        if !has_table { return Ok(None); }
+
+        let mut stmt = conn.prepare("SELECT saved_at, map_name, playtime, stock_snapshot, objectives_completed, objectives_total FROM save_metadata WHERE id = 1")?;
+        let mut rows = stmt.query([])?;
+        let Some(row) = rows.next()? else { return Ok(None); };
+        Ok(Some(Self {
+            saved_at: row.get::<_, i64>(0)? as u64,
+            map_name: row.get(1)?,
+            playtime: row.get(2)?,
+            stock_snapshot: row.get(3)?,
+            objectives_completed: row.get::<_, i64>(4)? as usize,
+            objectives_total: row.get::<_, i64>(5)? as usize,
+        }))
+    }
+
+    /// Parses `stock_snapshot` back into (resource_name, amount) pairs.
+    pub fn stock(&self) -> Vec<(&str, i32)> {
+        self.stock_snapshot
+            .split(';')
+            .filter_map(|entry| {
+                let (name, amount) = entry.split_once('=')?;
+                Some((name, amount.parse().ok()?))
+            })
+            .collect()
+    }
+}
+
+/// Time spent in `GameState::Running` on the current map, carried over between saves.
+#[derive(Resource, Default, Clone, Copy, SSS)]
+pub struct Playtime(pub Duration);
+impl Playtime {
+    fn tick(time: Res<Time>, mut playtime: ResMut<Playtime>) {
+        playtime.0 += time.delta();
+    }
+
+    fn on_game_save(
+        mut commands: Commands,
+        playtime: Res<Playtime>,
+    ) {
+        commands.queue(SaveableBatchCommand::from_single(*playtime));
+    }
+}
+impl Saveable for Playtime {
+    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
+        tx.save_stat("playtime", self.0.as_secs_f32())?;
+        Ok(())
+    }
+}
+impl Loadable for Playtime {
+    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
+        let seconds = ctx.conn.get_stat("playtime").unwrap_or(0.);
+        ctx.commands.insert_resource(Playtime(Duration::from_secs_f32(seconds)));
+        Ok(LoadResult::Finished)
+    }
+}
+
+/// Saves every `interval` of `GameState::Running` into one of `rotation_size` autosave slots, overwriting the oldest.
+#[derive(Resource)]
+pub struct Autosave {
+    pub enabled: bool,
+    pub interval: Duration,
+    pub rotation_size: usize,
+    elapsed: Duration,
+}
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(5 * 60),
+            rotation_size: 3,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+impl Autosave {
+    /// Picks the first missing autosave slot, or the one written longest ago.
+    pub fn next_slot_name(&self) -> String {
+        (0..self.rotation_size.max(1))
+            .map(|index| format!("{AUTOSAVE_SLOT_PREFIX}{index}"))
+            .min_by_key(|slot_name| std::fs::metadata(slot_path(slot_name)).and_then(|m| m.modified()).ok())
+            .expect("Autosave rotation has at least one slot")
+    }
+
+    fn tick(
+        time: Res<Time>,
+        mut autosave: ResMut<Autosave>,
+        mut writer: MessageWriter<SaveGameSignal>,
+    ) {
+        if !autosave.enabled { return; }
+        autosave.elapsed += time.delta();
+        if autosave.elapsed < autosave.interval { return; }
+        autosave.elapsed = Duration::ZERO;
+
+        let slot_name = autosave.next_slot_name();
+        println!("Autosaving to slot '{}'", slot_name);
+        writer.write(SaveGameSignal(slot_path(&slot_name)));
+    }
+}
@@ -0,0 +1,197 @@
+//! Seeded randomness for the simulation.
+//!
+//! Every gameplay system draws from its own `RngStream` of the `GameRng` resource, so adding a draw in one subsystem
+//! doesn't shift the numbers seen by the others. The seed and the current state of every stream are saved,
+//! which makes a loaded game continue exactly as the original would have.
+//! Purely visual randomness (sprite variants, shader parameters) keeps using `nanorand::tls_rng()`.
+use nanorand::Rng;
+
+use crate::lib_prelude::*;
+
+pub mod rng_prelude {
+    pub use super::*;
+}
+
+pub struct GameRngPlugin;
+impl Plugin for GameRngPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GameRng>()
+            .register_db_loader::<GameRng>(MapLoadingStage::LoadResources)
+            .register_db_saver(GameRng::on_game_save);
+    }
+}
+
+/// Independent random sequences of `GameRng`, one per gameplay subsystem.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    Summoning,
+    Wisps,
+    Mining,
+}
+impl RngStream {
+    pub const ALL: [RngStream; 3] = [RngStream::Summoning, RngStream::Wisps, RngStream::Mining];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RngStream::Summoning => "summoning",
+            RngStream::Wisps => "wisps",
+            RngStream::Mining => "mining",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|stream| stream.name() == name)
+    }
+}
+
+/// WyRand generator with an accessible state. Same algorithm as `nanorand::WyRand`, which keeps its state private.
+#[derive(Clone, Debug)]
+pub struct GameRngStream {
+    state: u64,
+}
+impl Rng<8> for GameRngStream {
+    fn rand(&mut self) -> [u8; 8] {
+        self.state = self.state.wrapping_add(0xa0761d6478bd642f);
+        let t = (self.state as u128).wrapping_mul((self.state ^ 0xe7037ed1a0b428db) as u128);
+        (((t >> 64) ^ t) as u64).to_ne_bytes()
+    }
+}
+
+#[derive(Resource, Clone, Debug, SSS)]
+pub struct GameRng {
+    seed: u64,
+    streams: [GameRngStream; RngStream::ALL.len()],
+}
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_entropy()
+    }
+}
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: RngStream::ALL.map(|stream| GameRngStream { state: Self::stream_initial_state(seed, stream) }),
+        }
+    }
+
+    pub fn from_entropy() -> Self {
+        Self::from_seed(nanorand::WyRand::new().generate())
+    }
+
+    /// Seed for maps that don't store one: FNV-1a of the map name and size, so every playthrough of a map starts the same.
+    pub fn seed_for_map(map_info: &MapInfo) -> u64 {
+        let size = [map_info.grid_width.to_le_bytes(), map_info.grid_height.to_le_bytes()].concat();
+        map_info.name.as_bytes().iter()
+            .chain(size.iter())
+            .fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut GameRngStream {
+        &mut self.streams[stream as usize]
+    }
+
+    /// SplitMix64 of the seed offset by the stream index, so streams of nearby seeds don't overlap.
+    fn stream_initial_state(seed: u64, stream: RngStream) -> u64 {
+        let mut z = seed.wrapping_add((stream as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn on_game_save(
+        mut commands: Commands,
+        game_rng: Res<GameRng>,
+    ) {
+        commands.queue(SaveableBatchCommand::from_single(game_rng.clone()));
+    }
+}
+impl Saveable for GameRng {
+    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO game_rng (id, seed) VALUES (1, ?1)",
+            [self.seed as i64],
+        )?;
+        for stream in RngStream::ALL {
+            tx.execute(
+                "INSERT OR REPLACE INTO game_rng_streams (stream_name, state) VALUES (?1, ?2)",
+                (stream.name(), self.streams[stream as usize].state as i64),
+            )?;
+        }
+        Ok(())
+    }
+}
+impl Loadable for GameRng {
+    /// Maps saved without a seed get one derived from the map. Streams missing from the save start from the seed.
+    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
+        let seed = match ctx.conn.query_row("SELECT seed FROM game_rng WHERE id = 1", [], |row| row.get::<_, i64>(0)) {
+            Ok(seed) => seed as u64,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                // MapInfo is loaded in an earlier stage and is a resource by the time the commands run
+                ctx.commands.queue(|world: &mut World| {
+                    let seed = GameRng::seed_for_map(world.resource::<MapInfo>());
+                    println!("No RNG seed stored, using seed {} derived from the map", seed);
+                    world.insert_resource(GameRng::from_seed(seed));
+                });
+                return Ok(LoadResult::Finished);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut game_rng = GameRng::from_seed(seed);
+        let mut stmt = ctx.conn.prepare("SELECT stream_name, state FROM game_rng_streams")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let stream_name: String = row.get(0)?;
+            let state: i64 = row.get(1)?;
+            let Some(stream) = RngStream::from_name(&stream_name) else {
+                ctx.report_issue(None, format!("Unknown RNG stream '{}'", stream_name));
+                continue;
+            };
+            game_rng.stream(stream).state = state as u64;
+        }
+        println!("Loaded RNG seed {}", seed);
+        ctx.commands.insert_resource(game_rng);
+        Ok(LoadResult::Finished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(game_rng: &mut GameRng, stream: RngStream) -> Vec<u64> {
+        (0..16).map(|_| game_rng.stream(stream).generate::<u64>()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_streams() {
+        let (mut first, mut second) = (GameRng::from_seed(42), GameRng::from_seed(42));
+        for stream in RngStream::ALL {
+            assert_eq!(draw(&mut first, stream), draw(&mut second, stream), "Stream {} differs", stream.name());
+        }
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut game_rng = GameRng::from_seed(42);
+        let summoning = draw(&mut game_rng, RngStream::Summoning);
+        let _ = draw(&mut game_rng, RngStream::Wisps);
+        let mut fresh = GameRng::from_seed(42);
+        assert_eq!(draw(&mut game_rng, RngStream::Mining), draw(&mut fresh, RngStream::Mining));
+        assert_ne!(summoning, draw(&mut fresh, RngStream::Wisps));
+    }
+
+    #[test]
+    fn map_seed_depends_only_on_the_map() {
+        let map_info = MapInfo { grid_width: 100, grid_height: 80, world_width: 0., world_height: 0., name: "Test Map".to_string() };
+        let other_map_info = MapInfo { name: "Other Map".to_string(), ..map_info.clone() };
+        assert_eq!(GameRng::seed_for_map(&map_info), GameRng::seed_for_map(&map_info.clone()));
+        assert_ne!(GameRng::seed_for_map(&map_info), GameRng::seed_for_map(&other_map_info));
+    }
+}
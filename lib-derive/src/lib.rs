@@ -188,3 +188,295 @@ fn extract_modifier_type(attrs: &[Attribute]) -> syn::Result<proc_macro2::TokenS
         "Modifier derive requires a #[require(ModifierType = ...)] attribute"
     ))
 }
+
+/// Derives `Saveable` and `Loadable` for Builder components persisted in their own marker table.
+///
+/// The builder must have a `save_data: Option<T>` field, where `T` derives `PersistSaveData`.
+/// Fields marked with `#[persist(grid_coords)]` are stored in the shared `grid_coords` table.
+/// Any other field is filled with `Default::default()` on load.
+///
+/// # Example
+/// ```rust
+/// #[derive(Component, SSS, Persist)]
+/// #[persist(table = "tower_blasters")]
+/// pub struct BuilderTowerBlaster {
+///     #[persist(grid_coords)]
+///     grid_position: GridCoords,
+///     save_data: Option<TowerBlasterSaveData>,
+/// }
+///
+/// // Generates:
+/// // impl Saveable for BuilderTowerBlaster { ... } - marker table, grid coords, then save_data
+/// // impl Loadable for BuilderTowerBlaster { ... } - paginated SELECT over the marker table
+/// ```
+#[proc_macro_derive(Persist, attributes(persist))]
+pub fn derive_persist(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match generate_persist_impl(&input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate_persist_impl(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let table = extract_persist_table(&input.attrs)?;
+    let fields = named_fields(&input.data, "Persist")?;
+
+    let mut grid_coords_fields = Vec::new();
+    let mut default_fields = Vec::new();
+    let mut save_data_type = None;
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        if field_name == "save_data" {
+            save_data_type = Some(extract_option_inner_type(&field.ty)?);
+            continue;
+        }
+        match extract_persist_flag(&field.attrs)? {
+            Some(flag) if flag == "grid_coords" => grid_coords_fields.push(field_name),
+            Some(flag) => return Err(syn::Error::new_spanned(flag, "Unknown persist attribute for Builder field, expected `grid_coords`")),
+            None => default_fields.push(field_name),
+        }
+    }
+    let save_data_type = save_data_type.ok_or_else(|| syn::Error::new_spanned(name, "Persist derive requires a `save_data: Option<T>` field"))?;
+
+    let expect_message = format!("{} for saving must have save_data", name);
+    let select_query = format!("SELECT id FROM {} LIMIT ?1 OFFSET ?2", table);
+
+    Ok(quote! {
+        impl #impl_generics Saveable for #name #ty_generics #where_clause {
+            fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
+                let save_data = self.save_data.expect(#expect_message);
+                let entity_index = PersistSaveData::entity(&save_data).index() as i64;
+
+                tx.save_marker(#table, entity_index)?;
+                #( tx.save_grid_coords(entity_index, self.#grid_coords_fields)?; )*
+                PersistSaveData::save(&save_data, tx, entity_index)?;
+                Ok(())
+            }
+        }
+
+        impl #impl_generics Loadable for #name #ty_generics #where_clause {
+            fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
+                let mut stmt = ctx.conn.prepare(#select_query)?;
+                let mut rows = stmt.query(ctx.pagination.as_params())?;
+
+                let mut count = 0;
+                while let Some(row) = rows.next()? {
+                    let old_id: i64 = row.get(0)?;
+                    count += 1;
+                    #( let Some(#grid_coords_fields) = ctx.expect_row(old_id, "grid_coords", ctx.conn.get_grid_coords(old_id))? else { continue; }; )*
+                    let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+                    let save_data = <#save_data_type as PersistSaveData>::load(ctx.conn, ctx.entity_map, old_id, new_entity)?;
+                    ctx.commands.entity(new_entity).insert(Self {
+                        #( #grid_coords_fields, )*
+                        #( #default_fields: Default::default(), )*
+                        save_data: Some(save_data),
+                    });
+                }
+
+                Ok(count.into())
+            }
+
+            fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+                conn.count_rows(#table).map(Some)
+            }
+        }
+    })
+}
+
+/// Derives the `PersistSaveData` trait for the save data carried by a `#[derive(Persist)]` Builder.
+///
+/// The struct must have an `entity: Entity` field. Supported field attributes:
+/// - `#[persist(health)]` - `f32` stored in the `healths` table
+/// - `#[persist(disabled_by_player)]` - `bool` stored in the `disabled_by_player` table
+/// - `#[persist(upgrades)]` - `HashMap<UpgradeType, usize>` stored in the `upgrade_levels` table
+/// - `#[persist(timer)]` - `f32` elapsed seconds stored in the `timers` table under the field name
+/// - `#[persist(top_rotation)]` - `f32` angle stored in the `tower_top_rotations` table
+/// - `#[persist(wisp_target)]` - `Option<Entity>` stored in the `tower_wisp_targets` table, remapped through `DbEntityMap` on load
+/// - `#[persist(targeting_priority)]` - `TargetingPriority` stored in the `tower_targeting_priorities` table
+/// - `#[persist(energy_priority)]` - `EnergyPriority` stored in the `energy_priorities` table
+///
+/// # Example
+/// ```rust
+/// #[derive(Clone, Debug, PersistSaveData)]
+/// pub struct TowerBlasterSaveData {
+///     entity: Entity,
+///     #[persist(health)]
+///     health: f32,
+///     #[persist(disabled_by_player)]
+///     disabled_by_player: bool,
+///     #[persist(upgrades)]
+///     upgrade_levels: HashMap<UpgradeType, usize>,
+/// }
+/// ```
+#[proc_macro_derive(PersistSaveData, attributes(persist))]
+pub fn derive_persist_save_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match generate_persist_save_data_impl(&input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn generate_persist_save_data_impl(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = named_fields(&input.data, "PersistSaveData")?;
+
+    if !fields.iter().any(|field| field.ident.as_ref().unwrap() == "entity") {
+        return Err(syn::Error::new_spanned(name, "PersistSaveData derive requires an `entity: Entity` field"));
+    }
+
+    let mut save_steps = Vec::new();
+    let mut load_steps = Vec::new();
+    let mut field_names = Vec::new();
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        if field_name == "entity" { continue; }
+        field_names.push(field_name);
+        match extract_persist_flag(&field.attrs)? {
+            Some(flag) if flag == "health" => {
+                save_steps.push(quote! { tx.save_health(entity_id, self.#field_name)?; });
+                load_steps.push(quote! { let #field_name = conn.get_health(old_id)?; });
+            }
+            Some(flag) if flag == "disabled_by_player" => {
+                save_steps.push(quote! { if self.#field_name { tx.save_disabled_by_player(entity_id)?; } });
+                load_steps.push(quote! { let #field_name = conn.get_disabled_by_player(old_id)?; });
+            }
+            Some(flag) if flag == "upgrades" => {
+                save_steps.push(quote! {
+                    for (upgrade_type, level) in &self.#field_name {
+                        tx.save_upgrade_level(entity_id, &upgrade_type.as_db_str(), *level)?;
+                    }
+                });
+                load_steps.push(quote! {
+                    let #field_name = conn.get_upgrade_levels_raw(old_id)?
+                        .into_iter()
+                        .filter_map(|(type_str, level)| UpgradeType::from_db_str(&type_str).map(|t| (t, level)))
+                        .collect();
+                });
+            }
+            Some(flag) if flag == "timer" => {
+                let timer_name = field_name.to_string();
+                save_steps.push(quote! { tx.save_timer_elapsed(entity_id, #timer_name, self.#field_name)?; });
+                load_steps.push(quote! { let #field_name = conn.get_timer_elapsed(old_id, #timer_name)?; });
+            }
+            Some(flag) if flag == "top_rotation" => {
+                save_steps.push(quote! { tx.save_tower_top_rotation(entity_id, self.#field_name)?; });
+                load_steps.push(quote! { let #field_name = conn.get_tower_top_rotation(old_id)?; });
+            }
+            Some(flag) if flag == "wisp_target" => {
+                save_steps.push(quote! {
+                    if let Some(target_wisp) = self.#field_name {
+                        tx.save_tower_wisp_target(entity_id, target_wisp.index() as i64)?;
+                    }
+                });
+                load_steps.push(quote! {
+                    let #field_name = conn.get_tower_wisp_target(old_id)?
+                        .and_then(|target_wisp_id| entity_map.map.get(&target_wisp_id).copied());
+                });
+            }
+            Some(flag) if flag == "targeting_priority" => {
+                save_steps.push(quote! { tx.save_tower_targeting_priority(entity_id, self.#field_name.as_db_str())?; });
+                load_steps.push(quote! {
+                    let #field_name = conn.get_tower_targeting_priority(old_id)?
+                        .and_then(|priority| TargetingPriority::from_db_str(&priority))
+                        .unwrap_or_default();
+                });
+            }
+            Some(flag) if flag == "energy_priority" => {
+                save_steps.push(quote! { tx.save_energy_priority(entity_id, self.#field_name.as_db_str())?; });
+                load_steps.push(quote! {
+                    let #field_name = conn.get_energy_priority(old_id)?
+                        .and_then(|priority| EnergyPriority::from_db_str(&priority))
+                        .unwrap_or_default();
+                });
+            }
+            Some(flag) => return Err(syn::Error::new_spanned(flag, "Unknown persist attribute, expected one of `health`, `disabled_by_player`, `upgrades`, `timer`, `top_rotation`, `wisp_target`, `targeting_priority`, `energy_priority`")),
+            None => {
+                load_steps.push(quote! { let #field_name = Default::default(); });
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics PersistSaveData for #name #ty_generics #where_clause {
+            fn entity(&self) -> Entity {
+                self.entity
+            }
+
+            fn save(&self, tx: &rusqlite::Transaction, entity_id: i64) -> rusqlite::Result<()> {
+                #( #save_steps )*
+                Ok(())
+            }
+
+            #[allow(unused_variables)] // Only used by some of the persisted fields
+            fn load(conn: &rusqlite::Connection, entity_map: &DbEntityMap, old_id: i64, new_entity: Entity) -> rusqlite::Result<Self> {
+                #( #load_steps )*
+                Ok(Self { entity: new_entity, #( #field_names, )* })
+            }
+        }
+    })
+}
+
+fn named_fields<'a>(data: &'a Data, derive_name: &str) -> syn::Result<&'a syn::punctuated::Punctuated<syn::Field, syn::token::Comma>> {
+    match data {
+        Data::Struct(data_struct) => match &data_struct.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new(proc_macro2::Span::call_site(), format!("{} derive only supports structs with named fields", derive_name))),
+        },
+        _ => Err(syn::Error::new(proc_macro2::Span::call_site(), format!("{} derive only supports structs", derive_name))),
+    }
+}
+
+/// Extracts `"table_name"` from `#[persist(table = "table_name")]`
+fn extract_persist_table(attrs: &[Attribute]) -> syn::Result<String> {
+    for attr in attrs {
+        if !attr.path().is_ident("persist") { continue; }
+        let mut table = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                table = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error("Unknown persist attribute, expected `table = \"...\"`"))
+            }
+        })?;
+        if let Some(table) = table {
+            return Ok(table);
+        }
+    }
+    Err(syn::Error::new(
+        proc_macro2::Span::call_site(),
+        "Persist derive requires a #[persist(table = \"...\")] attribute"
+    ))
+}
+
+/// Extracts `flag` from a field's `#[persist(flag)]` attribute
+fn extract_persist_flag(attrs: &[Attribute]) -> syn::Result<Option<syn::Ident>> {
+    for attr in attrs {
+        if attr.path().is_ident("persist") {
+            return attr.parse_args::<syn::Ident>().map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn extract_option_inner_type(ty: &syn::Type) -> syn::Result<&syn::Type> {
+    if let syn::Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                        return Ok(inner);
+                    }
+                }
+            }
+        }
+    }
+    Err(syn::Error::new_spanned(ty, "`save_data` must be of type Option<T>"))
+}
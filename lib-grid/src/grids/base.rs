@@ -1,12 +1,17 @@
 use std::ops::{Index, IndexMut};
 use crate::lib_prelude::*;
 
+/// Side of the square chunks whose changes are tracked separately, see `BaseGrid::area_changed_since()`.
+pub const CHUNK_SIZE: i32 = 8;
+
 #[derive(Resource)]
 pub struct BaseGrid<FieldType, GridVersionType> where FieldType: FieldTrait, GridVersionType: GridVersionTrait {
     pub width: i32,
     pub height: i32,
     pub grid: Vec<FieldType>,
     pub version: GridVersionType, // Used to determine whether the grid has changed
+    /// Version of the last change within every `CHUNK_SIZE` x `CHUNK_SIZE` chunk, row by row. Only kept by grids versioned with `GridVersion`.
+    chunk_versions: Vec<GridVersion>,
 }
 
 impl<FieldType, GridVersionType> BaseGrid<FieldType, GridVersionType> where FieldType: FieldTrait, GridVersionType: GridVersionTrait {
@@ -16,6 +21,7 @@ impl<FieldType, GridVersionType> BaseGrid<FieldType, GridVersionType> where Fiel
             height: 0,
             grid: vec![],
             version: GridVersionType::default(),
+            chunk_versions: vec![],
         }
     }
     pub fn new_with_size(width: i32, height: i32) -> Self {
@@ -24,6 +30,7 @@ impl<FieldType, GridVersionType> BaseGrid<FieldType, GridVersionType> where Fiel
             height,
             grid: vec![Default::default(); (width * height) as usize],
             version: GridVersionType::default(),
+            chunk_versions: vec![0; Self::chunk_count(width, height)],
         }
     }
     pub fn resize_and_reset(&mut self, bounds: (i32, i32)) {
@@ -31,6 +38,7 @@ impl<FieldType, GridVersionType> BaseGrid<FieldType, GridVersionType> where Fiel
             self.width = bounds.0;
             self.height = bounds.1;
             self.grid.resize((bounds.0 * bounds.1) as usize, Default::default());
+            self.chunk_versions = vec![0; Self::chunk_count(bounds.0, bounds.1)];
         }
         self.reset();
     }
@@ -43,6 +51,63 @@ impl<FieldType, GridVersionType> BaseGrid<FieldType, GridVersionType> where Fiel
     pub fn bounds(&self) -> (i32, i32) {
         (self.width, self.height)
     }
+    fn chunk_count(width: i32, height: i32) -> usize {
+        (Self::chunks_across(width) * Self::chunks_across(height)) as usize
+    }
+    fn chunks_across(length: i32) -> i32 {
+        (length + CHUNK_SIZE - 1) / CHUNK_SIZE
+    }
+    fn chunk_index(&self, chunk_x: i32, chunk_y: i32) -> usize {
+        (chunk_y * Self::chunks_across(self.width) + chunk_x) as usize
+    }
+    /// Chunk coordinates of the inclusive rectangle, clamped to the grid. None if the rectangle is entirely outside.
+    fn chunks_in_rect(&self, min: GridCoords, max: GridCoords) -> Option<((i32, i32), (i32, i32))> {
+        let (min_x, min_y) = (min.x.max(0), min.y.max(0));
+        let (max_x, max_y) = (max.x.min(self.width - 1), max.y.min(self.height - 1));
+        if min_x > max_x || min_y > max_y { return None; }
+        Some(((min_x / CHUNK_SIZE, min_y / CHUNK_SIZE), (max_x / CHUNK_SIZE, max_y / CHUNK_SIZE)))
+    }
+}
+
+impl<FieldType> BaseGrid<FieldType, GridVersion> where FieldType: FieldTrait {
+    /// Increments the version and marks the chunks of `changed_coords` as changed with it.
+    pub fn bump_version(&mut self, changed_coords: impl IntoIterator<Item = GridCoords>) {
+        self.version = self.version.wrapping_add(1);
+        for coords in changed_coords {
+            if !coords.is_in_bounds(self.bounds()) { continue; }
+            let index = self.chunk_index(coords.x / CHUNK_SIZE, coords.y / CHUNK_SIZE);
+            self.chunk_versions[index] = self.version;
+        }
+    }
+    /// Like `bump_version()` for every field of the inclusive rectangle.
+    pub fn bump_version_rect(&mut self, min: GridCoords, max: GridCoords) {
+        self.version = self.version.wrapping_add(1);
+        let Some(((min_x, min_y), (max_x, max_y))) = self.chunks_in_rect(min, max) else { return; };
+        for chunk_y in min_y..=max_y {
+            for chunk_x in min_x..=max_x {
+                let index = self.chunk_index(chunk_x, chunk_y);
+                self.chunk_versions[index] = self.version;
+            }
+        }
+    }
+    /// Whether the chunk of `coords` changed after `since_version`.
+    /// Versions are compared as serial numbers, so wrapping around only makes some unchanged chunks look changed.
+    pub fn changed_since(&self, coords: GridCoords, since_version: GridVersion) -> bool {
+        if !coords.is_in_bounds(self.bounds()) { return false; }
+        let chunk_version = self.chunk_versions[self.chunk_index(coords.x / CHUNK_SIZE, coords.y / CHUNK_SIZE)];
+        Self::is_newer(chunk_version, since_version)
+    }
+    /// Whether any chunk touched by the inclusive rectangle changed after `since_version`.
+    pub fn area_changed_since(&self, min: GridCoords, max: GridCoords, since_version: GridVersion) -> bool {
+        if self.version == since_version { return false; }
+        let Some(((min_x, min_y), (max_x, max_y))) = self.chunks_in_rect(min, max) else { return false; };
+        (min_y..=max_y).any(|chunk_y| (min_x..=max_x).any(|chunk_x| {
+            Self::is_newer(self.chunk_versions[self.chunk_index(chunk_x, chunk_y)], since_version)
+        }))
+    }
+    fn is_newer(version: GridVersion, than_version: GridVersion) -> bool {
+        (version.wrapping_sub(than_version) as i32) > 0
+    }
 }
 
 
@@ -61,3 +126,74 @@ impl<FieldType, GridVersionType>  IndexMut<GridCoords> for BaseGrid<FieldType, G
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use crate::grids::terrain::TerrainGrid;
+    use super::*;
+
+    /// 3 x 2 chunks.
+    fn grid() -> TerrainGrid {
+        TerrainGrid::new_with_size(3 * CHUNK_SIZE, 2 * CHUNK_SIZE)
+    }
+
+    #[test]
+    fn changes_are_tracked_per_chunk() {
+        let mut grid = grid();
+        let since = grid.version;
+        grid.bump_version([GridCoords { x: CHUNK_SIZE, y: 0 }]);
+        assert!(grid.changed_since(GridCoords { x: CHUNK_SIZE, y: 0 }, since));
+        assert!(grid.changed_since(GridCoords { x: 2 * CHUNK_SIZE - 1, y: CHUNK_SIZE - 1 }, since));
+        assert!(!grid.changed_since(GridCoords { x: CHUNK_SIZE - 1, y: 0 }, since));
+        assert!(!grid.changed_since(GridCoords { x: 2 * CHUNK_SIZE, y: 0 }, since));
+        assert!(!grid.changed_since(GridCoords { x: CHUNK_SIZE, y: CHUNK_SIZE }, since));
+        assert!(!grid.changed_since(GridCoords { x: CHUNK_SIZE, y: 0 }, grid.version));
+    }
+
+    #[test]
+    fn areas_see_changes_from_their_edge_fields() {
+        let mut grid = grid();
+        let since = grid.version;
+        grid.bump_version([GridCoords { x: CHUNK_SIZE, y: 0 }]);
+        let origin = GridCoords { x: 0, y: 0 };
+        assert!(!grid.area_changed_since(origin, GridCoords { x: CHUNK_SIZE - 1, y: CHUNK_SIZE - 1 }, since));
+        assert!(grid.area_changed_since(origin, GridCoords { x: CHUNK_SIZE, y: 0 }, since));
+        assert!(!grid.area_changed_since(GridCoords { x: 2 * CHUNK_SIZE, y: 0 }, GridCoords { x: 100, y: 100 }, since));
+        assert!(grid.area_changed_since(GridCoords { x: -5, y: -5 }, GridCoords { x: 100, y: 100 }, since));
+        assert!(!grid.area_changed_since(GridCoords { x: -5, y: -5 }, GridCoords { x: -1, y: -1 }, since));
+    }
+
+    #[test]
+    fn rect_changes_mark_every_chunk_they_touch() {
+        let mut grid = grid();
+        let since = grid.version;
+        grid.bump_version_rect(GridCoords { x: CHUNK_SIZE - 1, y: CHUNK_SIZE - 1 }, GridCoords { x: CHUNK_SIZE, y: CHUNK_SIZE });
+        for (x, y) in [(0, 0), (CHUNK_SIZE, 0), (0, CHUNK_SIZE), (CHUNK_SIZE, CHUNK_SIZE)] {
+            assert!(grid.changed_since(GridCoords { x, y }, since), "Chunk of ({}, {}) not marked", x, y);
+        }
+        assert!(!grid.changed_since(GridCoords { x: 2 * CHUNK_SIZE, y: 0 }, since));
+        assert!(!grid.changed_since(GridCoords { x: 2 * CHUNK_SIZE, y: CHUNK_SIZE }, since));
+    }
+
+    #[test]
+    fn changes_outside_of_the_grid_only_move_the_version() {
+        let mut grid = grid();
+        let since = grid.version;
+        grid.bump_version([GridCoords { x: -1, y: 0 }, GridCoords { x: 3 * CHUNK_SIZE, y: 0 }]);
+        assert_ne!(grid.version, since);
+        assert!(!grid.area_changed_since(GridCoords { x: 0, y: 0 }, GridCoords { x: 3 * CHUNK_SIZE - 1, y: 2 * CHUNK_SIZE - 1 }, since));
+    }
+
+    #[test]
+    fn versions_wrap_around() {
+        let mut grid = grid();
+        grid.version = GridVersion::MAX - 1;
+        let since = grid.version;
+        grid.bump_version([GridCoords { x: 0, y: 0 }]);
+        grid.bump_version([GridCoords { x: CHUNK_SIZE, y: 0 }]);
+        assert_eq!(grid.version, 0);
+        assert!(grid.changed_since(GridCoords { x: 0, y: 0 }, since));
+        assert!(grid.changed_since(GridCoords { x: CHUNK_SIZE, y: 0 }, since));
+        assert!(!grid.changed_since(GridCoords { x: 0, y: 0 }, GridVersion::MAX));
+    }
+}
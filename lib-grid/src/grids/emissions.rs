@@ -9,78 +9,81 @@ impl Plugin for EmissionsPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(EmissionsGrid::new_empty())
-            .init_resource::<EmissionsEnergyRecalculateAll>()
+            .init_resource::<EmissionsRecalculateAll>()
             .add_message::<EmitterChangedEvent>()
+            .add_message::<EmissionsPulse>()
             .add_systems(OnExit(MapLoadingStage::LoadMapInfo), |mut commands: Commands, map_info: Res<MapInfo>| { commands.insert_resource(EmissionsGrid::new_with_size(map_info.grid_width, map_info.grid_height)); })
             .add_systems(PostUpdate, (
+                emissions_decay_system.run_if(in_state(GameState::Running)),
                 emissions_calculations_system,
-            ))
-            .add_observer(EmitterEnergy::on_add)
-            .add_observer(EmitterEnergy::on_remove)
-            .add_observer(EmitterEnergy::on_spread_affector_insert)
-            .add_observer(EmitterEnergy::on_spread_affector_remove)
+            ).chain())
+            .add_observer(EmissionsEmitter::on_add)
+            .add_observer(EmissionsEmitter::on_remove)
+            .add_observer(EmissionsEmitter::on_spread_affector_insert)
+            .add_observer(EmissionsEmitter::on_spread_affector_remove)
             ;
     }
 }
 
-/// Companion component to EmitterEnergy. Use it to mark wheter Emitter is functional.
+/// Companion component to EmissionsEmitter. Use it to mark wheter Emitter is functional.
 #[derive(Component, Default)]
-pub struct EmitterEnergyEnabled;
+pub struct EmissionsEmitterEnabled;
+/// Building constantly spreading emissions around itself, one entry per channel.
 #[derive(Component)]
-pub struct EmitterEnergy(pub FloodEmissionsDetails);
-impl EmitterEnergy {
+pub struct EmissionsEmitter(pub Vec<FloodEmissionsDetails>);
+impl EmissionsEmitter {
     fn on_add(
-        trigger: On<Add, EmitterEnergy>,
+        trigger: On<Add, EmissionsEmitter>,
         mut commands: Commands,
     ) {
         let entity = trigger.entity;
         commands.entity(entity)
             .observe(Self::on_enable_or_insert)
             .observe(Self::on_disable_or_replace)
-            .insert(EmitterEnergyEnabled);
+            .insert(EmissionsEmitterEnabled);
     }
     fn on_remove(
-        trigger: On<Remove, EmitterEnergy>,
+        trigger: On<Remove, EmissionsEmitter>,
         mut commands: Commands,
     ) {
         let observer = trigger.observer();
         commands.entity(observer).despawn();
     }
     fn on_enable_or_insert(
-        trigger: On<Insert, (GridCoords, GridImprint, EmitterEnergyEnabled)>,
+        trigger: On<Insert, (GridCoords, GridImprint, EmissionsEmitterEnabled)>,
         mut events: MessageWriter<EmitterChangedEvent>,
-        suppliers: Query<(&GridCoords, &GridImprint, &EmitterEnergy)>,
+        emitters: Query<(&GridCoords, &GridImprint, &EmissionsEmitter)>,
     ) {
         let entity = trigger.entity;
-        let Ok((grid_coords, grid_imprint, emitter)) = suppliers.get(entity) else { return; };
+        let Ok((grid_coords, grid_imprint, emitter)) = emitters.get(entity) else { return; };
         events.write(EmitterChangedEvent {
             emitter_entity: entity,
             coords: grid_imprint.covered_coords(*grid_coords),
-            emissions_details: vec![emitter.0.clone()],
+            emissions_details: emitter.0.clone(),
         });
     }
     fn on_disable_or_replace(
-        trigger: On<Replace, (GridCoords, GridImprint, EmitterEnergyEnabled)>,
+        trigger: On<Replace, (GridCoords, GridImprint, EmissionsEmitterEnabled)>,
         mut events: MessageWriter<EmitterChangedEvent>,
-        suppliers: Query<(&GridCoords, &GridImprint, &EmitterEnergy), With<EmitterEnergyEnabled>>,
+        emitters: Query<(&GridCoords, &GridImprint, &EmissionsEmitter), With<EmissionsEmitterEnabled>>,
     ) {
         let entity = trigger.entity;
-        let Ok((grid_coords, grid_imprint, emitter)) = suppliers.get(entity) else { return; };
+        let Ok((grid_coords, grid_imprint, emitter)) = emitters.get(entity) else { return; };
         events.write(EmitterChangedEvent {
             emitter_entity: entity,
             coords: grid_imprint.covered_coords(*grid_coords),
-            emissions_details: vec![emitter.0.cloned_with_reversed_mode()],
+            emissions_details: emitter.0.iter().map(FloodEmissionsDetails::cloned_with_reversed_mode).collect(),
         });
     }
     fn on_spread_affector_insert(
         _trigger: On<Insert, EmissionsGridSpreadAffector>,
-        mut recalculate_all: ResMut<EmissionsEnergyRecalculateAll>,
+        mut recalculate_all: ResMut<EmissionsRecalculateAll>,
     ) {
         recalculate_all.0 = true;
     }
     fn on_spread_affector_remove(
         _trigger: On<Remove, EmissionsGridSpreadAffector>,
-        mut recalculate_all: ResMut<EmissionsEnergyRecalculateAll>,
+        mut recalculate_all: ResMut<EmissionsRecalculateAll>,
     ) {
         recalculate_all.0 = true;
     }
@@ -93,90 +96,233 @@ pub struct EmitterChangedEvent {
     pub emissions_details: Vec<FloodEmissionsDetails>,
 }
 
+/// One-off emissions, like the noise of a shot. Meant for the fading channels, which then wear off on their own.
+#[derive(Message, Debug)]
+pub struct EmissionsPulse {
+    pub coords: Vec<GridCoords>,
+    pub emissions_details: Vec<FloodEmissionsDetails>,
+}
+
+/// Recalculates the emissions of all emitters, e.g. after walls have changed how they spread.
 #[derive(Resource, Default)]
-pub struct EmissionsEnergyRecalculateAll(pub bool);
+pub struct EmissionsRecalculateAll(pub bool);
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum EmissionsType {
+    /// Spread by energy suppliers, beckons wisps.
     Energy,
+    /// Left by firing towers.
+    Noise,
+    /// Spread by deterrent buildings.
+    Fear,
+    /// Trails left by passing wisps.
+    Scent,
+}
+impl EmissionsType {
+    pub const ALL: [EmissionsType; 4] = [EmissionsType::Energy, EmissionsType::Noise, EmissionsType::Fear, EmissionsType::Scent];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmissionsType::Energy => "Energy",
+            EmissionsType::Noise => "Noise",
+            EmissionsType::Fear => "Fear",
+            EmissionsType::Scent => "Scent",
+        }
+    }
+
+    /// Fraction of the value that wears off every second. Zero for channels kept up by `EmissionsEmitter`s.
+    pub fn decay_per_second(&self) -> f32 {
+        match self {
+            EmissionsType::Energy | EmissionsType::Fear => 0.,
+            EmissionsType::Noise => 0.5,
+            EmissionsType::Scent => 0.1,
+        }
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.decay_per_second() > 0.
+    }
+
+    /// Heatmap color of the channel in the emissions overlay.
+    fn heatmap_color(&self, value: u8) -> [u8; 3] {
+        match self {
+            EmissionsType::Energy => [0, value, value],
+            EmissionsType::Noise => [value, value, 0],
+            EmissionsType::Fear => [value, 0, 0],
+            EmissionsType::Scent => [0, value, 0],
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug)]
 pub struct Emissions {
     pub energy: f32,
+    pub noise: f32,
+    pub fear: f32,
+    pub scent: f32,
+}
+impl Emissions {
+    pub fn get(&self, emissions_type: EmissionsType) -> f32 {
+        match emissions_type {
+            EmissionsType::Energy => self.energy,
+            EmissionsType::Noise => self.noise,
+            EmissionsType::Fear => self.fear,
+            EmissionsType::Scent => self.scent,
+        }
+    }
+    fn get_mut(&mut self, emissions_type: EmissionsType) -> &mut f32 {
+        match emissions_type {
+            EmissionsType::Energy => &mut self.energy,
+            EmissionsType::Noise => &mut self.noise,
+            EmissionsType::Fear => &mut self.fear,
+            EmissionsType::Scent => &mut self.scent,
+        }
+    }
 }
 #[derive(Default)]
 pub struct EmissionsGridVersion {
     pub energy: GridVersion,
+    pub noise: GridVersion,
+    pub fear: GridVersion,
+    pub scent: GridVersion,
+}
+impl EmissionsGridVersion {
+    pub fn get(&self, emissions_type: EmissionsType) -> GridVersion {
+        match emissions_type {
+            EmissionsType::Energy => self.energy,
+            EmissionsType::Noise => self.noise,
+            EmissionsType::Fear => self.fear,
+            EmissionsType::Scent => self.scent,
+        }
+    }
+    fn bump(&mut self, emissions_type: EmissionsType) {
+        let version = match emissions_type {
+            EmissionsType::Energy => &mut self.energy,
+            EmissionsType::Noise => &mut self.noise,
+            EmissionsType::Fear => &mut self.fear,
+            EmissionsType::Scent => &mut self.scent,
+        };
+        *version = version.wrapping_add(1);
+    }
+}
+
+/// How strongly a wisp is drawn to each channel. Negative weights repel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmissionsWeights {
+    pub energy: f32,
+    pub noise: f32,
+    pub fear: f32,
+    pub scent: f32,
+}
+impl Default for EmissionsWeights {
+    fn default() -> Self {
+        Self { energy: 1., noise: 0., fear: 0., scent: 0. }
+    }
+}
+impl EmissionsWeights {
+    pub fn get(&self, emissions_type: EmissionsType) -> f32 {
+        match emissions_type {
+            EmissionsType::Energy => self.energy,
+            EmissionsType::Noise => self.noise,
+            EmissionsType::Fear => self.fear,
+            EmissionsType::Scent => self.scent,
+        }
+    }
+    /// Weighted sum of all channels of the field.
+    pub fn attraction(&self, emissions: &Emissions) -> f32 {
+        EmissionsType::ALL.iter().map(|emissions_type| self.get(*emissions_type) * emissions.get(*emissions_type)).sum()
+    }
 }
 
 pub type EmissionsGrid = BaseGrid<Emissions, EmissionsGridVersion>;
 
 impl EmissionsGrid {
-    pub fn add_energy(&mut self, coords: GridCoords, energy: f32) {
-        self[coords].energy += energy;
-        if self[coords].energy.abs() < 0.0001 { self[coords].energy = 0.; }
-        self.version.energy = self.version.energy.wrapping_add(1);
+    pub fn add_emissions(&mut self, coords: GridCoords, emissions_type: EmissionsType, value: f32) {
+        let emissions = self[coords].get_mut(emissions_type);
+        *emissions += value;
+        if emissions.abs() < 0.0001 { *emissions = 0.; }
+        self.version.bump(emissions_type);
+    }
+    pub fn reset_emissions(&mut self, emissions_type: EmissionsType) {
+        self.grid.iter_mut().for_each(|emissions| {
+            *emissions.get_mut(emissions_type) = 0.;
+        });
+        self.version.bump(emissions_type);
     }
-    pub fn reset_energy_emissions(&mut self) {
+    /// Scales the whole channel down by `factor`. Values that become negligible are cleared.
+    pub fn decay_emissions(&mut self, emissions_type: EmissionsType, factor: f32) {
+        let mut changed = false;
         self.grid.iter_mut().for_each(|emissions| {
-            emissions.energy = 0.;
+            let value = emissions.get_mut(emissions_type);
+            if *value == 0. { return; }
+            *value *= factor;
+            if value.abs() < 0.01 { *value = 0.; }
+            changed = true;
         });
-        self.version.energy = self.version.energy.wrapping_add(1);
+        if changed { self.version.bump(emissions_type); }
     }
     pub fn imprint_into_heatmap(&self, heatmap: &mut Vec<u8>, emissions_type: EmissionsType) {
-        let (min_emission, max_emission) = match emissions_type {
-            EmissionsType::Energy => {
-                let (mut min, mut max) = (f32::MAX, f32::MIN);
-                for emissions in self.grid.iter() {
-                    if emissions.energy != 0. {
-                        min = min.min(emissions.energy);
-                    }
-                    max = max.max(emissions.energy);
-                }
-                (min, max)
-            },
-        };
+        let (mut min_emission, mut max_emission) = (f32::MAX, f32::MIN);
+        for emissions in self.grid.iter() {
+            let value = emissions.get(emissions_type);
+            if value != 0. {
+                min_emission = min_emission.min(value);
+            }
+            max_emission = max_emission.max(value);
+        }
         let emissions_range = max_emission - min_emission;
         let mut idx = 0;
         heatmap.chunks_mut(4).for_each(|chunk| {
-            let emissions = &self.grid[idx];
-            match emissions_type {
-                EmissionsType::Energy => {
-                    let value = {
-                        if emissions_range == 0. || emissions.energy == 0. {
-                            0
-                        } else {
-                            ((emissions.energy - min_emission) / emissions_range * 255.) as u8
-                        }
-                    };
-                    chunk[0] = 0;
-                    chunk[1] = value;
-                    chunk[2] = value;
-                    chunk[3] = 127;
+            let emission = self.grid[idx].get(emissions_type);
+            let value = {
+                if emissions_range == 0. || emission == 0. {
+                    0
+                } else {
+                    ((emission - min_emission) / emissions_range * 255.) as u8
                 }
-            }
+            };
+            let [red, green, blue] = emissions_type.heatmap_color(value);
+            chunk[0] = red;
+            chunk[1] = green;
+            chunk[2] = blue;
+            chunk[3] = 127;
             idx += 1;
         });
     }
 }
 
+fn emissions_decay_system(
+    time: Res<Time>,
+    mut emissions_grid: ResMut<EmissionsGrid>,
+) {
+    for emissions_type in EmissionsType::ALL.into_iter().filter(EmissionsType::is_fading) {
+        let factor = (1. - emissions_type.decay_per_second()).powf(time.delta_secs());
+        emissions_grid.decay_emissions(emissions_type, factor);
+    }
+}
+
 fn emissions_calculations_system(
-    mut recalculate_all: ResMut<EmissionsEnergyRecalculateAll>,
+    mut recalculate_all: ResMut<EmissionsRecalculateAll>,
     mut events: MessageReader<EmitterChangedEvent>,
+    mut pulses: MessageReader<EmissionsPulse>,
     mut emissions_grid: ResMut<EmissionsGrid>,
     obstacle_grid: Res<ObstacleGrid>,
-    emitters_buildings: Query<(&EmitterEnergy, &GridImprint, &GridCoords), With<EmitterEnergyEnabled>>,
+    emitters_buildings: Query<(&EmissionsEmitter, &GridImprint, &GridCoords), With<EmissionsEmitterEnabled>>,
 ) {
     if recalculate_all.0 {
         recalculate_all.0 = false;
-        emissions_grid.reset_energy_emissions();
+        // Fading channels only hold what is left of past pulses, so they are kept as they are
+        for emissions_type in EmissionsType::ALL.into_iter().filter(|emissions_type| !emissions_type.is_fading()) {
+            emissions_grid.reset_emissions(emissions_type);
+        }
         for (emitter, grid_imprint, coords) in emitters_buildings.iter() {
+            let emissions_details = emitter.0.iter().filter(|details| !details.emissions_type.is_fading()).cloned().collect::<Vec<_>>();
+            if emissions_details.is_empty() { continue; }
             flood_emissions(
                 &mut emissions_grid,
                 &obstacle_grid,
                 &grid_imprint.covered_coords(*coords),
-                &vec![emitter.0.clone()],
+                &emissions_details,
                 |field| !field.has_wall(),
             );
         }
@@ -192,4 +338,13 @@ fn emissions_calculations_system(
             );
         }
     }
-}
\ No newline at end of file
+    for pulse in pulses.read() {
+        flood_emissions(
+            &mut emissions_grid,
+            &obstacle_grid,
+            &pulse.coords,
+            &pulse.emissions_details,
+            |field| !field.has_wall(),
+        );
+    }
+}
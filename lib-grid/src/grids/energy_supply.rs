@@ -1,3 +1,7 @@
+use std::cmp::Reverse;
+
+use bevy::platform::collections::HashMap;
+
 use crate::lib_prelude::*;
 use crate::grids::base::BaseGrid;
 use crate::search::flooding::{flood_energy_supply, flood_power_coverage, FloodEnergySupplyMode};
@@ -8,17 +12,17 @@ impl Plugin for EnergySupplyPlugin {
         app
             .insert_resource(EnergySupplyGrid::new_empty())
             .init_resource::<EnergySupplyRecalculatePower>()
+            .init_resource::<EnergyNetworks>()
             .add_message::<SupplierChangedEvent>()
             .add_systems(OnExit(MapLoadingStage::LoadMapInfo), |mut commands: Commands, map_info: Res<MapInfo>| { commands.insert_resource(EnergySupplyGrid::new_with_size(map_info.grid_width, map_info.grid_height)); })
             .add_systems(PostUpdate, (
                 (
                     on_supplier_changed_system,
                     on_recalculate_power_system.run_if(resource_changed::<EnergySupplyRecalculatePower>),
-                    NeedsPower::update_system,
+                    NeedsPower::balance_system.run_if(NeedsPower::needs_balancing),
                 ).chain(),
             ))
             .add_observer(SupplierEnergy::on_add)
-            .add_observer(NeedsPower::on_add)
             ;
     }
 }
@@ -73,9 +77,13 @@ impl SupplierEnergy {
     }
 }
 
-// Produces energy
+/// Companion component to SupplierEnergy. Marks suppliers that are working, which are the ones wisps go for.
+#[derive(Component, Default)]
+pub struct SupplierEnergyEnabled;
+
+// Produces energy, as much as its `EnergyProduction`
 #[derive(Component, Copy, Clone, Debug)]
-#[require(HasPower)]
+#[require(HasPower, EnergyProduction)]
 pub struct GeneratorEnergy;
 
 #[derive(Message)]
@@ -90,10 +98,14 @@ pub struct SupplierChangedEvent {
 struct EnergySupplyRecalculatePower(bool);
 
 
+/// Cells connected to the same generators. Only valid until the next power recalculation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EnergyNetworkId(pub u32);
+
 #[derive(Clone, Debug, Default)]
 pub struct EnergySupplyField {
     suppliers: HashSet<Entity>,
-    has_power: bool,
+    network: Option<EnergyNetworkId>,
 }
 impl EnergySupplyField {
     // Only checks if there is any supplier, not if it has power
@@ -101,8 +113,9 @@ impl EnergySupplyField {
     pub fn add_supplier(&mut self, supplier: Entity) { self.suppliers.insert(supplier); }
     pub fn remove_supplier(&mut self, supplier: Entity) { self.suppliers.remove(&supplier); }
     pub fn has_supplier(&self, supplier: Entity) -> bool { self.suppliers.contains(&supplier) }
-    pub fn has_power(&self) -> bool { self.has_power }
-    pub fn set_power(&mut self, power: bool) { self.has_power = power; }
+    pub fn has_power(&self) -> bool { self.network.is_some() }
+    pub fn network(&self) -> Option<EnergyNetworkId> { self.network }
+    pub fn set_network(&mut self, network: Option<EnergyNetworkId>) { self.network = network; }
     pub fn suppliers(&self) -> &HashSet<Entity> { &self.suppliers }
 }
 
@@ -119,40 +132,22 @@ impl EnergySupplyGrid {
     }
     /// At least one of the imprint's cells mut have energy supply.
     pub fn is_imprint_suppliable(&self, coords: GridCoords, imprint: GridImprint) -> bool {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if inner_coords.is_in_bounds(self.bounds()) && self[inner_coords].has_supply() {
-                            return true;
-                        }
-
-                    }
-                }
-            }
-        }
-        false
+        imprint.iter_covered_coords(coords)
+            .any(|inner_coords| inner_coords.is_in_bounds(self.bounds()) && self[inner_coords].has_supply())
     }
     /// At least one of the imprint's cells must have power.
     pub fn is_imprint_powered(&self, coords: GridCoords, imprint: GridImprint) -> bool {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if inner_coords.is_in_bounds(self.bounds()) && self[inner_coords].has_power() {
-                            return true;
-                        }
-
-                    }
-                }
-            }
-        }
-        false
+        imprint.iter_covered_coords(coords)
+            .any(|inner_coords| inner_coords.is_in_bounds(self.bounds()) && self[inner_coords].has_power())
+    }
+    /// Network of the first powered cell of the imprint.
+    pub fn imprint_network(&self, coords: GridCoords, imprint: GridImprint) -> Option<EnergyNetworkId> {
+        imprint.iter_covered_coords(coords)
+            .filter(|inner_coords| inner_coords.is_in_bounds(self.bounds()))
+            .find_map(|inner_coords| self[inner_coords].network())
     }
     pub fn reset_all_power_indicators(&mut self) {
-        self.grid.iter_mut().for_each(|field| field.set_power(false));
+        self.grid.iter_mut().for_each(|field| field.set_network(None));
         self.version = self.version.wrapping_add(1);
     }
 }
@@ -190,47 +185,48 @@ fn on_recalculate_power_system(
 // ============================================================================
 
 /// Component indicating that an entity uses power and should have its power state managed.
-/// Automatically manages HasPower/NoPower companion components based on energy grid state.
+/// Automatically manages HasPower/NoPower companion components based on energy grid state and the network's balance.
 /// Also stores the current power state directly for convenience access.
 #[derive(Component, Default)]
-#[require(GridCoords, GridImprint, NoPower)]
+#[require(GridCoords, GridImprint, NoPower, EnergyConsumption, EnergyPriority)]
 pub struct NeedsPower {
     pub has_power: bool,
+    /// Network the entity is connected to, if any.
+    pub network: Option<EnergyNetworkId>,
 }
 #[derive(Component, Default)]
 pub struct HasPower;
 #[derive(Component, Default)]
 pub struct NoPower;
+/// Companion to NoPower. The entity is connected to a network that can't cover its consumption.
+#[derive(Component, Default)]
+pub struct Brownout;
 
-impl NeedsPower {
-    fn on_add(
-        trigger: On<Add, NeedsPower>,
-        mut commands: Commands,
-        energy_supply_grid: Res<EnergySupplyGrid>,
-        mut power_query: Query<(&GridCoords, &GridImprint, &mut NeedsPower)>,
-    ) {
-        let entity = trigger.entity;
-        let Ok((grid_coords, grid_imprint, mut uses_power)) = power_query.get_mut(entity) else { return; };
-
-        let has_power = energy_supply_grid.is_imprint_powered(*grid_coords, *grid_imprint);
-        uses_power.set(&mut commands, entity, has_power);
-        commands.entity(entity).observe(Self::on_coords_change);
-    }
-
-    /// Local observer triggered when GridCoords or GridImprint changes on UsesPower entities
-    fn on_coords_change(
-        trigger: On<Insert, (GridCoords, GridImprint)>,
-        mut commands: Commands,
-        energy_supply_grid: Res<EnergySupplyGrid>,
-        mut power_query: Query<(&GridCoords, &GridImprint, &mut NeedsPower)>,
-    ) {
-        let entity = trigger.entity;
-        let Ok((grid_coords, grid_imprint, mut uses_power)) = power_query.get_mut(entity) else { return; };
+/// Production and consumption of a network connected to at least one generator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnergyNetwork {
+    pub production: f32,
+    /// Consumption of every consumer in the network, browned out ones included. Consumers disabled by the player don't count.
+    pub demand: f32,
+    /// Consumption actually covered by the production.
+    pub supplied: f32,
+}
+impl EnergyNetwork {
+    pub fn has_brownout(&self) -> bool { self.supplied < self.demand }
+}
 
-        let has_power = energy_supply_grid.is_imprint_powered(*grid_coords, *grid_imprint);
-        uses_power.set(&mut commands, entity, has_power);
+/// Balance of every network, refreshed by `NeedsPower::balance_system()`.
+#[derive(Resource, Default)]
+pub struct EnergyNetworks {
+    networks: HashMap<EnergyNetworkId, EnergyNetwork>,
+}
+impl EnergyNetworks {
+    pub fn get(&self, network: EnergyNetworkId) -> Option<&EnergyNetwork> {
+        self.networks.get(&network)
     }
+}
 
+impl NeedsPower {
     /// Set the expected value and manage companion components
     fn set(&mut self, commands: &mut Commands, entity: Entity, has_power: bool) {
         if self.has_power != has_power {
@@ -243,20 +239,152 @@ impl NeedsPower {
         self.has_power = has_power;
     }
 
-    /// System that updates power states when the energy grid changes
-    fn update_system(
+    /// The balance only changes with the networks, priorities, disabling, production or consumption of the buildings.
+    fn needs_balancing(
         energy_supply_grid: Res<EnergySupplyGrid>,
+        changed_consumers: Query<(), (With<NeedsPower>, Or<(Added<NeedsPower>, Changed<EnergyPriority>, Changed<EnergyConsumption>, Added<DisabledByPlayer>)>)>,
+        changed_generators: Query<(), (With<GeneratorEnergy>, Changed<EnergyProduction>)>,
+        mut removed_consumers: RemovedComponents<NeedsPower>,
+        mut removed_generators: RemovedComponents<GeneratorEnergy>,
+        mut removed_disabled: RemovedComponents<DisabledByPlayer>,
+        mut last_grid_version: Local<Option<GridVersion>>,
+    ) -> bool {
+        let grid_changed = energy_supply_grid.is_added() || *last_grid_version != Some(energy_supply_grid.version);
+        *last_grid_version = Some(energy_supply_grid.version);
+        // Drain every reader, so old removals don't trigger another balance next frame
+        let any_removed = removed_consumers.read().count() + removed_generators.read().count() + removed_disabled.read().count() > 0;
+        grid_changed || any_removed || !changed_consumers.is_empty() || !changed_generators.is_empty()
+    }
+
+    /// Applies `allocate_energy()` to every consumer and keeps `EnergyNetworks` up to date.
+    fn balance_system(
         mut commands: Commands,
-        mut power_entities: Query<(Entity, &GridCoords, &GridImprint, &mut NeedsPower)>,
-        mut current_energy_supply_grid_version: Local<GridVersion>,
+        energy_supply_grid: Res<EnergySupplyGrid>,
+        mut energy_networks: ResMut<EnergyNetworks>,
+        generators: Query<(&GridCoords, &EnergyProduction), With<GeneratorEnergy>>,
+        mut consumers: Query<(Entity, &GridCoords, &GridImprint, &EnergyConsumption, &EnergyPriority, Has<DisabledByPlayer>, Has<Brownout>, &mut NeedsPower)>,
     ) {
-        // Only run when energy grid version changes
-        if *current_energy_supply_grid_version == energy_supply_grid.version { return; }
-        *current_energy_supply_grid_version = energy_supply_grid.version;
-        
-        for (entity, grid_coords, grid_imprint, mut uses_power) in power_entities.iter_mut() {
-            let has_power = energy_supply_grid.is_imprint_powered(*grid_coords, *grid_imprint);
-            uses_power.set(&mut commands, entity, has_power);
+        let mut networks: HashMap<EnergyNetworkId, EnergyNetwork> = HashMap::default();
+        for (coords, production) in generators.iter() {
+            if !coords.is_in_bounds(energy_supply_grid.bounds()) { continue; }
+            let Some(network) = energy_supply_grid[*coords].network() else { continue; };
+            networks.entry(network).or_default().production += production.0;
         }
+
+        let (entities, claims): (Vec<_>, Vec<_>) = consumers.iter()
+            .map(|(entity, coords, imprint, consumption, priority, is_disabled, ..)| (entity, EnergyClaim {
+                network: energy_supply_grid.imprint_network(*coords, *imprint),
+                priority: *priority,
+                coords: *coords,
+                consumption: consumption.0,
+                is_disabled,
+            }))
+            .unzip();
+        let browned_out = allocate_energy(&mut networks, &claims);
+
+        for ((entity, claim), is_browned_out) in entities.into_iter().zip(claims).zip(browned_out) {
+            let has_power = claim.network.is_some() && !is_browned_out;
+            let Ok((_, _, _, _, _, _, has_brownout, mut needs_power)) = consumers.get_mut(entity) else { continue; };
+            if needs_power.has_power != has_power || needs_power.network != claim.network {
+                needs_power.network = claim.network;
+                needs_power.set(&mut commands, entity, has_power);
+            }
+            if is_browned_out && !has_brownout {
+                commands.entity(entity).insert(Brownout);
+            } else if !is_browned_out && has_brownout {
+                commands.entity(entity).remove::<Brownout>();
+            }
+        }
+        energy_networks.networks = networks;
+    }
+}
+
+/// Consumer competing for the production of its network, see `allocate_energy()`.
+#[derive(Clone, Copy, Debug)]
+pub struct EnergyClaim {
+    pub network: Option<EnergyNetworkId>,
+    pub priority: EnergyPriority,
+    pub coords: GridCoords,
+    pub consumption: f32,
+    pub is_disabled: bool,
+}
+
+/// Serves the claims of every network from its production, highest priority first.
+/// A claim that doesn't fit into what is left gets browned out, smaller ones after it may still fit.
+/// Ties are broken by position, so replays split the energy the same way.
+/// Disabled claims draw nothing, claims outside of `networks` are left alone.
+/// Returns whether each claim is browned out, in the order of `claims`, and fills in the demand and supply of `networks`.
+pub fn allocate_energy(networks: &mut HashMap<EnergyNetworkId, EnergyNetwork>, claims: &[EnergyClaim]) -> Vec<bool> {
+    let mut order = (0..claims.len()).collect::<Vec<_>>();
+    order.sort_by_key(|index| (Reverse(claims[*index].priority), claims[*index].coords.y, claims[*index].coords.x));
+
+    let mut browned_out = vec![false; claims.len()];
+    for index in order {
+        let claim = &claims[index];
+        if claim.is_disabled { continue; }
+        let Some(balance) = claim.network.and_then(|network| networks.get_mut(&network)) else { continue; };
+        balance.demand += claim.consumption;
+        if balance.supplied + claim.consumption <= balance.production {
+            balance.supplied += claim.consumption;
+        } else {
+            browned_out[index] = true;
+        }
+    }
+    browned_out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETWORK: EnergyNetworkId = EnergyNetworkId(0);
+
+    fn network_producing(production: f32) -> HashMap<EnergyNetworkId, EnergyNetwork> {
+        HashMap::from_iter([(NETWORK, EnergyNetwork { production, ..default() })])
+    }
+
+    fn claim(priority: EnergyPriority, (x, y): (i32, i32), consumption: f32) -> EnergyClaim {
+        EnergyClaim { network: Some(NETWORK), priority, coords: GridCoords { x, y }, consumption, is_disabled: false }
+    }
+
+    #[test]
+    fn smaller_claims_fit_after_a_browned_out_one() {
+        let mut networks = network_producing(10.);
+        let claims = [
+            claim(EnergyPriority::High, (5, 5), 6.),
+            claim(EnergyPriority::Normal, (0, 0), 6.),
+            claim(EnergyPriority::Low, (1, 0), 3.),
+        ];
+        assert_eq!(allocate_energy(&mut networks, &claims), vec![false, true, false]);
+        let network = networks[&NETWORK];
+        assert_eq!((network.demand, network.supplied), (15., 9.));
+        assert!(network.has_brownout());
+    }
+
+    #[test]
+    fn disabled_claims_draw_nothing() {
+        let mut networks = network_producing(5.);
+        let claims = [
+            EnergyClaim { is_disabled: true, ..claim(EnergyPriority::High, (0, 0), 5.) },
+            claim(EnergyPriority::Normal, (1, 0), 5.),
+            EnergyClaim { network: None, ..claim(EnergyPriority::High, (2, 0), 5.) },
+        ];
+        assert_eq!(allocate_energy(&mut networks, &claims), vec![false, false, false]);
+        let network = networks[&NETWORK];
+        assert_eq!((network.demand, network.supplied), (5., 5.));
+        assert!(!network.has_brownout());
+    }
+
+    #[test]
+    fn ties_are_broken_by_position() {
+        let claims = [
+            claim(EnergyPriority::Normal, (3, 2), 5.),
+            claim(EnergyPriority::Normal, (1, 2), 5.),
+            claim(EnergyPriority::Normal, (9, 1), 5.),
+        ];
+        assert_eq!(allocate_energy(&mut network_producing(10.), &claims), vec![true, false, false]);
+        // The order the claims come in doesn't matter
+        let reversed = claims.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(allocate_energy(&mut network_producing(10.), &reversed), vec![false, false, true]);
     }
 }
@@ -0,0 +1,52 @@
+use crate::lib_prelude::*;
+use crate::grids::base::BaseGrid;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FlowStep {
+    #[default]
+    Unreachable,
+    /// The field itself is an energy supplier.
+    Target,
+    /// Next field on the cheapest way to the closest energy supplier.
+    Towards(GridCoords),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlowField {
+    /// Integrated cost of reaching the closest energy supplier.
+    pub cost: f32,
+    pub step: FlowStep,
+}
+impl Default for FlowField {
+    fn default() -> Self {
+        Self { cost: f32::INFINITY, step: FlowStep::Unreachable }
+    }
+}
+
+/// Flow field towards the energy suppliers, shared by all wisps weighing the emissions the same way, see `flood_flow_field()`.
+/// The version is the `ObstacleGrid` and `TerrainGrid` versions the field was computed for, `None` until the first computation.
+pub type FlowFieldGrid = BaseGrid<FlowField, Option<(GridVersion, GridVersion)>>;
+
+impl FlowFieldGrid {
+    pub fn is_up_to_date(&self, obstacle_grid_version: GridVersion, terrain_grid_version: GridVersion) -> bool {
+        self.version == Some((obstacle_grid_version, terrain_grid_version))
+    }
+    /// Follows the field from `start_coords`. The path excludes the start and ends on the energy supplier.
+    pub fn path_from(&self, start_coords: GridCoords) -> Option<Vec<GridCoords>> {
+        if !start_coords.is_in_bounds(self.bounds()) { return None; }
+        let mut path = Vec::new();
+        let mut coords = start_coords;
+        // The field has no cycles, the limit only guards against a corrupted one
+        for _ in 0..self.grid.len() {
+            match self[coords].step {
+                FlowStep::Unreachable => return None,
+                FlowStep::Target => return Some(path),
+                FlowStep::Towards(next_coords) => {
+                    path.push(next_coords);
+                    coords = next_coords;
+                }
+            }
+        }
+        None
+    }
+}
@@ -5,6 +5,8 @@ pub mod visited;
 pub mod emissions;
 pub mod energy_supply;
 pub mod tower_ranges;
+pub mod flow_field;
+pub mod terrain;
 
 use crate::lib_prelude::*;
 
@@ -18,6 +20,7 @@ impl Plugin for GridsPlugin {
                 obstacles::ObstaclesGridPlugin,
                 wisps::WispsGridPlugin,
                 tower_ranges::TowerRangesPlugin,
+                terrain::TerrainPlugin,
             ));
     }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use crate::lib_prelude::*;
 use crate::grids::base::BaseGrid;
+use crate::grids::energy_supply::SupplierEnergyEnabled;
 
 pub struct ObstaclesGridPlugin;
 impl Plugin for ObstaclesGridPlugin {
@@ -11,6 +12,8 @@ impl Plugin for ObstaclesGridPlugin {
             .add_systems(First, ReservedCoords::clear_system)
             .add_observer(on_obstacle_grid_object_inserted)
             .add_observer(on_obstacle_grid_object_removed)
+            .add_observer(on_supplier_energy_enabled)
+            .add_observer(on_supplier_energy_disabled)
             ;
     }
 }
@@ -20,6 +23,8 @@ pub struct Field {
     pub dark_ore: Option<Entity>,
     pub quantum_field: Option<Entity>,
     pub structure: GridStructureType,
+    /// Covered by an energy supplier with `SupplierEnergyEnabled`.
+    pub energy_supplier_enabled: bool,
 }
 impl Field {
     pub fn has_dark_ore(&self) -> bool {
@@ -37,6 +42,9 @@ impl Field {
     pub fn has_wall(&self) -> bool {
         matches!(self.structure, GridStructureType::Wall(_))
     }
+    pub fn has_enabled_energy_supplier(&self) -> bool {
+        self.energy_supplier_enabled && self.has_building()
+    }
     pub fn has_structure(&self) -> bool { 
         matches!(self.structure, GridStructureType::Wall(_) | GridStructureType::Building(..)) 
     }
@@ -54,30 +62,18 @@ pub type ObstacleGrid = BaseGrid<Field, GridVersion>;
 
 impl ObstacleGrid {
     pub fn imprint_structure(&mut self, coords: GridCoords, imprint: GridImprint, structure: GridStructureType) {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let index = self.index(coords.shifted((x, y)));
-                        self.grid[index].structure = structure.clone();
-                    }
-                }
-            }
+        for inner_coords in imprint.iter_covered_coords(coords) {
+            let index = self.index(inner_coords);
+            self.grid[index].structure = structure.clone();
         }
-        self.version = self.version.wrapping_add(1);
+        self.bump_version(imprint.covered_coords(coords));
     }
     pub fn deprint_structure(&mut self, coords: GridCoords, imprint: GridImprint) {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let index = self.index(coords.shifted((x, y)));
-                        self.grid[index].structure = GridStructureType::Empty;
-                    }
-                }
-            }
+        for inner_coords in imprint.iter_covered_coords(coords) {
+            let index = self.index(inner_coords);
+            self.grid[index].structure = GridStructureType::Empty;
         }
-        self.version = self.version.wrapping_add(1);
+        self.bump_version(imprint.covered_coords(coords));
     }
     // Naive reprint that deprints all in old coords and hard imprints in new coords
     pub fn reprint_structure(&mut self, old_coords: GridCoords, new_coords: GridCoords, imprint: GridImprint, new_structure: GridStructureType) {
@@ -85,85 +81,38 @@ impl ObstacleGrid {
         self.imprint_structure(new_coords, imprint, new_structure);
     }
     pub fn query_imprint_all(&self, coords: GridCoords, imprint: GridImprint, query: fn(&Field) -> bool) -> bool {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if inner_coords.is_in_bounds(self.bounds()) && !query(&self[inner_coords]) {
-                            return false;
-                        }
-                    }
-                }
-            }
-        }
-        true
+        imprint.iter_covered_coords(coords)
+            .filter(|inner_coords| inner_coords.is_in_bounds(self.bounds()))
+            .all(|inner_coords| query(&self[inner_coords]))
     }
     pub fn query_imprint_any(&self, coords: GridCoords, imprint: GridImprint, query: fn(&Field) -> bool) -> bool {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if inner_coords.is_in_bounds(self.bounds()) && query(&self[inner_coords]) {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
-        false
+        imprint.iter_covered_coords(coords)
+            .filter(|inner_coords| inner_coords.is_in_bounds(self.bounds()))
+            .any(|inner_coords| query(&self[inner_coords]))
     }
     pub fn query_imprint_count(&self, coords: GridCoords, imprint: GridImprint, query: fn(&Field) -> bool) -> usize {
-        let mut count = 0;
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if inner_coords.is_in_bounds(self.bounds()) && query(&self[inner_coords]) {
-                            count += 1;
-                        }
-                    }
-                }
-            }
-        }
-        count
+        imprint.iter_covered_coords(coords)
+            .filter(|inner_coords| inner_coords.is_in_bounds(self.bounds()) && query(&self[*inner_coords]))
+            .count()
     }
     pub fn query_imprint_element<T>(&self, coords: GridCoords, imprint: GridImprint, query: fn(&Field) -> Option<T>) -> Vec<T> {
-        let mut vec = Vec::new();
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if inner_coords.is_in_bounds(self.bounds()) {
-                            if let Some(element) = query(&self[inner_coords]) {
-                                vec.push(element);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        vec
+        imprint.iter_covered_coords(coords)
+            .filter(|inner_coords| inner_coords.is_in_bounds(self.bounds()))
+            .filter_map(|inner_coords| query(&self[inner_coords]))
+            .collect()
     }
 
     pub fn query_building_placement(&self, coords: GridCoords, building_type: BuildingType, imprint: GridImprint) -> bool {
         match building_type {
             BuildingType::MiningComplex => {
                 //MiningComplex requires at least one DarkOre cell and no other obstacles
-                let GridImprint::Rectangle{ width, height } = imprint;
                 let mut has_dark_ore = false;
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if !inner_coords.is_in_bounds(self.bounds()) { return false; }
-                        
-                        let field = &self[inner_coords];
-                        if field.is_within_quantum_field() || field.has_structure() { return false }
-                        if field.has_dark_ore() { has_dark_ore = true; }
-                    }
+                for inner_coords in imprint.iter_covered_coords(coords) {
+                    if !inner_coords.is_in_bounds(self.bounds()) { return false; }
+
+                    let field = &self[inner_coords];
+                    if field.is_within_quantum_field() || field.has_structure() { return false }
+                    if field.has_dark_ore() { has_dark_ore = true; }
                 }
                 return has_dark_ore;
             },
@@ -172,17 +121,37 @@ impl ObstacleGrid {
     }
 
     pub fn imprint_custom(&mut self, coords: GridCoords, imprint: GridImprint, imprint_fn: impl Fn(&mut Field)) {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let index = self.index(coords.shifted((x, y)));
-                        imprint_fn(&mut self.grid[index]);
-                    }
-                }
+        for inner_coords in imprint.iter_covered_coords(coords) {
+            let index = self.index(inner_coords);
+            imprint_fn(&mut self.grid[index]);
+        }
+        self.bump_version(imprint.covered_coords(coords));
+    }
+    /// Unlike `imprint_custom()` the version only changes if any field actually changed, as the supplier state gets refreshed often.
+    pub fn set_energy_supplier_enabled(&mut self, coords: GridCoords, imprint: GridImprint, enabled: bool) {
+        let changed_coords = imprint.covered_coords(coords).into_iter()
+            .filter(|coords| coords.is_in_bounds(self.bounds()) && self[*coords].energy_supplier_enabled != enabled)
+            .collect::<Vec<_>>();
+        if changed_coords.is_empty() { return; }
+        changed_coords.iter().for_each(|coords| self[*coords].energy_supplier_enabled = enabled);
+        self.bump_version(changed_coords);
+    }
+    /// Whether `start` or any field a wisp walks through following `path` from it changed after `since_version`.
+    /// Diagonal steps also cut through both corner fields.
+    pub fn path_changed_since<'a>(&self, start: GridCoords, path: impl IntoIterator<Item = &'a GridCoords>, since_version: GridVersion) -> bool {
+        if self.version == since_version { return false; }
+        if self.changed_since(start, since_version) { return true; }
+        let mut previous = start;
+        for &coords in path {
+            if self.changed_since(coords, since_version) { return true; }
+            if previous.x != coords.x && previous.y != coords.y
+                && (self.changed_since(GridCoords { x: previous.x, y: coords.y }, since_version)
+                    || self.changed_since(GridCoords { x: coords.x, y: previous.y }, since_version)) {
+                return true;
             }
+            previous = coords;
         }
-        self.version = self.version.wrapping_add(1);
+        false
     }
 }
 
@@ -233,6 +202,23 @@ fn on_obstacle_grid_object_removed(
     }
 }
 
+fn on_supplier_energy_enabled(
+    trigger: On<Insert, SupplierEnergyEnabled>,
+    mut obstacle_grid: ResMut<ObstacleGrid>,
+    suppliers: Query<(&GridImprint, &GridCoords)>,
+) {
+    let Ok((grid_imprint, grid_coords)) = suppliers.get(trigger.entity) else { return; };
+    obstacle_grid.set_energy_supplier_enabled(*grid_coords, *grid_imprint, true);
+}
+fn on_supplier_energy_disabled(
+    trigger: On<Remove, SupplierEnergyEnabled>,
+    mut obstacle_grid: ResMut<ObstacleGrid>,
+    suppliers: Query<(&GridImprint, &GridCoords)>,
+) {
+    let Ok((grid_imprint, grid_coords)) = suppliers.get(trigger.entity) else { return; };
+    obstacle_grid.set_energy_supplier_enabled(*grid_coords, *grid_imprint, false);
+}
+
 // When placing objects on map sometimes we want to reserve space so the state can be changed async later while ensuring that no other object can be placed there in parallel systems.
 // This resourvation are cleared in the Firs schedule of the following frame.
 #[derive(Resource, Default)]
@@ -241,31 +227,69 @@ pub struct ReservedCoords {
 }
 impl ReservedCoords {
     pub fn reserve(&mut self, coords: GridCoords, imprint: GridImprint) {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        self.for_structures.insert(inner_coords);
-                    }
-                }
-            }
-        }
+        self.for_structures.extend(imprint.iter_covered_coords(coords));
     }
     pub fn any_reserved(&self, coords: GridCoords, imprint: GridImprint) -> bool {
-        match imprint {
-            GridImprint::Rectangle { width, height } => {
-                for y in 0..height {
-                    for x in 0..width {
-                        let inner_coords = coords.shifted((x, y));
-                        if self.for_structures.contains(&inner_coords) { return true; }
-                    }
-                }
-                false
-            }
-        }
+        imprint.iter_covered_coords(coords).any(|inner_coords| self.for_structures.contains(&inner_coords))
     }
     fn clear_system(mut reserved_coords: ResMut<ReservedCoords>) {
         reserved_coords.for_structures.clear();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::grids::base::CHUNK_SIZE;
+    use super::*;
+
+    /// 3 x 2 chunks with a wall placed at `wall` after the returned version.
+    fn grid_changed_at(wall: (i32, i32)) -> (ObstacleGrid, GridVersion) {
+        let mut obstacle_grid = ObstacleGrid::new_with_size(3 * CHUNK_SIZE, 2 * CHUNK_SIZE);
+        let since = obstacle_grid.version;
+        obstacle_grid.imprint_structure(wall.into(), GridImprint::Rectangle { width: 1, height: 1 }, GridStructureType::Wall(Entity::PLACEHOLDER));
+        (obstacle_grid, since)
+    }
+
+    fn path(coords: &[(i32, i32)]) -> Vec<GridCoords> {
+        coords.iter().map(|coords| (*coords).into()).collect()
+    }
+
+    #[test]
+    fn change_in_a_crossed_chunk_invalidates_the_path() {
+        let (obstacle_grid, since) = grid_changed_at((CHUNK_SIZE + 4, 4));
+        let path = path(&[(3, 4), (4, 4), (CHUNK_SIZE + 1, 4), (CHUNK_SIZE + 2, 4)]);
+        assert!(obstacle_grid.path_changed_since((2, 4).into(), &path, since));
+        assert!(obstacle_grid.path_changed_since((CHUNK_SIZE + 2, 3).into(), &[], since), "The start counts too");
+    }
+
+    #[test]
+    fn change_in_an_untouched_chunk_keeps_the_path() {
+        let (obstacle_grid, since) = grid_changed_at((CHUNK_SIZE + 4, CHUNK_SIZE + 4));
+        let path = path(&[(3, 4), (4, 4), (CHUNK_SIZE + 1, 4), (2 * CHUNK_SIZE + 1, 4)]);
+        assert!(!obstacle_grid.path_changed_since((2, 4).into(), &path, since));
+        assert!(!obstacle_grid.path_changed_since((2, 4).into(), &path, obstacle_grid.version));
+    }
+
+    #[test]
+    fn change_on_the_chunk_boundary() {
+        // The first field of the next chunk changed, paths ending on the last field before it are fine
+        let (obstacle_grid, since) = grid_changed_at((CHUNK_SIZE, 4));
+        let up_to_boundary = path(&[(CHUNK_SIZE - 2, 4), (CHUNK_SIZE - 1, 4)]);
+        assert!(!obstacle_grid.path_changed_since((CHUNK_SIZE - 3, 4).into(), &up_to_boundary, since));
+        let across_boundary = path(&[(CHUNK_SIZE - 1, 4), (CHUNK_SIZE, 4)]);
+        assert!(obstacle_grid.path_changed_since((CHUNK_SIZE - 2, 4).into(), &across_boundary, since));
+
+        // The last field of the chunk changed
+        let (obstacle_grid, since) = grid_changed_at((CHUNK_SIZE - 1, 4));
+        let from_next_chunk = path(&[(CHUNK_SIZE + 1, 4), (CHUNK_SIZE + 2, 4)]);
+        assert!(!obstacle_grid.path_changed_since((CHUNK_SIZE, 4).into(), &from_next_chunk, since));
+        assert!(obstacle_grid.path_changed_since((CHUNK_SIZE - 1, 3).into(), &from_next_chunk, since));
+    }
+
+    #[test]
+    fn diagonal_steps_check_the_corners_they_cut() {
+        // Only the corner (CHUNK_SIZE, CHUNK_SIZE) of the step from (CHUNK_SIZE - 1, CHUNK_SIZE) lies in the changed chunk
+        let (obstacle_grid, since) = grid_changed_at((CHUNK_SIZE + 2, CHUNK_SIZE + 2));
+        let path = path(&[(CHUNK_SIZE - 1, CHUNK_SIZE), (CHUNK_SIZE, CHUNK_SIZE - 1), (CHUNK_SIZE + 1, CHUNK_SIZE - 2)]);
+        assert!(obstacle_grid.path_changed_since((CHUNK_SIZE - 2, CHUNK_SIZE + 1).into(), &path, since));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::lib_prelude::*;
+use crate::grids::base::BaseGrid;
+
+pub struct TerrainPlugin;
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(TerrainGrid::new_empty())
+            .add_systems(OnExit(MapLoadingStage::LoadMapInfo), |mut commands: Commands, map_info: Res<MapInfo>| { commands.insert_resource(TerrainGrid::new_with_size(map_info.grid_width, map_info.grid_height)); })
+            ;
+    }
+}
+
+pub type TerrainGrid = BaseGrid<TerrainType, GridVersion>;
+
+impl TerrainGrid {
+    pub fn set_terrain(&mut self, coords: GridCoords, terrain_type: TerrainType) {
+        if self[coords] == terrain_type { return; }
+        self[coords] = terrain_type;
+        self.bump_version([coords]);
+    }
+    /// Speed multiplier of the field, fields outside of the map count as plain ground.
+    pub fn speed_multiplier_at(&self, coords: GridCoords) -> f32 {
+        if coords.is_in_bounds(self.bounds()) { self[coords].speed_multiplier() } else { 1. }
+    }
+    /// Fields with anything other than plain ground, row by row.
+    pub fn iter_non_plain(&self) -> impl Iterator<Item = (GridCoords, TerrainType)> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| GridCoords { x, y }))
+            .map(|coords| (coords, self[coords]))
+            .filter(|(_, terrain_type)| *terrain_type != TerrainType::Plain)
+    }
+}
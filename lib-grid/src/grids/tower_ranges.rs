@@ -1,6 +1,5 @@
 use crate::lib_prelude::*;
 use crate::grids::base::BaseGrid;
-use crate::search::flooding::{flood_tower_range, FloodTowerRangeMode};
 
 pub struct TowerRangesPlugin;
 impl Plugin for TowerRangesPlugin {
@@ -18,28 +17,32 @@ pub type TowerRangesGrid = BaseGrid<HashSet<Entity>, GridVersion>;
 impl TowerRangesGrid {
     pub fn add_tower(&mut self, coords: GridCoords, tower: Entity) {
         self[coords].insert(tower);
-        self.version = self.version.wrapping_add(1);
+        self.bump_version([coords]);
     }
     pub fn remove_tower(&mut self, coords: GridCoords, tower: Entity) {
         self[coords].remove(&tower);
-        self.version = self.version.wrapping_add(1);
+        self.bump_version([coords]);
     }
     fn on_tower_added(
-        trigger: On<Insert, AttackRange>,
+        trigger: On<Insert, (AttackRange, TowerRangeShape)>,
         mut tower_ranges_grid: ResMut<TowerRangesGrid>,
-        towers: Query<(&GridCoords, &GridImprint, &AttackRange), With<Tower>>,
+        towers: Query<(&GridCoords, &GridImprint, &AttackRange, &TowerRangeShape), With<Tower>>,
     ) {
         let entity = trigger.entity;
-        let Ok((grid_coords, grid_imprint, attack_range)) = towers.get(entity) else { return; };
-        flood_tower_range(&mut tower_ranges_grid, &grid_imprint.covered_coords(*grid_coords), FloodTowerRangeMode::Add, attack_range.0 as usize, entity);
+        let Ok((grid_coords, grid_imprint, attack_range, range_shape)) = towers.get(entity) else { return; };
+        for coords in range_shape.covered_coords(*grid_coords, *grid_imprint, attack_range.0, tower_ranges_grid.bounds()) {
+            tower_ranges_grid.add_tower(coords, entity);
+        }
     }
     fn on_tower_removed(
-        trigger: On<Replace, AttackRange>,
+        trigger: On<Replace, (AttackRange, TowerRangeShape)>,
         mut tower_ranges_grid: ResMut<TowerRangesGrid>,
-        towers: Query<(&GridCoords, &GridImprint, &AttackRange), With<Tower>>,
+        towers: Query<(&GridCoords, &GridImprint, &AttackRange, &TowerRangeShape), With<Tower>>,
     ) {
         let entity = trigger.entity;
-        let Ok((grid_coords, grid_imprint, attack_range)) = towers.get(entity) else { return; };
-        flood_tower_range(&mut tower_ranges_grid, &grid_imprint.covered_coords(*grid_coords), FloodTowerRangeMode::Remove, attack_range.0 as usize, entity);
+        let Ok((grid_coords, grid_imprint, attack_range, range_shape)) = towers.get(entity) else { return; };
+        for coords in range_shape.covered_coords(*grid_coords, *grid_imprint, attack_range.0, tower_ranges_grid.bounds()) {
+            tower_ranges_grid.remove_tower(coords, entity);
+        }
     }
 }
@@ -1,3 +1,5 @@
+use bevy::ecs::query::QueryFilter;
+
 use crate::lib_prelude::*;
 use crate::grids::base::BaseGrid;
 
@@ -15,15 +17,187 @@ pub type WispsGrid = BaseGrid<Vec<Entity>, GridVersion>;
 impl WispsGrid {
     pub fn wisp_add(&mut self, coords: GridCoords, wisp: Entity) {
         self[coords].push(wisp);
-        self.version = self.version.wrapping_add(1);
+        self.bump_version([coords]);
     }
     pub fn wisp_remove(&mut self, coords: GridCoords, wisp: Entity) {
         let Some(pos) = self[coords].iter().position(|x| *x == wisp) else { return; };
         self[coords].swap_remove(pos);
-        self.version = self.version.wrapping_add(1);
+        self.bump_version([coords]);
     }
     pub fn wisp_move(&mut self, from_coords: GridCoords, to_coords: GridCoords, wisp: Entity) {
         self.wisp_remove(from_coords, wisp);
         self.wisp_add(to_coords, wisp);
     }
-}
\ No newline at end of file
+
+    /// Wisps whose position is within `radius` of `center`. `distance` is measured from `center`.
+    pub fn wisps_in_radius<F: QueryFilter>(&self, transforms: &Query<&Transform, F>, center: Vec2, radius: f32) -> Vec<WispHit> {
+        let reach = Vec2::splat(radius);
+        self.wisps_in_world_rect(transforms, center - reach, center + reach)
+            .map(|(entity, position)| WispHit { entity, position, distance: position.distance(center) })
+            .filter(|hit| hit.distance <= radius)
+            .collect()
+    }
+
+    /// Wisps whose position is within the world rectangle. `distance` is measured from the rectangle center.
+    pub fn wisps_in_rect<F: QueryFilter>(&self, transforms: &Query<&Transform, F>, min: Vec2, max: Vec2) -> Vec<WispHit> {
+        let center = (min + max) / 2.;
+        self.wisps_in_world_rect(transforms, min, max)
+            .map(|(entity, position)| WispHit { entity, position, distance: position.distance(center) })
+            .collect()
+    }
+
+    /// Up to `k` wisps closest to `center`, nearest first.
+    /// Searches square rings of fields around `center` until no unvisited wisp can be closer than the k-th found.
+    pub fn k_nearest<F: QueryFilter>(&self, transforms: &Query<&Transform, F>, center: Vec2, k: usize) -> Vec<WispHit> {
+        let mut hits = Vec::new();
+        if k == 0 { return hits; }
+        let center_coords = GridCoords::from_world_vec2(center);
+        let max_ring = [center_coords.x, self.width - 1 - center_coords.x, center_coords.y, self.height - 1 - center_coords.y]
+            .into_iter().map(i32::abs).max().unwrap_or_default();
+        for ring in 0..=max_ring {
+            let ring_coords = Self::ring_offsets(ring)
+                .map(|offset| center_coords.shifted(offset))
+                .filter(|coords| coords.is_in_bounds(self.bounds()));
+            for coords in ring_coords {
+                hits.extend(self[coords].iter().filter_map(|entity| {
+                    let position = transforms.get(*entity).ok()?.translation.xy();
+                    Some(WispHit { entity: *entity, position, distance: position.distance(center) })
+                }));
+            }
+            hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+            // Wisps outside of the visited rings are at least `ring` fields away
+            if hits.len() >= k && hits[k - 1].distance <= ring as f32 * CELL_SIZE { break; }
+        }
+        hits.truncate(k);
+        hits
+    }
+
+    /// Wisps whose position is within `half_width` of the segment from `a` to `b`, in the order the segment passes them.
+    /// `distance` is measured from the segment.
+    pub fn wisps_along_segment<F: QueryFilter>(&self, transforms: &Query<&Transform, F>, a: Vec2, b: Vec2, half_width: f32) -> Vec<WispHit> {
+        let reach = Vec2::splat(half_width);
+        let segment = b - a;
+        let length_squared = segment.length_squared();
+        let mut hits = self.wisps_in_world_rect(transforms, a.min(b) - reach, a.max(b) + reach)
+            .filter_map(|(entity, position)| {
+                let progress = if length_squared > 0. { ((position - a).dot(segment) / length_squared).clamp(0., 1.) } else { 0. };
+                let distance = position.distance(a + segment * progress);
+                (distance <= half_width).then_some((progress, WispHit { entity, position, distance }))
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        hits.into_iter().map(|(_, hit)| hit).collect()
+    }
+
+    /// Offsets of the fields forming the square ring `ring` fields away from the center.
+    fn ring_offsets(ring: i32) -> impl Iterator<Item = (i32, i32)> {
+        let horizontal = (-ring..=ring).flat_map(move |dx| std::iter::once((dx, -ring)).chain((ring > 0).then_some((dx, ring))));
+        let vertical = (1 - ring..ring).flat_map(move |dy| [(-ring, dy), (ring, dy)]);
+        horizontal.chain(vertical)
+    }
+
+    /// Wisps with their positions, for every wisp whose position is within the world rectangle.
+    /// Wisps are kept in the field containing their position, so only the fields overlapping the rectangle are checked.
+    fn wisps_in_world_rect<'a, F: QueryFilter>(&'a self, transforms: &'a Query<&Transform, F>, min: Vec2, max: Vec2) -> impl Iterator<Item = (Entity, Vec2)> + 'a {
+        let min_coords = GridCoords::from_world_vec2(min);
+        let max_coords = GridCoords::from_world_vec2(max);
+        (min_coords.y.max(0)..=max_coords.y.min(self.height - 1))
+            .flat_map(move |y| (min_coords.x.max(0)..=max_coords.x.min(self.width - 1)).map(move |x| GridCoords { x, y }))
+            .flat_map(move |coords| self[coords].iter())
+            .filter_map(move |entity| {
+                let position = transforms.get(*entity).ok()?.translation.xy();
+                let is_inside = position.cmpge(min).all() && position.cmple(max).all();
+                is_inside.then_some((*entity, position))
+            })
+    }
+}
+
+/// Wisp found by a spatial query of `WispsGrid`. What `distance` is measured from depends on the query.
+#[derive(Clone, Copy, Debug)]
+pub struct WispHit {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub distance: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// World position of a point given in fields.
+    fn at(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y) * CELL_SIZE
+    }
+
+    /// 20 x 20 fields with a wisp at each of `positions`.
+    fn world_with_wisps(positions: &[Vec2]) -> (World, Vec<Entity>) {
+        let mut world = World::new();
+        let mut wisps_grid = WispsGrid::new_with_size(20, 20);
+        let wisps = positions.iter().map(|position| {
+            let wisp = world.spawn(Transform::from_translation(position.extend(0.))).id();
+            wisps_grid.wisp_add(GridCoords::from_world_vec2(*position), wisp);
+            wisp
+        }).collect();
+        world.insert_resource(wisps_grid);
+        (world, wisps)
+    }
+
+    fn run_query(world: &mut World, query: impl Fn(&WispsGrid, &Query<&Transform>) -> Vec<WispHit> + Send + Sync + 'static) -> Vec<WispHit> {
+        world.run_system_once(move |wisps_grid: Res<WispsGrid>, transforms: Query<&Transform>| query(&wisps_grid, &transforms))
+            .expect("The query system runs")
+    }
+
+    fn entities(hits: &[WispHit]) -> Vec<Entity> {
+        hits.iter().map(|hit| hit.entity).collect()
+    }
+
+    #[test]
+    fn k_nearest_returns_the_closest_first() {
+        let (mut world, wisps) = world_with_wisps(&[at(11.5, 10.5), at(10.5, 13.5), at(15.5, 10.5), at(10.5, 10.9)]);
+        let center = at(10.5, 10.5);
+        let hits = run_query(&mut world, move |wisps_grid, transforms| wisps_grid.k_nearest(transforms, center, 3));
+        assert_eq!(entities(&hits), vec![wisps[3], wisps[0], wisps[1]]);
+        assert!((hits[1].distance - CELL_SIZE).abs() < 1e-3);
+        assert!(run_query(&mut world, move |wisps_grid, transforms| wisps_grid.k_nearest(transforms, center, 0)).is_empty());
+    }
+
+    #[test]
+    fn k_nearest_looks_past_the_ring_of_the_first_hit() {
+        // The wisp in the first ring sits in its far corner, the one two rings out is closer to the center
+        let (mut world, wisps) = world_with_wisps(&[at(9.05, 11.95), at(12.05, 10.5)]);
+        let center = at(10.9, 10.5);
+        let hits = run_query(&mut world, move |wisps_grid, transforms| wisps_grid.k_nearest(transforms, center, 1));
+        assert_eq!(entities(&hits), vec![wisps[1]]);
+    }
+
+    #[test]
+    fn k_nearest_near_the_map_edge() {
+        let (mut world, wisps) = world_with_wisps(&[at(19.5, 0.5), at(0.5, 18.5)]);
+        let center = at(0.5, 0.5);
+        let hits = run_query(&mut world, move |wisps_grid, transforms| wisps_grid.k_nearest(transforms, center, 5));
+        assert_eq!(entities(&hits), vec![wisps[1], wisps[0]]);
+    }
+
+    #[test]
+    fn wisps_in_rect_include_the_edges() {
+        let (mut world, wisps) = world_with_wisps(&[at(3., 3.), at(6., 4.), at(6.1, 3.), at(1.9, 3.), at(4., 3.)]);
+        let hits = run_query(&mut world, |wisps_grid, transforms| wisps_grid.wisps_in_rect(transforms, at(2., 2.), at(6., 4.)));
+        let mut found = entities(&hits);
+        found.sort();
+        let mut expected = vec![wisps[0], wisps[1], wisps[4]];
+        expected.sort();
+        assert_eq!(found, expected);
+        let center_hit = hits.iter().find(|hit| hit.entity == wisps[4]).unwrap();
+        assert!(center_hit.distance.abs() < 1e-3);
+    }
+
+    #[test]
+    fn wisps_along_segment_come_in_the_order_the_segment_passes_them() {
+        let (mut world, wisps) = world_with_wisps(&[at(8., 5.2), at(2., 4.8), at(5., 5.), at(5., 6.), at(0.8, 5.), at(10.3, 5.)]);
+        let hits = run_query(&mut world, |wisps_grid, transforms| wisps_grid.wisps_along_segment(transforms, at(1., 5.), at(10., 5.), 0.5 * CELL_SIZE));
+        assert_eq!(entities(&hits), vec![wisps[4], wisps[1], wisps[2], wisps[0], wisps[5]]);
+        assert!((hits[3].distance - 0.2 * CELL_SIZE).abs() < 1e-3);
+    }
+}
@@ -9,5 +9,5 @@ pub mod lib_prelude {
 }
 
 pub mod prelude {
-    pub use crate::grids::energy_supply::{NeedsPower, HasPower, NoPower};
+    pub use crate::grids::energy_supply::{NeedsPower, HasPower, NoPower, Brownout};
 }
\ No newline at end of file
@@ -1,9 +1,8 @@
 use std::collections::VecDeque;
 
-use crate::grids::tower_ranges::TowerRangesGrid;
 use crate::lib_prelude::*;
 use crate::grids::emissions::{EmissionsGrid, EmissionsType};
-use crate::grids::energy_supply::EnergySupplyGrid;
+use crate::grids::energy_supply::{EnergyNetworkId, EnergySupplyGrid};
 use crate::grids::obstacles::{Field, ObstacleGrid};
 
 use super::common::{VISITED_GRID, CARDINAL_DIRECTIONS};
@@ -102,11 +101,7 @@ fn apply_emissions_details(
             start_value * (-1. * decay * distance as f32).exp()
         },
     } * if matches!(details.mode, FloodEmissionsMode::Increase) { 1. } else { -1. };
-    match details.emissions_type {
-        EmissionsType::Energy => {
-            emissions_grid.add_energy(grid_coords, value);
-        }
-    }
+    emissions_grid.add_emissions(grid_coords, details.emissions_type, value);
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -158,83 +153,38 @@ pub fn flood_energy_supply<'a>(
 }
 
 
-/// Start with the list of generators coords and flood over all connected cells with energy supply
+/// Start with the list of generators coords and flood over all connected cells with energy supply.
+/// Every flood is a separate network; generators reached by an earlier flood join its network.
 pub fn flood_power_coverage<'a>(
     energy_supply_grid: &mut EnergySupplyGrid,
-    start_coords: impl IntoIterator<Item = &'a GridCoords> + Copy,
+    start_coords: impl IntoIterator<Item = &'a GridCoords>,
 ){
     energy_supply_grid.reset_all_power_indicators();
     VISITED_GRID.with_borrow_mut(|visited_grid| {
         visited_grid.resize_and_reset(energy_supply_grid.bounds());
         let mut queue = VecDeque::new();
-        start_coords.into_iter().for_each(|coords| {
-            queue.push_back(*coords);
-            visited_grid.set_visited(*coords);
-            energy_supply_grid[*coords].set_power(true);
-        });
-        while let Some(coords) = queue.pop_front() {
-            for (delta_x, delta_y) in CARDINAL_DIRECTIONS {
-                let new_coords = coords.shifted((delta_x, delta_y));
-                if !new_coords.is_in_bounds(energy_supply_grid.bounds())
-                    || visited_grid.is_visited(new_coords)
-                {
-                    continue;
-                }
+        for (index, start_coords) in start_coords.into_iter().enumerate() {
+            if !start_coords.is_in_bounds(energy_supply_grid.bounds()) || energy_supply_grid[*start_coords].network().is_some() { continue; }
+            let network = EnergyNetworkId(index as u32);
+            queue.push_back(*start_coords);
+            visited_grid.set_visited(*start_coords);
+            energy_supply_grid[*start_coords].set_network(Some(network));
+            while let Some(coords) = queue.pop_front() {
+                for (delta_x, delta_y) in CARDINAL_DIRECTIONS {
+                    let new_coords = coords.shifted((delta_x, delta_y));
+                    if !new_coords.is_in_bounds(energy_supply_grid.bounds())
+                        || visited_grid.is_visited(new_coords)
+                    {
+                        continue;
+                    }
 
-                visited_grid.set_visited(new_coords);
-                if energy_supply_grid[new_coords].has_supply() {
-                    queue.push_back(new_coords);
-                    energy_supply_grid[new_coords].set_power(true);
+                    visited_grid.set_visited(new_coords);
+                    if energy_supply_grid[new_coords].has_supply() {
+                        queue.push_back(new_coords);
+                        energy_supply_grid[new_coords].set_network(Some(network));
+                    }
                 }
             }
         }
     });
 }
-
-
-#[derive(Copy, Clone, Debug)]
-pub enum FloodTowerRangeMode {
-    Add,
-    Remove,
-}
-
-pub fn flood_tower_range<'a>(
-    tower_ranges_grid: &mut TowerRangesGrid,
-    start_coords: impl IntoIterator<Item = &'a GridCoords> + Copy,
-    mode: FloodTowerRangeMode,
-    range: usize,
-    tower_entity: Entity,
-) {
-    VISITED_GRID.with_borrow_mut(|visited_grid| {
-        visited_grid.resize_and_reset(tower_ranges_grid.bounds());
-        let mut queue = VecDeque::new();
-        start_coords.into_iter().for_each(|coords| {
-            match mode {
-                FloodTowerRangeMode::Add => tower_ranges_grid.add_tower(*coords, tower_entity),
-                FloodTowerRangeMode::Remove => tower_ranges_grid.remove_tower(*coords, tower_entity),
-            }
-            queue.push_back((0, *coords));
-            visited_grid.set_visited(*coords);
-        });
-        while let Some((distance, coords)) = queue.pop_front() {
-            for (delta_x, delta_y) in CARDINAL_DIRECTIONS {
-                let new_coords = coords.shifted((delta_x, delta_y));
-                if !new_coords.is_in_bounds(tower_ranges_grid.bounds())
-                    || visited_grid.is_visited(new_coords)
-                {
-                    continue;
-                }
-
-                visited_grid.set_visited(new_coords);
-                match mode {
-                    FloodTowerRangeMode::Add => tower_ranges_grid.add_tower(new_coords, tower_entity),
-                    FloodTowerRangeMode::Remove => tower_ranges_grid.remove_tower(new_coords, tower_entity),
-                }
-                let new_distance = distance + 1;
-                if new_distance < range {
-                    queue.push_back((new_distance, new_coords));
-                }
-            }
-        }
-    });
-}
\ No newline at end of file
@@ -1,4 +1,5 @@
 pub mod pathfinding;
 pub mod common;
 pub mod targetfinding;
-pub mod flooding;
\ No newline at end of file
+pub mod flooding;
+pub mod raycast;
\ No newline at end of file
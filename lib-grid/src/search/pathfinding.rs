@@ -1,8 +1,10 @@
 use std::collections::BinaryHeap;
 
 use crate::lib_prelude::*;
-use crate::grids::emissions::EmissionsGrid;
+use crate::grids::emissions::{EmissionsGrid, EmissionsWeights};
+use crate::grids::flow_field::{FlowField, FlowFieldGrid, FlowStep};
 use crate::grids::obstacles::{GridStructureType, ObstacleGrid};
+use crate::grids::terrain::TerrainGrid;
 use crate::search::common::{State, ALL_DIRECTIONS};
 
 use super::common::TRACKING_GRID;
@@ -10,18 +12,26 @@ use super::common::TRACKING_GRID;
 const EMPTY_FIELD_MODIFIER: f32 = 1.0;
 const BUILDING_FIELD_MODIFIER: f32 = 0.1;
 
+// Flow field costs of entering a field. Attracting emissions make fields cheaper, so wisps are drawn along them,
+// repelling ones make fields more expensive.
+const FLOW_EMPTY_FIELD_COST: f32 = 1.0;
+const FLOW_BUILDING_FIELD_COST: f32 = 2.0;
+const FLOW_EMISSIONS_ATTRACTION: f32 = 0.01;
+
 pub fn path_find_energy_beckon(
     obstacle_grid: &ObstacleGrid,
     emissions_grid: &EmissionsGrid,
+    terrain_grid: &TerrainGrid,
+    weights: &EmissionsWeights,
     start_coords: GridCoords,
 ) -> Option<Vec<GridCoords>> {
-    // BFS to find closest building field
+    // BFS to find closest building field. The cost carries the terrain cost travelled so far next to the priority.
     TRACKING_GRID.with_borrow_mut(|tracking| {
         tracking.resize_and_reset(obstacle_grid.bounds());
         let mut queue = BinaryHeap::new();
-        queue.push(State{ cost: f32::MIN, distance: 0, coords: start_coords });
+        queue.push(State{ cost: (f32::MIN, 0.), distance: 0, coords: start_coords });
         tracking.set_tracked(start_coords, start_coords);
-        while let Some(State{ distance, coords, .. }) = queue.pop() {
+        while let Some(State{ cost: (_, travelled), distance, coords }) = queue.pop() {
             for (delta_x, delta_y) in ALL_DIRECTIONS {
                 let new_coords = coords.shifted((delta_x, delta_y));
                 if !new_coords.is_in_bounds(obstacle_grid.bounds())
@@ -42,21 +52,185 @@ pub fn path_find_energy_beckon(
 
                 tracking.set_tracked(new_coords, coords);
                 let new_distance = distance + 1;
+                let new_travelled = travelled + terrain_grid[new_coords].path_cost();
+                // Suppliers turned off by the player or without power are passed like any other building
+                if obstacle_grid[new_coords].has_enabled_energy_supplier() {
+                    // Compile the path by backtracking
+                    return Some(tracking.compile_path(new_coords, start_coords));
+                }
+                let attraction = weights.attraction(&emissions_grid[new_coords]);
                 let new_cost = match obstacle_grid[new_coords].structure {
-                    GridStructureType::Building(_, building_type) => {
-                        // TODO: Currently finds the supplier even if its turned off(Disabled by player / not powered etc.)
-                        if building_type.is_energy_supplier() {
-                            // Compile the path by backtracking
-                            return Some(tracking.compile_path(new_coords, start_coords));
-                        } else {
-                            -emissions_grid[new_coords].energy * BUILDING_FIELD_MODIFIER + new_distance as f32
-                        }
-                    }
-                    _ => -emissions_grid[new_coords].energy * EMPTY_FIELD_MODIFIER + new_distance as f32,
+                    GridStructureType::Building(..) => -attraction * BUILDING_FIELD_MODIFIER + new_travelled,
+                    _ => -attraction * EMPTY_FIELD_MODIFIER + new_travelled,
                 };
-                queue.push(State { cost: new_cost, distance: new_distance, coords: new_coords });
+                queue.push(State { cost: (new_cost, new_travelled), distance: new_distance, coords: new_coords });
             }
         }
         None
     })
-}
\ No newline at end of file
+}
+
+/// Integrates the cost of reaching the closest enabled energy supplier from every field, starting from all suppliers at once.
+/// Each field then points to its cheapest neighbour, so any number of wisps can follow the field without searching on their own.
+/// Movement rules match `path_find_energy_beckon()`: walls block, buildings can be passed, diagonal moves need both adjacent fields free.
+/// Emissions are weighed with `weights`, so wisps reacting differently to them need their own field.
+/// Terrain scales the cost of entering each field by its path cost.
+pub fn flood_flow_field(
+    flow_field: &mut FlowFieldGrid,
+    obstacle_grid: &ObstacleGrid,
+    emissions_grid: &EmissionsGrid,
+    terrain_grid: &TerrainGrid,
+    weights: &EmissionsWeights,
+) {
+    flow_field.resize_and_reset(obstacle_grid.bounds());
+    let mut queue = BinaryHeap::new();
+    for y in 0..obstacle_grid.height {
+        for x in 0..obstacle_grid.width {
+            let coords = GridCoords { x, y };
+            if !obstacle_grid[coords].has_enabled_energy_supplier() { continue; }
+            flow_field[coords] = FlowField { cost: 0., step: FlowStep::Target };
+            queue.push(State { cost: 0., distance: 0, coords });
+        }
+    }
+
+    while let Some(State { cost, distance, coords }) = queue.pop() {
+        if cost > flow_field[coords].cost { continue; } // Already reached cheaper
+        let enter_cost = flow_enter_cost(obstacle_grid, emissions_grid, terrain_grid, weights, coords);
+
+        // Expanding backwards: neighbours are the fields a wisp would step from into `coords`
+        for (delta_x, delta_y) in ALL_DIRECTIONS {
+            let neighbour_coords = coords.shifted((delta_x, delta_y));
+            if !neighbour_coords.is_in_bounds(obstacle_grid.bounds()) || obstacle_grid[neighbour_coords].has_wall() {
+                continue;
+            }
+            if delta_x.abs() == delta_y.abs() {
+                let adjacent_x = (coords.x + delta_x, coords.y).into();
+                let adjacent_y = (coords.x, coords.y + delta_y).into();
+                if obstacle_grid[adjacent_x].has_structure() || obstacle_grid[adjacent_y].has_structure() {
+                    continue;
+                }
+            }
+
+            let new_cost = cost + enter_cost;
+            if new_cost < flow_field[neighbour_coords].cost {
+                flow_field[neighbour_coords] = FlowField { cost: new_cost, step: FlowStep::Towards(coords) };
+                queue.push(State { cost: new_cost, distance: distance + 1, coords: neighbour_coords });
+            }
+        }
+    }
+    flow_field.version = Some((obstacle_grid.version, terrain_grid.version));
+}
+/// Cost `flood_flow_field()` charges for stepping into `coords`. Walls can't be entered.
+pub fn flow_enter_cost(
+    obstacle_grid: &ObstacleGrid,
+    emissions_grid: &EmissionsGrid,
+    terrain_grid: &TerrainGrid,
+    weights: &EmissionsWeights,
+    coords: GridCoords,
+) -> f32 {
+    let attraction = weights.attraction(&emissions_grid[coords]) * FLOW_EMISSIONS_ATTRACTION;
+    match obstacle_grid[coords].structure {
+        GridStructureType::Wall(..) => f32::INFINITY,
+        GridStructureType::Building(..) => FLOW_BUILDING_FIELD_COST,
+        _ => FLOW_EMPTY_FIELD_COST,
+    } * if attraction >= 0. { 1. / (1. + attraction) } else { 1. - attraction }
+        * terrain_grid[coords].path_cost()
+}
+
+/// Cost of walking `path` under the current grids, comparable with `FlowField::cost` of the field the path starts from.
+pub fn flow_path_cost<'a>(
+    obstacle_grid: &ObstacleGrid,
+    emissions_grid: &EmissionsGrid,
+    terrain_grid: &TerrainGrid,
+    weights: &EmissionsWeights,
+    path: impl IntoIterator<Item = &'a GridCoords>,
+) -> f32 {
+    path.into_iter()
+        .map(|coords| {
+            if !coords.is_in_bounds(obstacle_grid.bounds()) { return f32::INFINITY; }
+            flow_enter_cost(obstacle_grid, emissions_grid, terrain_grid, weights, *coords)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: i32 = 10;
+    const HEIGHT: i32 = 6;
+    const SUPPLIER: GridCoords = GridCoords { x: 8, y: 3 };
+
+    /// Plain map with a single enabled energy supplier at `SUPPLIER` and walls at `walls`.
+    fn grids(walls: &[(i32, i32)]) -> (ObstacleGrid, EmissionsGrid, TerrainGrid) {
+        let mut obstacle_grid = ObstacleGrid::new_with_size(WIDTH, HEIGHT);
+        let imprint = GridImprint::Rectangle { width: 1, height: 1 };
+        obstacle_grid.imprint_structure(SUPPLIER, imprint, GridStructureType::Building(Entity::PLACEHOLDER, BuildingType::EnergyRelay));
+        obstacle_grid.set_energy_supplier_enabled(SUPPLIER, imprint, true);
+        for wall in walls {
+            obstacle_grid.imprint_structure((*wall).into(), imprint, GridStructureType::Wall(Entity::PLACEHOLDER));
+        }
+        (obstacle_grid, EmissionsGrid::new_with_size(WIDTH, HEIGHT), TerrainGrid::new_with_size(WIDTH, HEIGHT))
+    }
+
+    fn flood((obstacle_grid, emissions_grid, terrain_grid): &(ObstacleGrid, EmissionsGrid, TerrainGrid)) -> FlowFieldGrid {
+        let mut flow_field = FlowFieldGrid::new_empty();
+        flood_flow_field(&mut flow_field, obstacle_grid, emissions_grid, terrain_grid, &EmissionsWeights::default());
+        flow_field
+    }
+
+    fn path_cost((obstacle_grid, emissions_grid, terrain_grid): &(ObstacleGrid, EmissionsGrid, TerrainGrid), path: &[GridCoords]) -> f32 {
+        flow_path_cost(obstacle_grid, emissions_grid, terrain_grid, &EmissionsWeights::default(), path)
+    }
+
+    #[test]
+    fn field_path_costs_as_much_as_the_searched_path() {
+        let grids = grids(&[(5, 1), (5, 2), (5, 3), (5, 4)]);
+        let flow_field = flood(&grids);
+        let (obstacle_grid, emissions_grid, terrain_grid) = &grids;
+        for start in [GridCoords { x: 1, y: 3 }, GridCoords { x: 0, y: 0 }, GridCoords { x: 9, y: 5 }] {
+            let searched_path = path_find_energy_beckon(obstacle_grid, emissions_grid, terrain_grid, &EmissionsWeights::default(), start)
+                .expect("The supplier is reachable");
+            let field_path = flow_field.path_from(start).expect("The supplier is reachable");
+            assert_eq!(field_path.last(), Some(&SUPPLIER));
+            assert_eq!(flow_field[start].cost, path_cost(&grids, &field_path));
+            assert_eq!(flow_field[start].cost, path_cost(&grids, &searched_path), "Paths from {:?} differ in cost", start);
+        }
+        assert_eq!(flow_field[SUPPLIER].cost, 0.);
+    }
+
+    #[test]
+    fn walls_are_impassable() {
+        let walls = [(5, 0), (5, 1), (5, 2), (5, 3), (5, 4)];
+        let grids = grids(&walls);
+        let flow_field = flood(&grids);
+        let path = flow_field.path_from(GridCoords { x: 1, y: 3 }).expect("The gap at the top lets wisps through");
+        assert!(path.iter().all(|coords| !walls.contains(&(coords.x, coords.y))));
+        assert!(path.contains(&GridCoords { x: 5, y: 5 }));
+        for wall in walls {
+            assert_eq!(flow_enter_cost(&grids.0, &grids.1, &grids.2, &EmissionsWeights::default(), wall.into()), f32::INFINITY);
+            assert_eq!(flow_field[wall.into()].step, FlowStep::Unreachable);
+        }
+    }
+
+    #[test]
+    fn diagonal_moves_dont_cut_corners() {
+        // The direct diagonal step from (7, 2) into the supplier would cut the wall at (8, 2)
+        let grids = grids(&[(8, 2)]);
+        let flow_field = flood(&grids);
+        let path = flow_field.path_from(GridCoords { x: 7, y: 2 }).expect("The supplier is reachable");
+        assert_eq!(path, vec![GridCoords { x: 7, y: 3 }, SUPPLIER]);
+        assert_eq!(flow_field[GridCoords { x: 7, y: 2 }].cost, FLOW_EMPTY_FIELD_COST + FLOW_BUILDING_FIELD_COST);
+    }
+
+    #[test]
+    fn cut_off_fields_are_unreachable() {
+        let grids = grids(&[(5, 0), (5, 1), (5, 2), (5, 3), (5, 4), (5, 5)]);
+        let flow_field = flood(&grids);
+        let cut_off = GridCoords { x: 1, y: 3 };
+        assert_eq!(flow_field[cut_off].cost, f32::INFINITY);
+        assert_eq!(flow_field.path_from(cut_off), None);
+        assert_eq!(path_cost(&grids, &[cut_off, GridCoords { x: -1, y: 0 }]), f32::INFINITY);
+        assert!(flow_field[GridCoords { x: 6, y: 3 }].cost.is_finite());
+    }
+}
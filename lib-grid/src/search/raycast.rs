@@ -0,0 +1,97 @@
+use crate::lib_prelude::*;
+use crate::grids::obstacles::{GridStructureType, ObstacleGrid};
+
+/// Fields crossed by the straight line between `from` and `to`, excluding both ends (Bresenham)
+pub fn raycast_line(from: GridCoords, to: GridCoords) -> impl Iterator<Item = GridCoords> {
+    let (delta_x, delta_y) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let (step_x, step_y) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let mut error = delta_x + delta_y;
+    let mut coords = from;
+    std::iter::from_fn(move || {
+        if coords == to { return None; }
+        let doubled_error = 2 * error;
+        if doubled_error >= delta_y {
+            error += delta_y;
+            coords.x += step_x;
+        }
+        if doubled_error <= delta_x {
+            error += delta_x;
+            coords.y += step_y;
+        }
+        Some(coords)
+    }).take_while(move |coords| *coords != to)
+}
+
+/// Whether `viewer` standing on `from` can see `to`
+/// Walls and buildings block the sight, except for the viewer's own building
+pub fn has_line_of_sight(
+    obstacle_grid: &ObstacleGrid,
+    from: GridCoords,
+    to: GridCoords,
+    viewer: Entity,
+) -> bool {
+    raycast_line(from, to).all(|coords| match obstacle_grid[coords].structure {
+        GridStructureType::Empty => true,
+        GridStructureType::Building(entity, _) => entity == viewer,
+        GridStructureType::Wall(_) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+        raycast_line(from.into(), to.into()).map(|coords| coords.into()).collect()
+    }
+
+    #[test]
+    fn straight_and_diagonal_lines() {
+        assert_eq!(line((0, 0), (5, 0)), vec![(1, 0), (2, 0), (3, 0), (4, 0)]);
+        assert_eq!(line((0, 0), (0, -3)), vec![(0, -1), (0, -2)]);
+        assert_eq!(line((0, 0), (3, 3)), vec![(1, 1), (2, 2)]);
+        assert_eq!(line((3, 0), (0, 3)), vec![(2, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn ends_are_excluded() {
+        assert_eq!(line((2, 2), (2, 2)), vec![]);
+        assert_eq!(line((2, 2), (3, 2)), vec![]);
+        assert_eq!(line((2, 2), (3, 3)), vec![]);
+    }
+
+    #[test]
+    fn shallow_and_steep_lines_take_one_field_per_step_along_the_longer_axis() {
+        let shallow = line((0, 0), (4, 2));
+        assert_eq!(shallow, vec![(1, 1), (2, 1), (3, 2)]);
+        let steep = line((0, 0), (2, 4));
+        assert_eq!(steep, vec![(1, 1), (1, 2), (2, 3)]);
+        let long_shallow = line((0, 0), (9, 3));
+        assert_eq!(long_shallow.iter().map(|(x, _)| *x).collect::<Vec<_>>(), (1..9).collect::<Vec<_>>());
+        let long_steep = line((0, 0), (-3, -9));
+        assert_eq!(long_steep.iter().map(|(_, y)| *y).collect::<Vec<_>>(), (-8..0).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sight_is_blocked_by_walls_and_other_buildings_on_the_line() {
+        let mut world = World::new();
+        let (tower, wall, other_building) = (world.spawn_empty().id(), world.spawn_empty().id(), world.spawn_empty().id());
+        let mut obstacle_grid = ObstacleGrid::new_with_size(10, 10);
+        // The viewer's own 2x2 building covers the start of the line
+        obstacle_grid.imprint_structure((0, 0).into(), GridImprint::Rectangle { width: 2, height: 2 }, GridStructureType::Building(tower, BuildingType::Tower(TowerType::Blaster)));
+        assert!(has_line_of_sight(&obstacle_grid, (0, 0).into(), (6, 0).into(), tower));
+        assert!(!has_line_of_sight(&obstacle_grid, (0, 0).into(), (6, 0).into(), other_building));
+
+        let single_field = GridImprint::Rectangle { width: 1, height: 1 };
+        obstacle_grid.imprint_structure((4, 0).into(), single_field, GridStructureType::Wall(wall));
+        assert!(!has_line_of_sight(&obstacle_grid, (0, 0).into(), (6, 0).into(), tower));
+        // Next to the line doesn't block
+        assert!(has_line_of_sight(&obstacle_grid, (0, 1).into(), (6, 1).into(), tower));
+
+        obstacle_grid.imprint_structure((4, 4).into(), single_field, GridStructureType::Building(other_building, BuildingType::EnergyRelay));
+        assert!(!has_line_of_sight(&obstacle_grid, (1, 1).into(), (6, 6).into(), tower));
+        // A structure on the target field itself doesn't hide it
+        assert!(has_line_of_sight(&obstacle_grid, (1, 1).into(), (4, 4).into(), tower));
+        assert!(has_line_of_sight(&obstacle_grid, (2, 0).into(), (4, 0).into(), tower));
+    }
+}
@@ -8,6 +8,7 @@ use super::common::{VISITED_GRID, CARDINAL_DIRECTIONS, State};
 /// Finds the closest wisp
 /// `ignore_obstacles` ignores all grid obstacles
 /// `range` is the maximum searching range, diagonal moves are not allowed
+/// `is_in_range` filters the fields a wisp can be picked from, the search still passes through the others
 /// Returns grid coords and entity id of the closest wisp or None if no wisp is found
 pub fn target_find_closest_wisp(
     obstacle_grid: &Res<ObstacleGrid>,
@@ -15,6 +16,7 @@ pub fn target_find_closest_wisp(
     start_coords: Vec<GridCoords>,
     range: usize,
     ignore_obstacles: bool,
+    is_in_range: impl Fn(GridCoords) -> bool,
 ) -> Option<(GridCoords, Entity)> {
     VISITED_GRID.with_borrow_mut(|visited_grid| {
         visited_grid.resize_and_reset(obstacle_grid.bounds());
@@ -36,7 +38,7 @@ pub fn target_find_closest_wisp(
                     continue;
                 }
 
-                if !wisps_grid[new_coords].is_empty() {
+                if !wisps_grid[new_coords].is_empty() && is_in_range(new_coords) {
                     return Some((new_coords, wisps_grid[new_coords][0]));
                 }
 
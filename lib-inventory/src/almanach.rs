@@ -18,22 +18,28 @@ pub struct Almanach {
     buildings: HashMap<BuildingType, AlmanachBuildingInfo>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct AlmanachBuildingInfo {
     pub building_type: BuildingType,
     pub name: String,
     pub cost: Vec<Cost>,
     pub grid_imprint: GridImprint,
+    /// Towers only, others leave it out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_shape: Option<TowerRangeShape>,
+    /// Towers only. Direct fire can't hit wisps behind walls and buildings.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub requires_line_of_sight: bool,
     pub upgrades: HashMap<UpgradeType, AlmanachUpgradeInfo>,
     pub baseline: HashMap<ModifierType, f32>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct AlmanachUpgradeInfo {
     pub levels: Vec<AlmanachUpgradeLevelInfo>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct AlmanachUpgradeLevelInfo {
     pub cost: Vec<Cost>,
     pub value: f32,
@@ -44,6 +50,12 @@ impl Almanach {
         let info = self.buildings.get(&building_type).expect(format!("Building {building_type:?} not found in almanach").as_str());
         &info
     }
+    pub fn find_building_info(&self, building_type: BuildingType) -> Option<&AlmanachBuildingInfo> {
+        self.buildings.get(&building_type)
+    }
+    pub fn buildings(&self) -> impl Iterator<Item = &AlmanachBuildingInfo> {
+        self.buildings.values()
+    }
     pub fn add_building_info(&mut self, building_info: AlmanachBuildingInfo) {
         self.buildings.insert(building_info.building_type, building_info);
     }
@@ -31,6 +31,8 @@ pub enum ModifierType {
     MaxHealth,
     MovementSpeed,
     EnergySupplyRange,
+    EnergyProduction,
+    EnergyConsumption,
 }
 impl ModifierType {
     /// Inserts the corresponding value-holding component for this modifier type
@@ -46,6 +48,8 @@ impl ModifierType {
             Self::MaxHealth => { entity_commands.insert(MaxHealth::new(value)); }
             Self::MovementSpeed => { entity_commands.insert(MovementSpeed::new(value)); }
             Self::EnergySupplyRange => { entity_commands.insert(EnergySupplyRange::new(value)); }
+            Self::EnergyProduction => { entity_commands.insert(EnergyProduction::new(value)); }
+            Self::EnergyConsumption => { entity_commands.insert(EnergyConsumption::new(value)); }
         }
     }
 }
@@ -197,14 +201,14 @@ pub struct Upgrades {
 }
 impl Upgrades {
     /// Creates an Upgrades component from almanach upgrade info.
-    /// If `apply_levels` is provided, upgrades start at those levels.
+    /// If `apply_levels` is provided, upgrades start at those levels, capped at the levels the almanach knows.
     /// On insert, the observer will apply modifiers for any non-zero levels.
     pub fn from_almanach(
         almanach_upgrades: &HashMap<UpgradeType, AlmanachUpgradeInfo>,
         apply_levels: Option<&HashMap<UpgradeType, usize>>,
     ) -> Self {
         let upgrades = almanach_upgrades.iter().map(|(upgrade_type, info)| {
-            let level = apply_levels.and_then(|l| l.get(upgrade_type).copied()).unwrap_or(0);
+            let level = apply_levels.and_then(|l| l.get(upgrade_type).copied()).unwrap_or(0).min(info.levels.len());
             (*upgrade_type, UpgradeRuntimeInfo {
                 current_level: level,
                 static_info: info.clone(),
@@ -20,7 +20,7 @@ impl Plugin for LibUiPlugin {
 pub mod prelude {
     pub use crate::healthbar::Healthbar;
     pub use crate::cost_indicator::CostIndicator;
-    pub use crate::upgrade_line::UpgradeLineBuilder;
+    pub use crate::upgrade_line::{LevelUpUpgradeRequestedEvent, UpgradeButtonsLocked, UpgradeLineBuilder};
 }
 
 pub mod lib_prelude {
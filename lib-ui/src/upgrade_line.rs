@@ -6,6 +6,8 @@ pub struct CommonPlugin;
 impl Plugin for CommonPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<UpgradeButtonsLocked>()
+            .add_systems(Update, UpgradeButton::on_lock_changed_system.run_if(resource_changed::<UpgradeButtonsLocked>))
             .add_observer(UpgradeLineBuilder::on_add)
             .add_observer(UpgradeLine::on_insert)
             .add_observer(UpgradeLine::on_upgrade_applied);
@@ -76,6 +78,7 @@ impl UpgradeLine {
         trigger: On<Insert, UpgradeLine>,
         mut commands: Commands,
         upgrade_lines: Query<&UpgradeLine>,
+        locked: Res<UpgradeButtonsLocked>,
     ) {
         let upgrade_line_entity = trigger.entity;
         let Ok(upgrade_line) = upgrade_lines.get(upgrade_line_entity) else { return; };
@@ -120,7 +123,7 @@ impl UpgradeLine {
                         ..default()
                     },
                     BorderColor::all(Color::WHITE),
-                    BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.8)),
+                    BackgroundColor(UpgradeButton::background_color(locked.0)),
                 ))
                     .observe(Self::on_click)
                     .with_children(|parent| {
@@ -159,19 +162,22 @@ impl UpgradeLine {
         });
     }
 
+    /// Paying and applying is left to whoever observes `LevelUpUpgradeRequestedEvent`.
     fn on_click(
         trigger: On<Pointer<Click>>,
         mut commands: Commands,
-        mut stock: ResMut<Stock>,
+        stock: Res<Stock>,
+        locked: Res<UpgradeButtonsLocked>,
         upgrade_buttons: Query<&UpgradeButton>,
         upgrade_lines: Query<&UpgradeLine>,
     ) {
+        if locked.0 { return; }
         let entity = trigger.entity;
         let Ok(upgrade_button) = upgrade_buttons.get(entity) else { return; };
         let Ok(upgrade_line) = upgrade_lines.get(upgrade_button.0) else { return; };
-        if !stock.try_pay_costs(&upgrade_line.costs) { return; }
+        if !stock.can_cover_all(&upgrade_line.costs) { return; }
 
-        commands.queue(LevelUpUpgradeMessage {
+        commands.trigger(LevelUpUpgradeRequestedEvent {
             entity: upgrade_line.target_entity,
             upgrade_type: upgrade_line.upgrade_type,
         });
@@ -201,4 +207,29 @@ impl UpgradeLine {
 
 #[derive(Component)]
 #[require(Button)]
-pub struct UpgradeButton(Entity);
\ No newline at end of file
+pub struct UpgradeButton(Entity);
+impl UpgradeButton {
+    fn background_color(locked: bool) -> Color {
+        if locked { Color::srgba(0.1, 0.1, 0.1, 0.5) } else { Color::srgba(0.2, 0.2, 0.2, 0.8) }
+    }
+
+    fn on_lock_changed_system(
+        locked: Res<UpgradeButtonsLocked>,
+        mut upgrade_buttons: Query<&mut BackgroundColor, With<UpgradeButton>>,
+    ) {
+        for mut background_color in upgrade_buttons.iter_mut() {
+            background_color.0 = Self::background_color(locked.0);
+        }
+    }
+}
+
+/// Sent when the player clicks an affordable upgrade. The game decides whether and when the upgrade is applied.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct LevelUpUpgradeRequestedEvent {
+    pub entity: Entity,
+    pub upgrade_type: UpgradeType,
+}
+
+/// Upgrade buttons ignore clicks and are greyed out while set, e.g. during a replay.
+#[derive(Resource, Default)]
+pub struct UpgradeButtonsLocked(pub bool);
\ No newline at end of file
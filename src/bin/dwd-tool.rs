@@ -0,0 +1,319 @@
+//! Inspects and validates `.dwd` save/map databases without starting the game.
+//!
+//! Usage:
+//!   dwd-tool summary <file.dwd>
+//!   dwd-tool validate <file.dwd> [--data assets/data.yaml]
+//!   dwd-tool migrate <file.dwd>
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use serde::Deserialize;
+
+use lib_core::buildings::{BuildingType, BUILDING_TABLES};
+use lib_core::grids::{GridCoords, GridImprint, TerrainType};
+use lib_core::persistence::common::{db_migrations, GameDbHelpers};
+use lib_core::persistence::rusqlite::{self, Connection, OpenFlags};
+
+const USAGE: &str = "Usage:
+  dwd-tool summary <file.dwd>
+  dwd-tool validate <file.dwd> [--data assets/data.yaml]
+  dwd-tool migrate <file.dwd>";
+const DEFAULT_DATA_PATH: &str = "assets/data.yaml";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let (Some(command), Some(path)) = (args.first(), args.get(1)) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let data_path = args.iter()
+        .position(|arg| arg == "--data")
+        .and_then(|index| args.get(index + 1))
+        .map_or(DEFAULT_DATA_PATH, |data_path| data_path.as_str());
+
+    let result = match command.as_str() {
+        "summary" => summary(path),
+        "validate" => validate(path, data_path),
+        "migrate" => migrate(path),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn open_read_only(path: &str) -> rusqlite::Result<Connection> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+}
+
+fn table_names(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'refinery_schema_history' ORDER BY name")?;
+    stmt.query_map([], |row| row.get(0))?.collect()
+}
+
+fn count_rows(conn: &Connection, table: &str) -> rusqlite::Result<i64> {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+}
+
+fn map_size(conn: &Connection) -> rusqlite::Result<Option<(String, i32, i32)>> {
+    let mut stmt = conn.prepare("SELECT name, width, height FROM map_info WHERE id = 1")?;
+    let mut rows = stmt.query([])?;
+    let Some(row) = rows.next()? else { return Ok(None); };
+    Ok(Some((row.get(0)?, row.get(1)?, row.get(2)?)))
+}
+
+fn summary(path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = open_read_only(path)?;
+
+    match map_size(&conn)? {
+        Some((name, width, height)) => println!("Map: {} ({}x{})", name, width, height),
+        None => println!("Map: <no map_info>"),
+    }
+
+    println!("\nRows per table:");
+    for table in table_names(&conn)? {
+        println!("  {:<24} {}", table, count_rows(&conn, &table)?);
+    }
+
+    println!("\nStock:");
+    let mut stmt = conn.prepare("SELECT resource_name, amount FROM stock ORDER BY resource_name")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        println!("  {:<24} {}", row.get::<_, String>(0)?, row.get::<_, i64>(1)?);
+    }
+
+    println!("\nObjectives:");
+    let mut stmt = conn.prepare("SELECT id, id_name, objective_type, activation_event, state FROM objectives ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        println!(
+            "  #{} {} [{}] on '{}': {}",
+            row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get::<_, String>(4)?,
+        );
+    }
+
+    println!("\nSummonings:");
+    let mut stmt = conn.prepare("SELECT id, summoning_json, produced, is_active FROM summonings ORDER BY id")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let summoning_json: String = row.get(1)?;
+        let summoning = serde_json::from_str::<serde_json::Value>(&summoning_json).unwrap_or_default();
+        println!(
+            "  #{} {} on '{}': produced {}, {}",
+            row.get::<_, i64>(0)?,
+            summoning["id_name"].as_str().unwrap_or("<invalid summoning_json>"),
+            summoning["activation_event"].as_str().unwrap_or("?"),
+            row.get::<_, i64>(2)?,
+            if row.get::<_, bool>(3)? { "active" } else { "inactive" },
+        );
+    }
+    Ok(true)
+}
+
+#[derive(Deserialize)]
+struct AlmanachData {
+    buildings: Vec<AlmanachBuilding>,
+}
+#[derive(Deserialize)]
+struct AlmanachBuilding {
+    building_type: BuildingType,
+    grid_imprint: GridImprint,
+}
+
+/// What occupies a grid cell, used to find overlapping imprints.
+#[derive(Clone, Copy, PartialEq)]
+enum Occupant {
+    Solid(i64),
+    MiningComplex(i64),
+    DarkOre(i64),
+}
+impl Occupant {
+    fn entity_id(&self) -> i64 {
+        match self {
+            Occupant::Solid(entity_id) | Occupant::MiningComplex(entity_id) | Occupant::DarkOre(entity_id) => *entity_id,
+        }
+    }
+}
+
+fn validate(path: &str, data_path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let conn = open_read_only(path)?;
+    let mut problems = Vec::new();
+
+    // Rows referencing missing entities
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let table: String = row.get(0)?;
+        let row_id: Option<i64> = row.get(1)?;
+        let parent: String = row.get(2)?;
+        problems.push(format!("{} row {:?} references a missing row in '{}'", table, row_id, parent));
+    }
+
+    // Entities nobody owns. Owner tables use their primary key `id` to reference `entities`;
+    // grid_imprints does too, but only describes an entity owned elsewhere.
+    let mut owner_tables = Vec::new();
+    for table in table_names(&conn)?.into_iter().filter(|table| table != "grid_imprints") {
+        let mut stmt = conn.prepare(&format!("PRAGMA foreign_key_list({})", table))?;
+        let references_entities_by_id = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(2)?, row.get::<_, String>(3)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .iter()
+            .any(|(parent, from)| parent == "entities" && from == "id");
+        if references_entities_by_id { owner_tables.push(table); }
+    }
+    if !owner_tables.is_empty() {
+        let owned = owner_tables.iter().map(|table| format!("SELECT id FROM {}", table)).collect::<Vec<_>>().join(" UNION ");
+        let mut stmt = conn.prepare(&format!("SELECT id FROM entities WHERE id NOT IN ({}) ORDER BY id", owned))?;
+        for entity_id in stmt.query_map([], |row| row.get::<_, i64>(0))? {
+            problems.push(format!("entities row {} is not referenced by any table", entity_id?));
+        }
+    }
+
+    // Buildings must have grid_coords and healths
+    for (table, _) in BUILDING_TABLES {
+        for (attribute_table, column) in [("grid_coords", "entity_id"), ("healths", "entity_id")] {
+            let mut stmt = conn.prepare(&format!("SELECT id FROM {table} WHERE id NOT IN (SELECT {column} FROM {attribute_table}) ORDER BY id"))?;
+            for entity_id in stmt.query_map([], |row| row.get::<_, i64>(0))? {
+                problems.push(format!("{} row {} has no '{}' row", table, entity_id?, attribute_table));
+            }
+        }
+    }
+
+    let Some((_, width, height)) = map_size(&conn)? else {
+        problems.push("map_info is missing, bounds and imprints were not checked".to_string());
+        return Ok(report(problems));
+    };
+
+    // Bounds of single cells
+    let mut stmt = conn.prepare("SELECT entity_id, x, y FROM grid_coords ORDER BY entity_id")?;
+    let grid_coords = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, GridCoords { x: row.get(1)?, y: row.get(2)? })))?
+        .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+    let is_in_bounds = |coords: GridCoords| coords.x >= 0 && coords.y >= 0 && coords.x < width && coords.y < height;
+    for (entity_id, coords) in &grid_coords {
+        if !is_in_bounds(*coords) {
+            problems.push(format!("entity {} at ({}, {}) is outside of the {}x{} map", entity_id, coords.x, coords.y, width, height));
+        }
+    }
+
+    // Terrain fields, saves older than the terrain table have none
+    if table_names(&conn)?.iter().any(|table| table == "terrain") {
+        let mut stmt = conn.prepare("SELECT x, y, terrain_type FROM terrain ORDER BY y, x")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let coords = GridCoords { x: row.get(0)?, y: row.get(1)? };
+            let terrain_type: String = row.get(2)?;
+            if TerrainType::from_db_str(&terrain_type).is_none() {
+                problems.push(format!("terrain at ({}, {}) has unknown type '{}'", coords.x, coords.y, terrain_type));
+            }
+            if !is_in_bounds(coords) {
+                problems.push(format!("terrain at ({}, {}) is outside of the {}x{} map", coords.x, coords.y, width, height));
+            }
+        }
+    }
+
+    // Imprints of everything that blocks the grid
+    let almanach: AlmanachData = serde_yaml::from_reader(std::fs::File::open(data_path)?)?;
+    for building in almanach.buildings.iter() {
+        if let Err(e) = building.grid_imprint.validate() {
+            problems.push(format!("{:?} has an invalid grid_imprint in '{}': {}", building.building_type, data_path, e));
+        }
+    }
+    let building_imprints = almanach.buildings.iter()
+        .filter(|building| building.grid_imprint.validate().is_ok())
+        .map(|building| (building.building_type, building.grid_imprint))
+        .collect::<HashMap<_, _>>();
+    let mut imprints = Vec::new();
+    for (table, building_type) in BUILDING_TABLES {
+        let Some(imprint) = building_imprints.get(&building_type) else {
+            problems.push(format!("{} has no grid_imprint in '{}'", table, data_path));
+            continue;
+        };
+        let mut stmt = conn.prepare(&format!("SELECT id FROM {}", table))?;
+        for entity_id in stmt.query_map([], |row| row.get::<_, i64>(0))? {
+            let entity_id = entity_id?;
+            let occupant = if building_type == BuildingType::MiningComplex { Occupant::MiningComplex(entity_id) } else { Occupant::Solid(entity_id) };
+            imprints.push((occupant, *imprint));
+        }
+    }
+    let mut stmt = conn.prepare("SELECT id FROM quantum_fields WHERE id IN (SELECT id FROM grid_imprints)")?;
+    for entity_id in stmt.query_map([], |row| row.get::<_, i64>(0))? {
+        let entity_id = entity_id?;
+        match conn.get_grid_imprint(entity_id) {
+            Ok(imprint) => imprints.push((Occupant::Solid(entity_id), imprint)),
+            Err(e) => problems.push(format!("grid_imprints row {} can't be read: {}", entity_id, e)),
+        }
+    }
+    for (table, single_cell) in [("walls", Occupant::Solid as fn(i64) -> Occupant), ("dark_ores", Occupant::DarkOre)] {
+        let mut stmt = conn.prepare(&format!("SELECT id FROM {}", table))?;
+        for entity_id in stmt.query_map([], |row| row.get::<_, i64>(0))? {
+            imprints.push((single_cell(entity_id?), GridImprint::Rectangle { width: 1, height: 1 }));
+        }
+    }
+
+    let mut cells: HashMap<(i32, i32), Vec<Occupant>> = HashMap::new();
+    for (occupant, imprint) in imprints {
+        let entity_id = occupant.entity_id();
+        let Some(coords) = grid_coords.get(&entity_id) else { continue; };
+        for cell in imprint.covered_coords(*coords) {
+            if !is_in_bounds(cell) {
+                problems.push(format!("imprint of entity {} covers ({}, {}) outside of the map", entity_id, cell.x, cell.y));
+                break;
+            }
+            cells.entry((cell.x, cell.y)).or_default().push(occupant);
+        }
+    }
+    let mut overlaps = cells.into_iter()
+        .filter(|(_, occupants)| {
+            let dark_ore_count = occupants.iter().filter(|occupant| matches!(occupant, Occupant::DarkOre(_))).count();
+            let structure_count = occupants.len() - dark_ore_count;
+            // Mining complexes are the only structures built on top of dark ore
+            let has_solid = occupants.iter().any(|occupant| matches!(occupant, Occupant::Solid(_)));
+            structure_count > 1 || dark_ore_count > 1 || (dark_ore_count > 0 && has_solid)
+        })
+        .collect::<Vec<_>>();
+    overlaps.sort_by_key(|((x, y), _)| (*y, *x));
+    for ((x, y), occupants) in overlaps {
+        let entity_ids = occupants.iter()
+            .map(|occupant| occupant.entity_id().to_string())
+            .collect::<Vec<_>>();
+        problems.push(format!("cell ({}, {}) is covered by overlapping imprints of entities {}", x, y, entity_ids.join(", ")));
+    }
+
+    Ok(report(problems))
+}
+
+fn report(problems: Vec<String>) -> bool {
+    if problems.is_empty() {
+        println!("No problems found");
+        return true;
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    println!("\n{} problem(s) found", problems.len());
+    false
+}
+
+fn migrate(path: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    if !std::path::Path::new(path).exists() {
+        return Err(format!("'{}' does not exist", path).into());
+    }
+    let mut conn = Connection::open(path)?;
+    let report = db_migrations::migrations::runner().run(&mut conn)?;
+    if report.applied_migrations().is_empty() {
+        println!("Already up to date");
+    }
+    for migration in report.applied_migrations() {
+        println!("Applied {}", migration);
+    }
+    Ok(true)
+}
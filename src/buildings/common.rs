@@ -1,3 +1,6 @@
+use lib_grid::grids::emissions::{EmissionsPulse, EmissionsType};
+use lib_grid::search::flooding::{FloodEmissionsDetails, FloodEmissionsEvaluator, FloodEmissionsMode};
+
 use crate::prelude::*;
 
 // Building sub-parts markers
@@ -11,6 +14,19 @@ pub struct TowerTopRotation {
     pub current_angle: f32,
 }
 
+/// Noise of a tower shot, spreading from the whole tower and fading out over time.
+pub fn tower_shot_noise(coords: GridCoords, grid_imprint: GridImprint) -> EmissionsPulse {
+    EmissionsPulse {
+        coords: grid_imprint.covered_coords(coords),
+        emissions_details: vec![FloodEmissionsDetails {
+            emissions_type: EmissionsType::Noise,
+            range: 12,
+            evaluator: FloodEmissionsEvaluator::ExponentialDecay { start_value: 20., decay: 0.2 },
+            mode: FloodEmissionsMode::Increase,
+        }],
+    }
+}
+
 #[derive(Message)]
 pub struct BuildingDestroyedmessage(pub Entity);
 impl Command for BuildingDestroyedmessage {
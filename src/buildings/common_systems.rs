@@ -1,12 +1,16 @@
 use lib_grid::grids::obstacles::{ObstacleGrid, ReservedCoords};
+use lib_grid::grids::terrain::TerrainGrid;
 use lib_grid::grids::wisps::WispsGrid;
+use lib_grid::search::raycast::has_line_of_sight;
 use lib_grid::search::targetfinding::target_find_closest_wisp;
 use lib_core::utils::angle_difference;
 
 use crate::effects::explosions::BuilderExplosion;
 use crate::prelude::*;
+use crate::replay::{entity_at_coords, PlayerCommand};
 use crate::ui::grid_object_placer::GridObjectPlacer;
-use crate::wisps::components::Wisp;
+use crate::wisps::components::{Wisp, WispType};
+use crate::wisps::systems::WispFlowFields;
 use super::{
     energy_relay::BuilderEnergyRelay,
     exploration_center::BuilderExplorationCenter,
@@ -32,95 +36,139 @@ impl Plugin for CommonSystemsPlugin {
                 ).run_if(in_state(GameState::Running)),
             ))
             .add_observer(on_building_destroy_request)
+            .add_observer(on_place_building_command)
+            .add_observer(on_destroy_building_command)
             ;
     }
 }
 
 fn onclick_building_spawn_system(
     mut commands: Commands,
-    mut reserved_coords: ResMut<ReservedCoords>,
-    obstacle_grid: Res<ObstacleGrid>,
     mouse: Res<ButtonInput<MouseButton>>,
     mouse_info: Res<MouseInfo>,
+    grid_object_placer: Single<&GridObjectPlacer>,
+) {
+    if mouse_info.is_over_ui || !mouse.just_released(MouseButton::Left) { return; }
+    let GridObjectPlacer::Building(building_type) = grid_object_placer.into_inner() else { return; };
+    commands.queue(PlayerCommand::PlaceBuilding { building_type: *building_type, coords: mouse_info.grid_coords });
+}
+
+fn on_place_building_command(
+    trigger: On<PlayerCommand>,
+    mut commands: Commands,
+    mut reserved_coords: ResMut<ReservedCoords>,
+    obstacle_grid: Res<ObstacleGrid>,
     almanach: Res<Almanach>,
     mut stock: ResMut<Stock>,
-    grid_object_placer: Single<(&GridObjectPlacer, &GridImprint)>,
     main_base: Query<Entity, With<MainBase>>,
 ) {
-    let mouse_coords = mouse_info.grid_coords;
-    if mouse_info.is_over_ui || !mouse.just_released(MouseButton::Left) { return; }
-    let (grid_object_placer, grid_imprint) = grid_object_placer.into_inner();
-    let GridObjectPlacer::Building(building_type) = grid_object_placer else { return; };
+    let PlayerCommand::PlaceBuilding { building_type, coords } = *trigger.event() else { return; };
+    let building_info = almanach.get_building_info(building_type);
+    let grid_imprint = building_info.grid_imprint;
     // Grid Placement Validation
-    if !mouse_coords.is_imprint_in_bounds(grid_imprint, obstacle_grid.bounds())
-        || !obstacle_grid.query_building_placement(mouse_coords, *building_type, *grid_imprint) 
-        || reserved_coords.any_reserved(mouse_coords, *grid_imprint) { return; }
+    if !coords.is_imprint_in_bounds(&grid_imprint, obstacle_grid.bounds())
+        || !obstacle_grid.query_building_placement(coords, building_type, grid_imprint) 
+        || reserved_coords.any_reserved(coords, grid_imprint) { return; }
     // Payment
-    let building_costs = &almanach.get_building_info(*building_type).cost;
-    if !stock.try_pay_costs(building_costs) { println!("Not enough dark ore"); return; }
+    if !stock.try_pay_costs(&building_info.cost) { println!("Not enough dark ore"); return; }
     // Creation
     // ---
     // ---
-    reserved_coords.reserve(mouse_coords, *grid_imprint);
+    reserved_coords.reserve(coords, grid_imprint);
     match building_type {
         BuildingType::EnergyRelay => {
-            commands.spawn(BuilderEnergyRelay::new(mouse_coords));
+            commands.spawn(BuilderEnergyRelay::new(coords));
         }
         BuildingType::ExplorationCenter => {
-            commands.spawn(BuilderExplorationCenter::new(mouse_coords));
+            commands.spawn(BuilderExplorationCenter::new(coords));
         }
         BuildingType::Tower(TowerType::Blaster) => {
-            commands.spawn(BuilderTowerBlaster::new(mouse_coords));
+            commands.spawn(BuilderTowerBlaster::new(coords));
         },
         BuildingType::Tower(TowerType::Cannon) => {
-            commands.spawn(BuilderTowerCannon::new(mouse_coords));
+            commands.spawn(BuilderTowerCannon::new(coords));
         },
         BuildingType::Tower(TowerType::RocketLauncher) => {
-            commands.spawn(BuilderTowerRocketLauncher::new(mouse_coords));
+            commands.spawn(BuilderTowerRocketLauncher::new(coords));
         },
         BuildingType::Tower(TowerType::Emitter) => {
-            commands.spawn(BuilderTowerEmitter::new(mouse_coords));
+            commands.spawn(BuilderTowerEmitter::new(coords));
         },
         BuildingType::MainBase => {
             let Ok(main_base_entity) = main_base.single() else { return; };
             // Remove/Insert ObstacleGridObject to trigger grid reprint
-            commands.entity(main_base_entity).remove::<ObstacleGridObject>().insert(mouse_coords).insert(ObstacleGridObject::Building);
+            commands.entity(main_base_entity).remove::<ObstacleGridObject>().insert(coords).insert(ObstacleGridObject::Building);
         },
         BuildingType::MiningComplex => {
-            commands.spawn(BuilderMiningComplex::new(mouse_coords));
+            commands.spawn(BuilderMiningComplex::new(coords));
         },
     };
 
 }
 
+fn on_destroy_building_command(
+    trigger: On<PlayerCommand>,
+    mut commands: Commands,
+    buildings: Query<(Entity, &GridCoords), With<Building>>,
+) {
+    let PlayerCommand::DestroyBuilding { coords } = *trigger.event() else { return; };
+    let Some(building) = entity_at_coords(&buildings, coords) else { return; };
+    commands.trigger(BuildingDestroyRequest(building));
+}
+
 fn targeting_system(
     obstacle_grid: Res<ObstacleGrid>,
     wisps_grid: Res<WispsGrid>,
-    mut towers: Query<(&GridCoords, &GridImprint, &AttackRange, &mut TowerWispTarget), (With<Tower>, With<HasPower>, Without<DisabledByPlayer>)>,
-    wisps: Query<&GridCoords, With<Wisp>>,
+    terrain_grid: Res<TerrainGrid>,
+    flow_fields: Res<WispFlowFields>,
+    mut towers: Query<(Entity, &GridCoords, &GridImprint, &AttackRange, &TowerRangeShape, &TargetingPriority, Has<RequiresLineOfSight>, &mut TowerWispTarget), (With<Tower>, With<HasPower>, Without<DisabledByPlayer>)>,
+    wisps: Query<(&GridCoords, &WispType, &Health, &MovementSpeed), With<Wisp>>,
+    main_base: Query<&GridCoords, With<MainBase>>,
 ) {
-    for (coords, grid_imprint, range, mut target) in towers.iter_mut() {
+    for (tower_entity, coords, grid_imprint, range, range_shape, priority, requires_line_of_sight, mut target) in towers.iter_mut() {
+        let can_target = |wisp_coords: GridCoords| {
+            range_shape.covers(*coords, *grid_imprint, range.get(), wisp_coords)
+                && (!requires_line_of_sight || grid_imprint.iter_covered_coords(*coords)
+                    .any(|tower_coords| has_line_of_sight(&obstacle_grid, tower_coords, wisp_coords, tower_entity)))
+        };
         match *target {
             TowerWispTarget::Wisp(wisp_entity) => {
-                if let Ok(wisp_coords) = wisps.get(wisp_entity) {
-                    // Check if wisp is still in range. For now we use Manhattan distance to check. This may not be correct for all tower types.
-                    if coords.manhattan_distance(wisp_coords) <= range.get() as i32 { continue; }
+                if let Ok((wisp_coords, ..)) = wisps.get(wisp_entity) {
+                    // Check if wisp is still in range and in sight
+                    if can_target(*wisp_coords) { continue; }
                 }
             },
             TowerWispTarget::NoValidTargets(grid_version) => {
-                if grid_version == wisps_grid.version {
+                // Only wisps moving within the range bounds can become new targets
+                let (width, height) = grid_imprint.bounds();
+                let reach = range.get().ceil() as i32;
+                let range_min = coords.shifted((-reach, -reach));
+                let range_max = coords.shifted((width - 1 + reach, height - 1 + reach));
+                if !wisps_grid.area_changed_since(range_min, range_max, grid_version) {
+                    *target = TowerWispTarget::NoValidTargets(wisps_grid.version);
                     continue;
                 }
             },
             TowerWispTarget::SearchForNewTarget => {},
         }
-        if let Some((_a, target_wisp)) = target_find_closest_wisp(
-            &obstacle_grid,
-            &wisps_grid,
-            grid_imprint.covered_coords(*coords),
-            range.get() as usize,
-            true,
-        ) {
+        let new_target = if *priority == TargetingPriority::Closest {
+            target_find_closest_wisp(
+                &obstacle_grid,
+                &wisps_grid,
+                grid_imprint.covered_coords(*coords),
+                // Straight-line ranges reach further than the same number of steps along the diagonals
+                (range.get() * 2.).ceil() as usize,
+                true,
+                can_target,
+            ).map(|(_, wisp)| wisp)
+        } else {
+            let candidates = range_shape.covered_coords(*coords, *grid_imprint, range.get(), wisps_grid.bounds())
+                .into_iter()
+                .filter(|wisp_coords| !wisps_grid[*wisp_coords].is_empty() && can_target(*wisp_coords))
+                .flat_map(|wisp_coords| wisps_grid[wisp_coords].iter().copied());
+            target_find_by_priority(*priority, candidates, &wisps, &terrain_grid, &flow_fields, main_base.single().ok().copied())
+        };
+        if let Some(target_wisp) = new_target {
             *target = TowerWispTarget::Wisp(target_wisp);
         } else {
             *target = TowerWispTarget::NoValidTargets(wisps_grid.version);
@@ -128,6 +176,32 @@ fn targeting_system(
     }
 }
 
+/// Picks the best wisp among the candidates according to the priority. `Closest` is handled by the pathfinding search.
+fn target_find_by_priority(
+    priority: TargetingPriority,
+    candidates: impl Iterator<Item = Entity>,
+    wisps: &Query<(&GridCoords, &WispType, &Health, &MovementSpeed), With<Wisp>>,
+    terrain_grid: &TerrainGrid,
+    flow_fields: &WispFlowFields,
+    main_base_coords: Option<GridCoords>,
+) -> Option<Entity> {
+    let scored = candidates.filter_map(|wisp| {
+        let (wisp_coords, wisp_type, health, movement_speed) = wisps.get(wisp).ok()?;
+        // Lower score is better
+        let score = match priority {
+            TargetingPriority::Closest => 0.,
+            // Closest to reaching an energy supplier, as weighed by the wisp's own flow field
+            TargetingPriority::First => flow_fields.cost_at(*wisp_type, *wisp_coords),
+            TargetingPriority::Strongest => -health.get_current(),
+            TargetingPriority::Weakest => health.get_current(),
+            TargetingPriority::Fastest => -movement_speed.0 * terrain_grid.speed_multiplier_at(*wisp_coords),
+            TargetingPriority::ClosestToMainBase => main_base_coords.map_or(0., |main_base_coords| wisp_coords.manhattan_distance(&main_base_coords) as f32),
+        };
+        Some((score, wisp))
+    });
+    scored.min_by(|(a, _), (b, _)| a.total_cmp(b)).map(|(_, wisp)| wisp)
+}
+
 fn tick_shooting_timers_system(
     mut shooting_timers: Query<&mut TowerShootingTimer, (With<HasPower>, Without<DisabledByPlayer>)>,
     time: Res<Time>,
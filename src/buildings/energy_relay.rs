@@ -1,5 +1,5 @@
-use lib_grid::grids::emissions::{EmissionsType, EmitterEnergy, EmitterEnergyEnabled};
-use lib_grid::grids::energy_supply::SupplierEnergy;
+use lib_grid::grids::emissions::{EmissionsType, EmissionsEmitter, EmissionsEmitterEnabled};
+use lib_grid::grids::energy_supply::{SupplierEnergy, SupplierEnergyEnabled};
 use lib_grid::search::flooding::{FloodEmissionsDetails, FloodEmissionsEvaluator, FloodEmissionsMode};
 
 use crate::prelude::*;
@@ -17,56 +17,24 @@ impl Plugin for EnergyRelayPlugin {
 
 pub const ENERGY_RELAY_BASE_IMAGE: &str = "buildings/energy_relay.png";
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PersistSaveData)]
 pub struct EnergyRelaySaveData {
     pub entity: Entity,
+    #[persist(health)]
     pub health: f32,
+    #[persist(disabled_by_player)]
     pub disabled_by_player: bool,
+    #[persist(energy_priority)]
+    pub energy_priority: EnergyPriority,
 }
 
-#[derive(Component, SSS)]
+#[derive(Component, SSS, Persist)]
+#[persist(table = "energy_relays")]
 pub struct BuilderEnergyRelay {
+    #[persist(grid_coords)]
     pub grid_position: GridCoords,
     pub save_data: Option<EnergyRelaySaveData>,
 }
-impl Saveable for BuilderEnergyRelay {
-    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
-        let save_data = self.save_data.expect("BuilderEnergyRelay for saving purpose must have save_data");
-        let entity_index = save_data.entity.index() as i64;
-
-        tx.save_marker("energy_relays", entity_index)?;
-        tx.save_grid_coords(entity_index, self.grid_position)?;
-        tx.save_health(entity_index, save_data.health)?;
-        if save_data.disabled_by_player {
-            tx.save_disabled_by_player(entity_index)?;
-        }
-        Ok(())
-    }
-}
-impl Loadable for BuilderEnergyRelay {
-    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        let mut stmt = ctx.conn.prepare("SELECT id FROM energy_relays LIMIT ?1 OFFSET ?2")?;
-        let mut rows = stmt.query(ctx.pagination.as_params())?;
-        
-        let mut count = 0;
-        while let Some(row) = rows.next()? {
-            let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            let disabled_by_player = ctx.conn.get_disabled_by_player(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = EnergyRelaySaveData { entity: new_entity, health, disabled_by_player };
-                ctx.commands.entity(new_entity).insert(BuilderEnergyRelay::new_for_saving(grid_position, save_data));
-            } else {
-                eprintln!("Warning: EnergyRelay with old ID {} has no corresponding new entity", old_id);
-            }
-            count += 1;
-        }
-
-        Ok(count.into())
-    }
-}
 impl BuilderEnergyRelay {
     pub fn new(grid_position: GridCoords) -> Self {
         Self { grid_position, save_data: None }
@@ -77,15 +45,16 @@ impl BuilderEnergyRelay {
 
     fn on_game_save(
         mut commands: Commands,
-        relays: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>), With<EnergyRelay>>,
+        relays: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &EnergyPriority), With<EnergyRelay>>,
     ) {
         if relays.is_empty() { return; }
         println!("Creating batch of BuilderEnergyRelay for saving. {} items", relays.iter().count());
-        let batch = relays.iter().map(|(entity, coords, health, disabled_by_player)| {
+        let batch = relays.iter().map(|(entity, coords, health, disabled_by_player, energy_priority)| {
             let save_data = EnergyRelaySaveData {
                 entity,
                 health: health.get_current(),
                 disabled_by_player,
+                energy_priority: *energy_priority,
             };
             BuilderEnergyRelay::new_for_saving(*coords, save_data)
         }).collect::<SaveableBatchCommand<_>>();
@@ -108,6 +77,7 @@ impl BuilderEnergyRelay {
         if let Some(save_data) = &builder.save_data {
             // Save data
             entity_commands.insert(Health::new(save_data.health));
+            entity_commands.insert(save_data.energy_priority);
             if save_data.disabled_by_player {
                 entity_commands.insert(DisabledByPlayer);
             }
@@ -126,12 +96,12 @@ impl BuilderEnergyRelay {
                 builder.grid_position,
                 building_info.grid_imprint,
                 NeedsPower::default(),
-                EmitterEnergy(FloodEmissionsDetails {
+                EmissionsEmitter(vec![FloodEmissionsDetails {
                     emissions_type: EmissionsType::Energy,
                     range: usize::MAX,
                     evaluator: FloodEmissionsEvaluator::ExponentialDecay{start_value: 100., decay: 0.1},
                     mode: FloodEmissionsMode::Increase,
-                }),
+                }]),
                 SupplierEnergy,
                 ModifiersBank::from_baseline(&building_info.baseline),
                 related![Indicators[
@@ -165,12 +135,15 @@ impl RequestTechnicalStateUpdate {
         let Ok((has_disabled_by_player, has_no_power)) = relays.get(entity) else { return; };
         let mut entity_commands = commands.entity(entity);
         if has_disabled_by_player {
-            entity_commands.remove::<SupplierEnergy>().remove::<EmitterEnergyEnabled>().remove::<ColorPulsation>();
+            entity_commands.remove::<SupplierEnergy>().remove::<SupplierEnergyEnabled>().remove::<EmissionsEmitterEnabled>().remove::<ColorPulsation>();
         }
         else if has_no_power {
-            entity_commands.remove::<EmitterEnergyEnabled>().remove::<ColorPulsation>().try_insert(SupplierEnergy);
+            // Intended: an unpowered relay, browned out ones included, keeps spreading supply. Networks are joined through
+            // supplied fields, so pulling it would split the network it sits in, which frees energy and can power it again
+            // on the next balance. It only stops being a target for wisps and stops pulsating.
+            entity_commands.remove::<SupplierEnergyEnabled>().remove::<EmissionsEmitterEnabled>().remove::<ColorPulsation>().try_insert(SupplierEnergy);
         } else {
-            entity_commands.try_insert(SupplierEnergy).try_insert(EmitterEnergyEnabled).try_insert(ColorPulsation::new(1.0, 1.8, 3.0));
+            entity_commands.try_insert(SupplierEnergy).try_insert(SupplierEnergyEnabled).try_insert(EmissionsEmitterEnabled).try_insert(ColorPulsation::new(1.0, 1.8, 3.0));
         }
     }
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::prelude::*;
 use crate::ui::indicators::{IndicatorDisplay, IndicatorType, Indicators};
 use crate::map_objects::common::{ExpeditionTargetMarker, ExpeditionZone};
@@ -21,58 +23,34 @@ pub const EXPLORATION_CENTER_BASE_IMAGE: &str = "buildings/exploration_center.pn
 
 #[derive(Component)]
 pub struct ExplorationCenterNewExpeditionTimer(pub Timer);
+impl ExplorationCenterNewExpeditionTimer {
+    pub fn new(elapsed_secs: f32) -> Self {
+        let mut timer = Timer::from_seconds(3.0, TimerMode::Repeating);
+        timer.set_elapsed(Duration::from_secs_f32(elapsed_secs));
+        Self(timer)
+    }
+}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PersistSaveData)]
 pub struct ExplorationCenterSaveData {
     pub entity: Entity,
+    #[persist(health)]
     pub health: f32,
+    #[persist(disabled_by_player)]
     pub disabled_by_player: bool,
+    #[persist(energy_priority)]
+    pub energy_priority: EnergyPriority,
+    #[persist(timer)]
+    pub new_expedition_timer: f32,
 }
 
-#[derive(Component, SSS)]
+#[derive(Component, SSS, Persist)]
+#[persist(table = "exploration_centers")]
 pub struct BuilderExplorationCenter {
+    #[persist(grid_coords)]
     pub grid_position: GridCoords,
     pub save_data: Option<ExplorationCenterSaveData>,
 }
-impl Saveable for BuilderExplorationCenter {
-    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
-        let save_data = self.save_data.expect("BuilderExplorationCenter for saving purpose must have save_data");
-        let entity_index = save_data.entity.index() as i64;
-
-        tx.save_marker("exploration_centers", entity_index)?;
-        tx.save_grid_coords(entity_index, self.grid_position)?;
-        tx.save_health(entity_index, save_data.health)?;
-        if save_data.disabled_by_player {
-            tx.save_disabled_by_player(entity_index)?;
-        }
-        Ok(())
-    }
-}
-impl Loadable for BuilderExplorationCenter {
-    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        // Even if we don't strictly need pagination for ExplorationCenter (few items), respecting it is safer for generic loader logic
-        let mut stmt = ctx.conn.prepare("SELECT id FROM exploration_centers LIMIT ?1 OFFSET ?2")?;
-        let mut rows = stmt.query(ctx.pagination.as_params())?;
-        
-        let mut count = 0;
-        while let Some(row) = rows.next()? {
-            let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            let disabled_by_player = ctx.conn.get_disabled_by_player(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = ExplorationCenterSaveData { entity: new_entity, health, disabled_by_player };
-                ctx.commands.entity(new_entity).insert(BuilderExplorationCenter::new_for_saving(grid_position, save_data));
-            } else {
-                eprintln!("Warning: ExplorationCenter with old ID {} has no corresponding new entity", old_id);
-            }
-            count += 1;
-        }
-
-        Ok(count.into())
-    }
-}
 impl BuilderExplorationCenter {
     pub fn new(grid_position: GridCoords) -> Self {
         Self { grid_position, save_data: None }
@@ -83,15 +61,17 @@ impl BuilderExplorationCenter {
 
     fn on_game_save(
         mut commands: Commands,
-        exploration_centers: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>), With<ExplorationCenter>>,
+        exploration_centers: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &EnergyPriority, &ExplorationCenterNewExpeditionTimer), With<ExplorationCenter>>,
     ) {
         if exploration_centers.is_empty() { return; }
         println!("Creating batch of BuilderExplorationCenter for saving. {} items", exploration_centers.iter().count());
-        let batch = exploration_centers.iter().map(|(entity, coords, health, disabled_by_player)| {
+        let batch = exploration_centers.iter().map(|(entity, coords, health, disabled_by_player, energy_priority, new_expedition_timer)| {
             let save_data = ExplorationCenterSaveData {
                 entity,
                 health: health.get_current(),
                 disabled_by_player,
+                energy_priority: *energy_priority,
+                new_expedition_timer: new_expedition_timer.0.elapsed_secs(),
             };
             BuilderExplorationCenter::new_for_saving(*coords, save_data)
         }).collect::<SaveableBatchCommand<_>>();
@@ -115,6 +95,7 @@ impl BuilderExplorationCenter {
         if let Some(save_data) = &builder.save_data {
             // Save data
             entity_commands.insert(Health::new(save_data.health));
+            entity_commands.insert(save_data.energy_priority);
             if save_data.disabled_by_player {
                 entity_commands.insert(DisabledByPlayer);
             }
@@ -132,7 +113,7 @@ impl BuilderExplorationCenter {
                 builder.grid_position,
                 grid_imprint,
                 NeedsPower::default(),
-                ExplorationCenterNewExpeditionTimer(Timer::from_seconds(3.0, TimerMode::Repeating)),
+                ExplorationCenterNewExpeditionTimer::new(builder.save_data.map_or(0., |d| d.new_expedition_timer)),
                 ModifiersBank::from_baseline(&building_info.baseline),
                 related![Indicators[
                     IndicatorType::NoPower,
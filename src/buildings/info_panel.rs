@@ -1,7 +1,9 @@
-use bevy::color::palettes::css::{BLUE, WHITE};
+use bevy::color::palettes::css::{BLUE, ORANGE, WHITE};
+use lib_grid::grids::energy_supply::EnergyNetworks;
 use lib_ui::prelude::{Healthbar, UpgradeLineBuilder};
 
 use crate::prelude::*;
+use crate::replay::{entity_at_coords, PlayerCommand};
 use crate::ui::display_info_panel::{DisplayInfoPanel, DisplayPanelMainContentRoot, UiMapObjectFocusedTrigger};
 
 pub struct InfoPanelPlugin;
@@ -9,13 +11,23 @@ impl Plugin for InfoPanelPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(PostStartup, initialize_building_panel_content_system)
-            .add_systems(Update, update_building_info_panel_system.run_if(in_state(UiInteraction::DisplayInfoPanel)))
+            .add_systems(Update, (
+                update_building_info_panel_system,
+                BuildingInfoPanelEnergyStatusText::update_system,
+            ).run_if(in_state(UiInteraction::DisplayInfoPanel)))
             .add_observer(on_ui_map_object_focus_changed_trigger)
             .add_observer(on_building_info_panel_enabled_for_towers_trigger)
             .add_observer(BuildingInfoPanelTowerUpgradeCountText::refresh_upgrade_count_on::<BuildingInfoPanelEnabledTrigger, ()>) // Refresh upgrade text on panel enabled
             .add_observer(BuildingInfoPanelTowerUpgradeCountText::refresh_upgrade_count_on::<LevelUpUpgradeAppliedEvent, ()>) // Refresh upgrade text after upgrade applied
             .add_observer(BuildingInfoPanelDisableButton::on_add)
             .add_observer(BuildingInfoPanelDestroyButton::on_add)
+            .add_observer(BuildingInfoPanelDisableButton::on_set_disabled_by_player_command)
+            .add_observer(BuildingInfoPanelTargetingPriorityButton::on_add)
+            .add_observer(BuildingInfoPanelTargetingPriorityButton::refresh_on_panel_enabled)
+            .add_observer(BuildingInfoPanelTargetingPriorityButton::on_set_targeting_priority_command)
+            .add_observer(on_building_info_panel_enabled_for_consumers_trigger)
+            .add_observer(BuildingInfoPanelEnergyPriorityButton::on_add)
+            .add_observer(BuildingInfoPanelEnergyPriorityButton::on_set_energy_priority_command)
             ;
     }
 }
@@ -178,12 +190,146 @@ fn initialize_building_panel_content_system(
                     ],
                 ),
                 // Specialized panels depending on the building type
+                energy_subpanel_content_bundle(),
                 tower_subpanel_content_bundle(),
             ],
         ));
     });
 }
 
+// Energy subpanel section, for buildings drawing energy from their network
+#[derive(Component)]
+struct BuildingInfoPanelEnergyRoot;
+
+fn on_building_info_panel_enabled_for_consumers_trigger(
+    trigger: On<BuildingInfoPanelEnabledTrigger>,
+    energy_subpanel_root: Single<&mut Node, With<BuildingInfoPanelEnergyRoot>>,
+    consumers: Query<&EnergyPriority, With<NeedsPower>>,
+    priority_text: Single<&mut Text, With<BuildingInfoPanelEnergyPriorityText>>,
+) {
+    let Ok(priority) = consumers.get(trigger.entity) else {
+        energy_subpanel_root.into_inner().display = Display::None;
+        return;
+    };
+    energy_subpanel_root.into_inner().display = Display::Flex;
+    priority_text.into_inner().0 = BuildingInfoPanelEnergyPriorityButton::text(*priority);
+}
+
+fn energy_subpanel_content_bundle() -> impl Bundle {
+    (
+        Node {
+            width: Val::Percent(100.),
+            flex_direction: FlexDirection::Row,
+            justify_content: JustifyContent::Start,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BuildingInfoPanelEnergyRoot,
+        children![
+            BuildingInfoPanelEnergyPriorityButton,
+            (
+                Text::new("Energy ##/##"),
+                TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                Node {
+                    margin: UiRect { left: Val::Px(4.), right: Val::Px(4.), bottom: Val::Px(4.), ..default() },
+                    ..default()
+                },
+                BuildingInfoPanelEnergyStatusText,
+            ),
+        ],
+    )
+}
+
+// Consumption of the building and the balance of its network
+#[derive(Component)]
+struct BuildingInfoPanelEnergyStatusText;
+impl BuildingInfoPanelEnergyStatusText {
+    fn update_system(
+        display_info_panel: Single<&DisplayInfoPanel>,
+        energy_networks: Res<EnergyNetworks>,
+        consumers: Query<(&EnergyConsumption, &NeedsPower, Has<Brownout>)>,
+        status_text: Single<(&mut Text, &mut TextColor), With<BuildingInfoPanelEnergyStatusText>>,
+    ) {
+        let focused_entity = display_info_panel.into_inner().current_focus;
+        let Ok((consumption, needs_power, is_browned_out)) = consumers.get(focused_entity) else { return; };
+        let (mut text, mut text_color) = status_text.into_inner();
+        let network = needs_power.network.and_then(|network| energy_networks.get(network));
+        let status = match network {
+            None => "No power".to_string(),
+            Some(network) => format!(
+                "{} {:.0} | Network {:.0}/{:.0}",
+                if is_browned_out { "Brownout" } else { "Uses" }, consumption.0, network.demand, network.production,
+            ),
+        };
+        if text.0 != status { text.0 = status; }
+        let color = if is_browned_out || network.is_none() { ORANGE.into() } else { Color::WHITE };
+        if text_color.0 != color { text_color.0 = color; }
+    }
+}
+
+// Energy priority button, cycles through the options on click
+#[derive(Component)]
+#[require(Button)]
+struct BuildingInfoPanelEnergyPriorityButton;
+#[derive(Component)]
+struct BuildingInfoPanelEnergyPriorityText;
+impl BuildingInfoPanelEnergyPriorityButton {
+    fn on_add(
+        trigger: On<Add, BuildingInfoPanelEnergyPriorityButton>,
+        mut commands: Commands,
+    ) {
+        let entity = trigger.entity;
+        commands
+            .entity(entity)
+            .insert((
+                Node {
+                    margin: UiRect { left: Val::Px(4.), right: Val::Px(4.), bottom: Val::Px(4.), ..default() },
+                    padding: UiRect::axes(Val::Px(4.), Val::Px(2.)),
+                    ..default()
+                },
+                BackgroundColor::from(WHITE.with_alpha(0.1)),
+            ))
+            .observe(Self::on_click)
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(Self::text(EnergyPriority::default())),
+                    TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                    BuildingInfoPanelEnergyPriorityText,
+                ));
+            });
+    }
+
+    fn text(priority: EnergyPriority) -> String {
+        format!("Power: {}", priority.label())
+    }
+
+    fn on_click(
+        _trigger: On<Pointer<Click>>,
+        mut commands: Commands,
+        display_info_panel: Single<&DisplayInfoPanel>,
+        consumers: Query<(&GridCoords, &EnergyPriority), With<NeedsPower>>,
+        text: Single<&mut Text, With<BuildingInfoPanelEnergyPriorityText>>,
+    ) {
+        let focused_entity = display_info_panel.into_inner().current_focus;
+        let Ok((coords, priority)) = consumers.get(focused_entity) else { return; };
+        let priority = priority.next();
+        commands.queue(PlayerCommand::SetEnergyPriority { coords: *coords, priority });
+
+        text.into_inner().0 = Self::text(priority);
+    }
+
+    fn on_set_energy_priority_command(
+        trigger: On<PlayerCommand>,
+        mut commands: Commands,
+        consumers: Query<(Entity, &GridCoords), With<NeedsPower>>,
+    ) {
+        let PlayerCommand::SetEnergyPriority { coords, priority } = *trigger.event() else { return; };
+        let Some(consumer) = entity_at_coords(&consumers, coords) else { return; };
+        // Picked up by the next balancing of the network
+        commands.entity(consumer).insert(priority);
+    }
+}
+
 // Tower subpanel section
 fn on_building_info_panel_enabled_for_towers_trigger(
     trigger: On<BuildingInfoPanelEnabledTrigger>,
@@ -226,6 +372,7 @@ fn tower_subpanel_content_bundle() -> impl Bundle {
         },
         BuildingInfoPanelTowerRoot,
         children![
+            BuildingInfoPanelTargetingPriorityButton,
             (
                 Text::new("--- Upgrades ##/## ---"),
                 TextColor::from(BLUE),
@@ -249,6 +396,78 @@ fn tower_subpanel_content_bundle() -> impl Bundle {
     )
 }
 
+// Targeting priority button, cycles through the options on click
+#[derive(Component)]
+#[require(Button)]
+struct BuildingInfoPanelTargetingPriorityButton;
+#[derive(Component)]
+struct BuildingInfoPanelTargetingPriorityText;
+impl BuildingInfoPanelTargetingPriorityButton {
+    fn on_add(
+        trigger: On<Add, BuildingInfoPanelTargetingPriorityButton>,
+        mut commands: Commands,
+    ) {
+        let entity = trigger.entity;
+        commands
+            .entity(entity)
+            .insert((
+                Node {
+                    margin: UiRect { left: Val::Px(4.), right: Val::Px(4.), bottom: Val::Px(4.), ..default() },
+                    padding: UiRect::axes(Val::Px(4.), Val::Px(2.)),
+                    ..default()
+                },
+                BackgroundColor::from(WHITE.with_alpha(0.1)),
+            ))
+            .observe(Self::on_click)
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(Self::text(TargetingPriority::default())),
+                    TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                    BuildingInfoPanelTargetingPriorityText,
+                ));
+            });
+    }
+
+    fn text(priority: TargetingPriority) -> String {
+        format!("Target: {}", priority.label())
+    }
+
+    fn refresh_on_panel_enabled(
+        trigger: On<BuildingInfoPanelEnabledTrigger>,
+        towers: Query<&TargetingPriority, With<Tower>>,
+        text: Single<&mut Text, With<BuildingInfoPanelTargetingPriorityText>>,
+    ) {
+        let Ok(priority) = towers.get(trigger.entity) else { return; };
+        text.into_inner().0 = Self::text(*priority);
+    }
+
+    fn on_click(
+        _trigger: On<Pointer<Click>>,
+        mut commands: Commands,
+        display_info_panel: Single<&DisplayInfoPanel>,
+        towers: Query<(&GridCoords, &TargetingPriority), With<Tower>>,
+        text: Single<&mut Text, With<BuildingInfoPanelTargetingPriorityText>>,
+    ) {
+        let focused_entity = display_info_panel.into_inner().current_focus;
+        let Ok((coords, priority)) = towers.get(focused_entity) else { return; };
+        let priority = priority.next();
+        commands.queue(PlayerCommand::SetTargetingPriority { coords: *coords, priority });
+
+        text.into_inner().0 = Self::text(priority);
+    }
+
+    fn on_set_targeting_priority_command(
+        trigger: On<PlayerCommand>,
+        mut commands: Commands,
+        towers: Query<(Entity, &GridCoords), With<Tower>>,
+    ) {
+        let PlayerCommand::SetTargetingPriority { coords, priority } = *trigger.event() else { return; };
+        let Some(tower) = entity_at_coords(&towers, coords) else { return; };
+        // The current target is kept while it stays in range, the new priority applies to the next pick
+        commands.entity(tower).insert(priority);
+    }
+}
+
 // Disable/Enable button
 #[derive(Component)]
 #[require(Button)]
@@ -290,19 +509,31 @@ impl BuildingInfoPanelDisableButton {
         mut commands: Commands,
         display_info_panel: Single<&DisplayInfoPanel>,
         disabled_by_player: Query<(), With<DisabledByPlayer>>,
+        buildings: Query<&GridCoords, With<Building>>,
         icon: Single<&mut ImageNode, With<BuildingInfoPanelDisableButtonIcon>>,
     ) {
         let focused_entity = display_info_panel.into_inner().current_focus;
+        let Ok(coords) = buildings.get(focused_entity) else { return; };
         let is_disabled = disabled_by_player.contains(focused_entity);
-        if is_disabled {
-            commands.entity(focused_entity).remove::<DisabledByPlayer>();
-        } else {
-            commands.entity(focused_entity).insert(DisabledByPlayer);
-        }
+        commands.queue(PlayerCommand::SetDisabledByPlayer { coords: *coords, disabled: !is_disabled });
 
         // Update icon alpha to reflect state after toggle
         icon.into_inner().color.set_alpha(if is_disabled { 0.35 } else { 1.0 });
     }
+
+    fn on_set_disabled_by_player_command(
+        trigger: On<PlayerCommand>,
+        mut commands: Commands,
+        buildings: Query<(Entity, &GridCoords), With<Building>>,
+    ) {
+        let PlayerCommand::SetDisabledByPlayer { coords, disabled } = *trigger.event() else { return; };
+        let Some(building) = entity_at_coords(&buildings, coords) else { return; };
+        if disabled {
+            commands.entity(building).insert(DisabledByPlayer);
+        } else {
+            commands.entity(building).remove::<DisabledByPlayer>();
+        }
+    }
 }
 
 // Destroy button
@@ -344,10 +575,12 @@ impl BuildingInfoPanelDestroyButton {
         _trigger: On<Pointer<Click>>,
         mut commands: Commands,
         display_info_panel: Single<&DisplayInfoPanel>,
+        buildings: Query<&GridCoords, With<Building>>,
     ) {
         let focused_entity = display_info_panel.into_inner().current_focus;
-        
-        // Emit building destroy request event
-        commands.trigger(BuildingDestroyRequest(focused_entity));
+        let Ok(coords) = buildings.get(focused_entity) else { return; };
+
+        // Recorded for replays, then turned into a BuildingDestroyRequest
+        commands.queue(PlayerCommand::DestroyBuilding { coords: *coords });
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
-use lib_grid::grids::emissions::{EmissionsType, EmitterEnergy};
-use lib_grid::grids::energy_supply::{GeneratorEnergy, SupplierEnergy};
+use lib_grid::grids::emissions::{EmissionsType, EmissionsEmitter};
+use lib_grid::grids::energy_supply::{GeneratorEnergy, SupplierEnergy, SupplierEnergyEnabled};
 use lib_grid::search::flooding::{FloodEmissionsDetails, FloodEmissionsEvaluator, FloodEmissionsMode};
 
 use crate::prelude::*;
@@ -19,52 +19,24 @@ pub const MAIN_BASE_BASE_IMAGE: &str = "buildings/main_base.png";
 
 
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PersistSaveData)]
 pub struct MainBaseSaveData {
     pub entity: Entity,
+    #[persist(health)]
     pub health: f32,
 }
 
-#[derive(Component, SSS)]
+#[derive(Component, SSS, Persist)]
+#[persist(table = "main_bases")]
 pub struct BuilderMainBase {
+    #[persist(grid_coords)]
     pub grid_position: GridCoords,
     pub save_data: Option<MainBaseSaveData>,
 }
-impl Saveable for BuilderMainBase {
-    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
-        let save_data = self.save_data.expect("BuilderMainBase for saving purpose must have save_data");
-        let entity_index = save_data.entity.index() as i64;
-
-        tx.save_marker("main_bases", entity_index)?;
-        tx.save_grid_coords(entity_index, self.grid_position)?;
-        tx.save_health(entity_index, save_data.health)?;
-        Ok(())
-    }
-}
-impl Loadable for BuilderMainBase {
-    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        let mut stmt = ctx.conn.prepare("SELECT id FROM main_bases LIMIT ?1 OFFSET ?2")?;
-        let mut rows = stmt.query(ctx.pagination.as_params())?;
-        
-        let mut count = 0;
-        while let Some(row) = rows.next()? {
-            let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = MainBaseSaveData { entity: new_entity, health };
-                ctx.commands.entity(new_entity).insert(BuilderMainBase::new_for_saving(grid_position, save_data));
-            } else {
-                eprintln!("Warning: MainBase with old ID {} has no corresponding new entity", old_id);
-            }
-            count += 1;
-        }
-
-        Ok(count.into())
-    }
-}
 impl BuilderMainBase {
+    pub fn new(grid_position: GridCoords) -> Self {
+        Self { grid_position, save_data: None }
+    }
     pub fn new_for_saving(grid_position: GridCoords, save_data: MainBaseSaveData) -> Self {
         Self { grid_position, save_data: Some(save_data) }
     }
@@ -115,14 +87,15 @@ impl BuilderMainBase {
                 MainBase,
                 builder.grid_position,
                 grid_imprint,
-                EmitterEnergy(FloodEmissionsDetails {
+                EmissionsEmitter(vec![FloodEmissionsDetails {
                     emissions_type: EmissionsType::Energy,
                     range: usize::MAX,
                     evaluator: FloodEmissionsEvaluator::ExponentialDecay { start_value: 100., decay: 0.1 },
                     mode: FloodEmissionsMode::Increase,
-                }),
+                }]),
                 GeneratorEnergy,
                 SupplierEnergy,
+                SupplierEnergyEnabled,
                 ModifiersBank::from_baseline(&building_info.baseline),
             ));
     }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::map_objects::dark_ore::{
     DarkOre,
     dark_ore_area_scanner::{DarkOreAreaScanner, DarkOreInRange},
@@ -23,57 +25,34 @@ pub const MINING_COMPLEX_BASE_IMAGE: &str = "buildings/mining_complex.png";
 
 #[derive(Component)]
 pub struct MiningComplexDeliveryTimer(pub Timer);
+impl MiningComplexDeliveryTimer {
+    pub fn new(elapsed_secs: f32) -> Self {
+        let mut timer = Timer::from_seconds(1.0, TimerMode::Repeating);
+        timer.set_elapsed(Duration::from_secs_f32(elapsed_secs));
+        Self(timer)
+    }
+}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PersistSaveData)]
 pub struct MiningComplexSaveData {
     pub entity: Entity,
+    #[persist(health)]
     pub health: f32,
+    #[persist(disabled_by_player)]
     pub disabled_by_player: bool,
+    #[persist(energy_priority)]
+    pub energy_priority: EnergyPriority,
+    #[persist(timer)]
+    pub delivery_timer: f32,
 }
 
-#[derive(Component, SSS)]
+#[derive(Component, SSS, Persist)]
+#[persist(table = "mining_complexes")]
 pub struct BuilderMiningComplex {
+    #[persist(grid_coords)]
     pub grid_position: GridCoords,
     pub save_data: Option<MiningComplexSaveData>,
 }
-impl Saveable for BuilderMiningComplex {
-    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
-        let save_data = self.save_data.expect("BuilderMiningComplex for saving purpose must have save_data");
-        let entity_index = save_data.entity.index() as i64;
-
-        tx.save_marker("mining_complexes", entity_index)?;
-        tx.save_grid_coords(entity_index, self.grid_position)?;
-        tx.save_health(entity_index, save_data.health)?;
-        if save_data.disabled_by_player {
-            tx.save_disabled_by_player(entity_index)?;
-        }
-        Ok(())
-    }
-}
-impl Loadable for BuilderMiningComplex {
-    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        let mut stmt = ctx.conn.prepare("SELECT id FROM mining_complexes LIMIT ?1 OFFSET ?2")?;
-        let mut rows = stmt.query(ctx.pagination.as_params())?;
-        
-        let mut count = 0;
-        while let Some(row) = rows.next()? {
-            let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            let disabled_by_player = ctx.conn.get_disabled_by_player(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = MiningComplexSaveData { entity: new_entity, health, disabled_by_player };
-                ctx.commands.entity(new_entity).insert(BuilderMiningComplex::new_for_saving(grid_position, save_data));
-            } else {
-                eprintln!("Warning: MiningComplex with old ID {} has no corresponding new entity", old_id);
-            }
-            count += 1;
-        }
-
-        Ok(count.into())
-    }
-}
 impl BuilderMiningComplex {
     pub fn new(grid_position: GridCoords) -> Self {
         Self { grid_position, save_data: None }
@@ -84,15 +63,17 @@ impl BuilderMiningComplex {
 
     fn on_game_save(
         mut commands: Commands,
-        mining_complexes: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>), With<MiningComplex>>,
+        mining_complexes: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &EnergyPriority, &MiningComplexDeliveryTimer), With<MiningComplex>>,
     ) {
         if mining_complexes.is_empty() { return; }
         println!("Creating batch of BuilderMiningComplex for saving. {} items", mining_complexes.iter().count());
-        let batch = mining_complexes.iter().map(|(entity, coords, health, disabled_by_player)| {
+        let batch = mining_complexes.iter().map(|(entity, coords, health, disabled_by_player, energy_priority, delivery_timer)| {
             let save_data = MiningComplexSaveData {
                 entity,
                 health: health.get_current(),
                 disabled_by_player,
+                energy_priority: *energy_priority,
+                delivery_timer: delivery_timer.0.elapsed_secs(),
             };
             BuilderMiningComplex::new_for_saving(*coords, save_data)
         }).collect::<SaveableBatchCommand<_>>();
@@ -116,6 +97,7 @@ impl BuilderMiningComplex {
         if let Some(save_data) = &builder.save_data {
             // Save data
             entity_commands.insert(Health::new(save_data.health));
+            entity_commands.insert(save_data.energy_priority);
             if save_data.disabled_by_player {
                 entity_commands.insert(DisabledByPlayer);
             }
@@ -134,7 +116,7 @@ impl BuilderMiningComplex {
                 grid_imprint,
                 NeedsPower::default(),
                 DarkOreAreaScanner{range_imprint: grid_imprint},
-                MiningComplexDeliveryTimer(Timer::from_seconds(1.0, TimerMode::Repeating)),
+                MiningComplexDeliveryTimer::new(builder.save_data.map_or(0., |d| d.delivery_timer)),
                 ModifiersBank::from_baseline(&building_info.baseline),
                 related![Indicators[
                     IndicatorType::NoPower,
@@ -152,9 +134,10 @@ fn mine_ore_system(
     mut stock: ResMut<Stock>,
     mut mining_complexes: Query<(&mut MiningComplexDeliveryTimer, &DarkOreInRange), (With<MiningComplex>, With<HasPower>, Without<DisabledByPlayer>)>,
     mut dark_ores: Query<&mut DarkOre>,
+    mut game_rng: ResMut<GameRng>,
     time: Res<Time>,
 ) {
-    let mut rng = nanorand::tls_rng();
+    let rng = game_rng.stream(RngStream::Mining);
     for (mut timer, ore_in_range) in mining_complexes.iter_mut() {
         let ore_in_range = &ore_in_range.0;
         if ore_in_range.is_empty() { continue; }
@@ -1,3 +1,6 @@
+use lib_grid::grids::emissions::{EmissionsEmitter, EmissionsPulse, EmissionsType};
+use lib_grid::search::flooding::{FloodEmissionsDetails, FloodEmissionsEvaluator, FloodEmissionsMode};
+
 use crate::effects::ripple::BuilderRipple;
 use crate::prelude::*;
 use crate::ui::indicators::{IndicatorDisplay, IndicatorType, Indicators};
@@ -17,65 +20,33 @@ impl Plugin for TowerEmitterPlugin {
 
 pub const TOWER_EMITTER_BASE_IMAGE: &str = "buildings/tower_emitter.png";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PersistSaveData)]
 pub struct TowerEmitterSaveData {
     entity: Entity,
+    #[persist(health)]
     health: f32,
+    #[persist(disabled_by_player)]
     disabled_by_player: bool,
+    #[persist(energy_priority)]
+    energy_priority: EnergyPriority,
+    #[persist(upgrades)]
     upgrade_levels: HashMap<UpgradeType, usize>,
+    #[persist(timer)]
+    shooting_timer: f32,
+    #[persist(wisp_target)]
+    wisp_target: Option<Entity>,
+    #[persist(targeting_priority)]
+    targeting_priority: TargetingPriority,
 }
 
-#[derive(Component, SSS)]
+#[derive(Component, SSS, Persist)]
+#[persist(table = "tower_emitters")]
 pub struct BuilderTowerEmitter {
+    #[persist(grid_coords)]
     grid_position: GridCoords,
     save_data: Option<TowerEmitterSaveData>,
 }
 
-impl Saveable for BuilderTowerEmitter {
-    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
-        let save_data = self.save_data.expect("BuilderTowerEmitter for saving must have save_data");
-        let entity_index = save_data.entity.index() as i64;
-
-        tx.save_marker("tower_emitters", entity_index)?;
-        tx.save_grid_coords(entity_index, self.grid_position)?;
-        tx.save_health(entity_index, save_data.health)?;
-        if save_data.disabled_by_player {
-            tx.save_disabled_by_player(entity_index)?;
-        }
-        for (upgrade_type, level) in &save_data.upgrade_levels {
-            tx.save_upgrade_level(entity_index, &upgrade_type.as_db_str(), *level)?;
-        }
-        Ok(())
-    }
-}
-
-impl Loadable for BuilderTowerEmitter {
-    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        let mut stmt = ctx.conn.prepare("SELECT id FROM tower_emitters LIMIT ?1 OFFSET ?2")?;
-        let mut rows = stmt.query(ctx.pagination.as_params())?;
-        
-        let mut count = 0;
-        while let Some(row) = rows.next()? {
-            let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            let disabled_by_player = ctx.conn.get_disabled_by_player(old_id)?;
-            let upgrade_levels: HashMap<UpgradeType, usize> = ctx.conn.get_upgrade_levels_raw(old_id)?
-                .into_iter()
-                .filter_map(|(type_str, level)| UpgradeType::from_db_str(&type_str).map(|t| (t, level)))
-                .collect();
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = TowerEmitterSaveData { entity: new_entity, health, disabled_by_player, upgrade_levels };
-                ctx.commands.entity(new_entity).insert(BuilderTowerEmitter::new_for_saving(grid_position, save_data));
-            }
-            count += 1;
-        }
-
-        Ok(count.into())
-    }
-}
-
 impl BuilderTowerEmitter {
     pub fn new(grid_position: GridCoords) -> Self { Self { grid_position, save_data: None } }
     pub fn new_for_saving(grid_position: GridCoords, save_data: TowerEmitterSaveData) -> Self {
@@ -84,15 +55,19 @@ impl BuilderTowerEmitter {
 
     fn on_game_save(
         mut commands: Commands,
-        towers: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &Upgrades), With<TowerEmitter>>,
+        towers: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &EnergyPriority, &Upgrades, &TowerShootingTimer, &TowerWispTarget, &TargetingPriority), With<TowerEmitter>>,
     ) {
         if towers.is_empty() { return; }
-        let batch = towers.iter().map(|(entity, coords, health, disabled_by_player, upgrades)| {
+        let batch = towers.iter().map(|(entity, coords, health, disabled_by_player, energy_priority, upgrades, shooting_timer, wisp_target, targeting_priority)| {
             let save_data = TowerEmitterSaveData {
                 entity,
                 health: health.get_current(),
                 disabled_by_player,
+                energy_priority: *energy_priority,
                 upgrade_levels: upgrades.get_levels(),
+                shooting_timer: shooting_timer.0.elapsed_secs(),
+                wisp_target: wisp_target.wisp(),
+                targeting_priority: *targeting_priority,
             };
             BuilderTowerEmitter::new_for_saving(*coords, save_data)
         }).collect::<SaveableBatchCommand<_>>();
@@ -114,11 +89,20 @@ impl BuilderTowerEmitter {
 
         let mut entity_commands = commands.entity(entity);
         if let Some(save_data) = &builder.save_data {
-            entity_commands.insert(Health::new(save_data.health));
+            entity_commands.insert((
+                Health::new(save_data.health),
+                TowerShootingTimer::from_elapsed_secs(save_data.shooting_timer),
+                TowerWispTarget::from(save_data.wisp_target),
+                save_data.targeting_priority,
+                save_data.energy_priority,
+            ));
             if save_data.disabled_by_player {
                 entity_commands.insert(DisabledByPlayer);
             }
         }
+        if building_info.requires_line_of_sight {
+            entity_commands.insert(RequiresLineOfSight);
+        }
 
         entity_commands
             .remove::<BuilderTowerEmitter>()
@@ -132,6 +116,14 @@ impl BuilderTowerEmitter {
                 Tower,
                 builder.grid_position,
                 grid_imprint,
+                building_info.range_shape.unwrap_or_default(),
+                // The ripples keep wisps wary of the tower's surroundings
+                EmissionsEmitter(vec![FloodEmissionsDetails {
+                    emissions_type: EmissionsType::Fear,
+                    range: 8,
+                    evaluator: FloodEmissionsEvaluator::ExponentialDecay { start_value: 30., decay: 0.3 },
+                    mode: FloodEmissionsMode::Increase,
+                }]),
                 NeedsPower::default(),
                 ModifiersBank::from_baseline(&building_info.baseline),
                 Upgrades::from_almanach(&building_info.upgrades, builder.save_data.as_ref().map(|d| &d.upgrade_levels)),
@@ -148,10 +140,11 @@ impl BuilderTowerEmitter {
 
 pub fn shooting_system(
     mut commands: Commands,
-    mut tower_emitters: Query<(&Transform, &AttackRange, &mut TowerShootingTimer, &mut TowerWispTarget), (With<TowerEmitter>, With<HasPower>, Without<DisabledByPlayer>)>,
+    mut noise: MessageWriter<EmissionsPulse>,
+    mut tower_emitters: Query<(&GridCoords, &GridImprint, &Transform, &AttackRange, &mut TowerShootingTimer, &mut TowerWispTarget), (With<TowerEmitter>, With<HasPower>, Without<DisabledByPlayer>)>,
     wisps: Query<(), With<Wisp>>,
 ) {
-    for (transform, range, mut timer, mut target) in tower_emitters.iter_mut() {
+    for (coords, grid_imprint, transform, range, mut timer, mut target) in tower_emitters.iter_mut() {
         let TowerWispTarget::Wisp(target_wisp) = *target else { continue; };
         if !timer.0.is_finished() { continue; }
 
@@ -163,5 +156,6 @@ pub fn shooting_system(
 
         commands.spawn(BuilderRipple::new(transform.translation.xy(), range.0 as f32 * CELL_SIZE));
         timer.0.reset();
+        noise.write(tower_shot_noise(*coords, *grid_imprint));
     }
 }
@@ -1,6 +1,7 @@
 use bevy::sprite::Anchor;
 
 use lib_core::utils::angle_difference;
+use lib_grid::grids::emissions::EmissionsPulse;
 
 use crate::prelude::*;
 use crate::ui::indicators::{IndicatorDisplay, IndicatorType, Indicators};
@@ -21,65 +22,35 @@ impl Plugin for TowerRocketLauncherPlugin {
 
 pub const TOWER_ROCKET_LAUNCHER_BASE_IMAGE: &str = "buildings/tower_rocket_launcher.png";
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PersistSaveData)]
 pub struct TowerRocketLauncherSaveData {
     entity: Entity,
+    #[persist(health)]
     health: f32,
+    #[persist(disabled_by_player)]
     disabled_by_player: bool,
+    #[persist(energy_priority)]
+    energy_priority: EnergyPriority,
+    #[persist(upgrades)]
     upgrade_levels: HashMap<UpgradeType, usize>,
+    #[persist(timer)]
+    shooting_timer: f32,
+    #[persist(wisp_target)]
+    wisp_target: Option<Entity>,
+    #[persist(targeting_priority)]
+    targeting_priority: TargetingPriority,
+    #[persist(top_rotation)]
+    top_rotation: f32,
 }
 
-#[derive(Component, SSS)]
+#[derive(Component, SSS, Persist)]
+#[persist(table = "tower_rocket_launchers")]
 pub struct BuilderTowerRocketLauncher {
+    #[persist(grid_coords)]
     grid_position: GridCoords,
     save_data: Option<TowerRocketLauncherSaveData>,
 }
 
-impl Saveable for BuilderTowerRocketLauncher {
-    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
-        let save_data = self.save_data.expect("BuilderTowerRocketLauncher for saving must have save_data");
-        let entity_index = save_data.entity.index() as i64;
-
-        tx.save_marker("tower_rocket_launchers", entity_index)?;
-        tx.save_grid_coords(entity_index, self.grid_position)?;
-        tx.save_health(entity_index, save_data.health)?;
-        if save_data.disabled_by_player {
-            tx.save_disabled_by_player(entity_index)?;
-        }
-        for (upgrade_type, level) in &save_data.upgrade_levels {
-            tx.save_upgrade_level(entity_index, &upgrade_type.as_db_str(), *level)?;
-        }
-        Ok(())
-    }
-}
-
-impl Loadable for BuilderTowerRocketLauncher {
-    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        let mut stmt = ctx.conn.prepare("SELECT id FROM tower_rocket_launchers LIMIT ?1 OFFSET ?2")?;
-        let mut rows = stmt.query(ctx.pagination.as_params())?;
-        
-        let mut count = 0;
-        while let Some(row) = rows.next()? {
-            let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            let disabled_by_player = ctx.conn.get_disabled_by_player(old_id)?;
-            let upgrade_levels: HashMap<UpgradeType, usize> = ctx.conn.get_upgrade_levels_raw(old_id)?
-                .into_iter()
-                .filter_map(|(type_str, level)| UpgradeType::from_db_str(&type_str).map(|t| (t, level)))
-                .collect();
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = TowerRocketLauncherSaveData { entity: new_entity, health, disabled_by_player, upgrade_levels };
-                ctx.commands.entity(new_entity).insert(BuilderTowerRocketLauncher::new_for_saving(grid_position, save_data));
-            }
-            count += 1;
-        }
-
-        Ok(count.into())
-    }
-}
-
 impl BuilderTowerRocketLauncher {
     pub fn new(grid_position: GridCoords) -> Self { Self { grid_position, save_data: None } }
     pub fn new_for_saving(grid_position: GridCoords, save_data: TowerRocketLauncherSaveData) -> Self {
@@ -88,15 +59,20 @@ impl BuilderTowerRocketLauncher {
 
     fn on_game_save(
         mut commands: Commands,
-        towers: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &Upgrades), With<TowerRocketLauncher>>,
+        towers: Query<(Entity, &GridCoords, &Health, Has<DisabledByPlayer>, &EnergyPriority, &Upgrades, &TowerShootingTimer, &TowerWispTarget, &TargetingPriority, &TowerTopRotation), With<TowerRocketLauncher>>,
     ) {
         if towers.is_empty() { return; }
-        let batch = towers.iter().map(|(entity, coords, health, disabled_by_player, upgrades)| {
+        let batch = towers.iter().map(|(entity, coords, health, disabled_by_player, energy_priority, upgrades, shooting_timer, wisp_target, targeting_priority, top_rotation)| {
             let save_data = TowerRocketLauncherSaveData {
                 entity,
                 health: health.get_current(),
                 disabled_by_player,
+                energy_priority: *energy_priority,
                 upgrade_levels: upgrades.get_levels(),
+                shooting_timer: shooting_timer.0.elapsed_secs(),
+                wisp_target: wisp_target.wisp(),
+                targeting_priority: *targeting_priority,
+                top_rotation: top_rotation.current_angle,
             };
             BuilderTowerRocketLauncher::new_for_saving(*coords, save_data)
         }).collect::<SaveableBatchCommand<_>>();
@@ -118,11 +94,20 @@ impl BuilderTowerRocketLauncher {
         let mut entity_commands = commands.entity(entity);
         
         if let Some(save_data) = &builder.save_data {
-            entity_commands.insert(Health::new(save_data.health));
+            entity_commands.insert((
+                Health::new(save_data.health),
+                TowerShootingTimer::from_elapsed_secs(save_data.shooting_timer),
+                TowerWispTarget::from(save_data.wisp_target),
+                save_data.targeting_priority,
+                save_data.energy_priority,
+            ));
             if save_data.disabled_by_player {
                 entity_commands.insert(DisabledByPlayer);
             }
         }
+        if building_info.requires_line_of_sight {
+            entity_commands.insert(RequiresLineOfSight);
+        }
         
         let tower_base_entity = entity_commands
             .remove::<BuilderTowerRocketLauncher>()
@@ -136,7 +121,8 @@ impl BuilderTowerRocketLauncher {
                 Tower,
                 builder.grid_position,
                 grid_imprint,
-                TowerTopRotation { speed: 1.0, current_angle: 0. },
+                building_info.range_shape.unwrap_or_default(),
+                TowerTopRotation { speed: 1.0, current_angle: builder.save_data.as_ref().map_or(0., |d| d.top_rotation) },
                 NeedsPower::default(),
                 ModifiersBank::from_baseline(&building_info.baseline),
                 Upgrades::from_almanach(&building_info.upgrades, builder.save_data.as_ref().map(|d| &d.upgrade_levels)),
@@ -165,10 +151,11 @@ impl BuilderTowerRocketLauncher {
 
 pub fn shooting_system(
     mut commands: Commands,
-    mut tower_rocket_launchers: Query<(&GridImprint, &Transform, &mut TowerShootingTimer, &mut TowerWispTarget, &TowerTopRotation, &AttackDamage), (With<TowerRocketLauncher>, With<HasPower>, Without<DisabledByPlayer>)>,
+    mut noise: MessageWriter<EmissionsPulse>,
+    mut tower_rocket_launchers: Query<(&GridCoords, &GridImprint, &Transform, &mut TowerShootingTimer, &mut TowerWispTarget, &TowerTopRotation, &AttackDamage), (With<TowerRocketLauncher>, With<HasPower>, Without<DisabledByPlayer>)>,
     wisps: Query<&Transform, With<Wisp>>,
 ) {
-    for (grid_imprint, transform, mut timer, mut target, top_rotation, attack_damage) in tower_rocket_launchers.iter_mut() {
+    for (coords, grid_imprint, transform, mut timer, mut target, top_rotation, attack_damage) in tower_rocket_launchers.iter_mut() {
         let TowerWispTarget::Wisp(target_wisp) = *target else { continue; };
         if !timer.0.is_finished() { continue; }
 
@@ -194,5 +181,6 @@ pub fn shooting_system(
         let rocket_angle = Quat::from_rotation_z(top_rotation.current_angle);
         commands.spawn(BuilderRocket::new(spawn_position, rocket_angle, target_wisp, attack_damage.clone()));
         timer.0.reset();
+        noise.write(tower_shot_noise(*coords, *grid_imprint));
     }
 }
@@ -1,16 +1,26 @@
 use std::fs::File;
-    
+
 use serde::{Deserialize, Serialize};
 
+use lib_core::persistence::rusqlite::OptionalExtension;
+
 use crate::prelude::*;
 
 pub struct DataLoaderPlugin;
 impl Plugin for DataLoaderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(MapLoadingStage::LoadMapInfo), load_data_system);
+        app
+            .init_resource::<AlmanachSource>()
+            .init_resource::<AlmanachSnapshotDiff>()
+            .add_systems(OnEnter(MapLoadingStage::LoadMapInfo), load_data_system)
+            .add_observer(AlmanachSource::on_load_game_signal)
+            .add_observer(ReloadWithAlmanachSource::on_trigger)
+            .register_db_loader::<AlmanachSnapshot>(MapLoadingStage::LoadResources)
+            .register_db_saver(AlmanachSnapshot::on_game_save);
     }
 }
 
+pub const DATA_PATH: &str = "assets/data.yaml";
 
 #[derive(Serialize, Deserialize)]
 struct Data {
@@ -19,9 +29,197 @@ struct Data {
 
 fn load_data_system(
     mut almanach: ResMut<Almanach>,
+    mut snapshot_diff: ResMut<AlmanachSnapshotDiff>,
 ) {
-    let data: Data = serde_yaml::from_reader(File::open(format!("assets/data.yaml")).unwrap()).unwrap();
+    let data: Data = serde_yaml::from_reader(File::open(DATA_PATH).unwrap()).unwrap();
+    for building_info in data.buildings.iter() {
+        if let Err(e) = building_info.grid_imprint.validate() {
+            panic!("Invalid grid_imprint of '{}' in '{}': {}", building_info.name, DATA_PATH, e);
+        }
+    }
     data.buildings.into_iter().for_each(
         |building_info| almanach.add_building_info(building_info)
     );
-}
\ No newline at end of file
+    snapshot_diff.changed_buildings.clear();
+}
+
+/// Which building data a loaded save uses when it carries an almanach snapshot.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub enum AlmanachSource {
+    /// The data the game was saved with.
+    #[default]
+    Snapshot,
+    /// The data of `assets/data.yaml`.
+    Current,
+}
+impl AlmanachSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlmanachSource::Snapshot => "saved",
+            AlmanachSource::Current => "current",
+        }
+    }
+
+    /// Every load starts with the saved data, unless it is a `ReloadWithAlmanachSource` asking for another one.
+    fn on_load_game_signal(
+        _trigger: On<LoadGameSignal>,
+        mut commands: Commands,
+        mut almanach_source: ResMut<AlmanachSource>,
+        pending_source: Option<Res<PendingAlmanachSource>>,
+    ) {
+        *almanach_source = pending_source.map_or(AlmanachSource::default(), |pending_source| pending_source.0);
+        commands.remove_resource::<PendingAlmanachSource>();
+    }
+}
+
+/// Source picked by `ReloadWithAlmanachSource` for the load it triggers.
+#[derive(Resource)]
+struct PendingAlmanachSource(AlmanachSource);
+
+/// Names of the buildings whose snapshot in the loaded save differs from the current data.
+#[derive(Resource, Default)]
+pub struct AlmanachSnapshotDiff {
+    pub changed_buildings: Vec<String>,
+}
+
+/// Loads the current save again using the given building data.
+#[derive(Event)]
+pub struct ReloadWithAlmanachSource(pub AlmanachSource);
+impl ReloadWithAlmanachSource {
+    fn on_trigger(
+        trigger: On<ReloadWithAlmanachSource>,
+        mut commands: Commands,
+        load_report: Res<LoadReport>,
+    ) {
+        let almanach_source = trigger.event().0;
+        println!("Reloading '{}' with the {} building data", load_report.path, almanach_source.label());
+        commands.insert_resource(PendingAlmanachSource(almanach_source));
+        commands.trigger(LoadGameSignal(load_report.path.clone()));
+    }
+}
+
+struct SavedUpgradeLevel {
+    entity_id: i64,
+    building_type: BuildingType,
+    upgrade_type: String,
+    level: usize,
+}
+
+/// Building data the game was saved with, stored in the same format as `assets/data.yaml`.
+/// Keeps balance changes from silently altering costs and upgrades of older saves.
+#[derive(SSS)]
+struct AlmanachSnapshot {
+    data: String,
+}
+impl AlmanachSnapshot {
+    fn on_game_save(
+        mut commands: Commands,
+        almanach: Res<Almanach>,
+    ) {
+        let mut buildings = almanach.buildings().cloned().collect::<Vec<_>>();
+        buildings.sort_by(|a, b| a.name.cmp(&b.name));
+        match serde_yaml::to_string(&Data { buildings }) {
+            Ok(data) => commands.queue(SaveableBatchCommand::from_single(AlmanachSnapshot { data })),
+            Err(e) => eprintln!("Failed to serialize the almanach snapshot: {}", e),
+        }
+    }
+
+    /// Puts the snapshot into the almanach unless the player picked the current data,
+    /// then checks the saved upgrade levels against the data in use. Returns the issues found.
+    fn apply(world: &mut World, snapshot: Option<Vec<AlmanachBuildingInfo>>, saved_levels: Vec<SavedUpgradeLevel>) -> Vec<(Option<i64>, String)> {
+        let almanach_source = *world.resource::<AlmanachSource>();
+        let mut issues = Vec::new();
+
+        if let Some(snapshot) = snapshot {
+            let mut almanach = world.resource_mut::<Almanach>();
+            let changed_buildings = snapshot.iter()
+                .filter(|info| almanach.find_building_info(info.building_type) != Some(*info))
+                .map(|info| info.name.clone())
+                .collect::<Vec<_>>();
+            if !changed_buildings.is_empty() {
+                issues.push((None, format!(
+                    "Saved building data differs from '{}' for {}, using the {} data",
+                    DATA_PATH, changed_buildings.join(", "), almanach_source.label(),
+                )));
+            }
+            if almanach_source == AlmanachSource::Snapshot {
+                snapshot.into_iter().for_each(|info| almanach.add_building_info(info));
+            }
+            world.resource_mut::<AlmanachSnapshotDiff>().changed_buildings = changed_buildings;
+        }
+
+        let almanach = world.resource::<Almanach>();
+        for saved in saved_levels {
+            let Some(upgrade_type) = UpgradeType::from_db_str(&saved.upgrade_type) else {
+                issues.push((Some(saved.entity_id), format!("Unknown upgrade type '{}'", saved.upgrade_type)));
+                continue;
+            };
+            let available_levels = almanach.find_building_info(saved.building_type)
+                .and_then(|info| info.upgrades.get(&upgrade_type))
+                .map_or(0, |upgrade_info| upgrade_info.levels.len());
+            if saved.level > available_levels {
+                issues.push((Some(saved.entity_id), format!(
+                    "{:?} upgrade {} is at level {}, but the {} data only has {} level(s)",
+                    saved.building_type, saved.upgrade_type, saved.level, almanach_source.label(), available_levels,
+                )));
+            }
+        }
+        issues
+    }
+}
+impl Saveable for AlmanachSnapshot {
+    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
+        tx.execute(
+            "INSERT OR REPLACE INTO almanach_snapshot (id, data) VALUES (1, ?1)",
+            [self.data],
+        )?;
+        Ok(())
+    }
+}
+impl Loadable for AlmanachSnapshot {
+    /// Saves without a snapshot keep the current data.
+    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
+        let data = ctx.conn.query_row("SELECT data FROM almanach_snapshot WHERE id = 1", [], |row| row.get::<_, String>(0)).optional()?;
+        let snapshot = match data.map(|data| serde_yaml::from_str::<Data>(&data)) {
+            Some(Ok(data)) => match data.buildings.iter().find_map(|info| info.grid_imprint.validate().err().map(|e| (info, e))) {
+                Some((info, e)) => {
+                    ctx.report_issue(None, format!("Invalid grid_imprint of '{}' in the almanach snapshot, using the current data: {}", info.name, e));
+                    None
+                }
+                None => Some(data.buildings),
+            },
+            Some(Err(e)) => {
+                ctx.report_issue(None, format!("Malformed almanach snapshot, using the current data: {}", e));
+                None
+            }
+            None => None,
+        };
+
+        let mut saved_levels = Vec::new();
+        for (table, building_type) in BUILDING_TABLES {
+            let mut stmt = ctx.conn.prepare(&format!(
+                "SELECT entity_id, upgrade_type, current_level FROM upgrade_levels WHERE entity_id IN (SELECT id FROM {})", table
+            ))?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                saved_levels.push(SavedUpgradeLevel {
+                    entity_id: row.get(0)?,
+                    building_type,
+                    upgrade_type: row.get(1)?,
+                    level: row.get::<_, i64>(2)? as usize,
+                });
+            }
+        }
+
+        // The almanach is a resource, so the snapshot is applied once the commands run
+        let (loader, stage) = (ctx.loader_name, ctx.stage.clone());
+        ctx.commands.queue(move |world: &mut World| {
+            let issues = AlmanachSnapshot::apply(world, snapshot, saved_levels);
+            let mut load_report = world.resource_mut::<LoadReport>();
+            for (row_id, error) in issues {
+                load_report.push(LoadIssue { loader, stage: stage.clone(), row_id, error });
+            }
+        });
+        Ok(LoadResult::Finished)
+    }
+}
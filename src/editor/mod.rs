@@ -6,6 +6,7 @@ use strum::{AsRefStr, EnumIter, IntoEnumIterator};
 
 use crate::objectives::{ObjectiveDetails, ObjectiveKillWisps};
 use crate::prelude::*;
+use crate::text_map::{text_map_export_path, ExportTextMapSignal};
 use crate::wisps::summoning::Summoning;
 
 pub struct EditorPlugin;
@@ -40,6 +41,7 @@ fn editor_ui(
     mut commands: Commands,
     mut summonings: Query<(Entity, &mut Summoning)>,
     mut objectives_query: Query<(Entity, &mut ObjectiveDetails, Option<&mut ObjectiveKillWisps>)>,
+    map_info: Option<Res<MapInfo>>,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else { return };
     egui::Window::new("Editor")
@@ -57,13 +59,22 @@ fn editor_ui(
             ui.separator();
 
             match state.active_tab {
-                EditorTab::General => tab_general(ui),
+                EditorTab::General => tab_general(ui, &mut commands, map_info.as_deref()),
                 EditorTab::Summonings => summonings::tab_summonings(ui, &mut state, &mut commands, &mut summonings),
                 EditorTab::Objectives => objectives::tab_objectives(ui, &mut state, &mut commands, &mut objectives_query),
             }
         });
 }
 
-fn tab_general(ui: &mut egui::Ui) {
+fn tab_general(ui: &mut egui::Ui, commands: &mut Commands, map_info: Option<&MapInfo>) {
     ui.label("General settings");
+    let Some(map_info) = map_info else { return; };
+    ui.label(format!("Map: {} ({}x{})", map_info.name, map_info.grid_width, map_info.grid_height));
+    ui.horizontal(|ui| {
+        for extension in ["yaml", "json"] {
+            if ui.button(format!("Export as {}", extension.to_uppercase())).clicked() {
+                commands.trigger(ExportTextMapSignal(text_map_export_path(&map_info.name, extension)));
+            }
+        }
+    });
 }
@@ -65,20 +65,23 @@ impl Loadable for BuilderRipple {
             let old_id: i64 = row.get(0)?;
             let max_radius: f32 = row.get(1)?;
             let current_radius: f32 = row.get(2)?;
-            let world_position = ctx.conn.get_world_position(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = RippleSaveData { entity: new_entity, current_radius };
-                ctx.commands.entity(new_entity).insert(BuilderRipple::new_for_saving(
-                    world_position,
-                    max_radius,
-                    save_data
-                ));
-            }
             count += 1;
+            let Some(world_position) = ctx.expect_row(old_id, "world_positions", ctx.conn.get_world_position(old_id))? else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+            let save_data = RippleSaveData { entity: new_entity, current_radius };
+            ctx.commands.entity(new_entity).insert(BuilderRipple::new_for_saving(
+                world_position,
+                max_radius,
+                save_data
+            ));
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("ripples").map(Some)
+    }
 }
 
 impl BuilderRipple {
@@ -185,27 +188,15 @@ fn ripple_propagate_system(
 pub fn ripple_hit_system(
     wisps_grid: Res<WispsGrid>,
     ripples: Query<(&Ripple, &Transform)>,
-    mut wisps: Query<(&mut Health, &Transform), With<Wisp>>,
+    wisps_transforms: Query<&Transform, With<Wisp>>,
+    mut wisps_health: Query<&mut Health, With<Wisp>>,
 ) {
     for (ripple, ripple_transform) in ripples.iter() {
-        // Check all fields covered by the ripple for wisp collisions
-        let starting_grid_coords = GridCoords::from_transform(&ripple_transform);
-        let bounds_range = (ripple.current_radius / CELL_SIZE) as i32;
-        // Make bounds +/-1 since the ripple starts from in-between the grid fields
-        let lower_bound_x = std::cmp::max(0, starting_grid_coords.x - bounds_range - 1);
-        let lower_bound_y = std::cmp::max(0, starting_grid_coords.y - bounds_range - 1);
-        let upper_bound_x = std::cmp::min(wisps_grid.width - 1, starting_grid_coords.x + bounds_range + 1);
-        let upper_bound_y = std::cmp::min(wisps_grid.height - 1, starting_grid_coords.y + bounds_range + 1);
-        for x in lower_bound_x..=upper_bound_x {
-            for y in lower_bound_y..=upper_bound_y {
-                for wisp in &wisps_grid[GridCoords{ x, y }] {
-                    let Ok((mut wisp_health, wisp_transform)) = wisps.get_mut(*wisp) else { continue; };
-                    let distance = wisp_transform.translation.distance(ripple_transform.translation);
-                    // Hit only wisps that are up to 5 units away from the front of the ripple
-                    if distance > ripple.current_radius || distance < ripple.current_radius - 1. { continue; }
-                    wisp_health.decrease(1.);
-                }
-            }
+        for hit in wisps_grid.wisps_in_radius(&wisps_transforms, ripple_transform.translation.xy(), ripple.current_radius) {
+            // Hit only wisps that are up to 1 unit away from the front of the ripple
+            if hit.distance < ripple.current_radius - 1. { continue; }
+            let Ok(mut wisp_health) = wisps_health.get_mut(hit.entity) else { continue; };
+            wisp_health.decrease(1.);
         }
     }
 }
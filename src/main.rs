@@ -7,6 +7,10 @@ mod overlays;
 mod objectives;
 mod prelude;
 mod projectiles;
+mod replay;
+#[cfg(test)]
+mod save_load_tests;
+mod text_map;
 mod ui;
 mod units;
 mod wisps;
@@ -41,6 +45,8 @@ fn main() {
         .add_plugins((
             data_loader::DataLoaderPlugin,
             editor::EditorPlugin,
+            text_map::TextMapPlugin,
+            replay::ReplayPlugin,
         ))
         // Warning: Bevy behaves wierdly when there are many Startup systems. If some plugins begin to not run at all, watch out for Startup systems at all places. Use Post/Pre Startup instead.
         .add_systems(PostStartup, |mut commands: Commands| commands.trigger(lib_core::persistence::load::LoadGameSignal("maps/test_map.dwd".to_string())))
@@ -82,19 +82,20 @@ impl Loadable for BuilderDarkOre {
         while let Some(row) = rows.next()? {
             let old_id: i64 = row.get(0)?;
             let amount: u32 = row.get(1)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = DarkOreSaveData { entity: new_entity };
-                ctx.commands.entity(new_entity).insert(BuilderDarkOre::new_for_saving(grid_position, amount, save_data));
-            } else {
-                eprintln!("Warning: DarkOre with old ID {} has no corresponding new entity", old_id);
-            }
             count += 1;
+            let Some(grid_position) = ctx.expect_row(old_id, "grid_coords", ctx.conn.get_grid_coords(old_id))? else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+            let save_data = DarkOreSaveData { entity: new_entity };
+            ctx.commands.entity(new_entity).insert(BuilderDarkOre::new_for_saving(grid_position, amount, save_data));
         }
 
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("dark_ores").map(Some)
+    }
 }
 impl BuilderDarkOre {
     pub fn new(grid_position: GridCoords, amount: u32) -> Self {
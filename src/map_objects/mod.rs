@@ -2,6 +2,7 @@ pub mod walls;
 pub mod dark_ore;
 pub mod quantum_field;
 pub mod common;
+pub mod terrain;
 
 use crate::prelude::*;
 
@@ -14,6 +15,7 @@ impl Plugin for MapObjectsPlugin {
                 dark_ore::DarkOrePlugin,
                 quantum_field::QuantumFieldPlugin,
                 walls::WallPlugin,
+                terrain::TerrainPlugin,
             ));
     }
 }
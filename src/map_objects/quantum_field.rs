@@ -4,6 +4,7 @@ use lib_grid::grids::obstacles::{ObstacleGrid, ReservedCoords};
 use lib_ui::prelude::*;
 
 use crate::prelude::*;
+use crate::replay::{entity_at_coords, PlayerCommand};
 use crate::map_objects::common::ExpeditionZone;
 use crate::ui::display_info_panel::{DisplayInfoPanel, DisplayPanelMainContentRoot, UiMapObjectFocusedTrigger};
 use crate::ui::grid_object_placer::{GridObjectPlacer, GridObjectPlacerRequest};
@@ -33,6 +34,7 @@ impl Plugin for QuantumFieldPlugin {
             .add_observer(GridPlacerUiForQuantumField::on_add)
             .add_observer(ArrowButton::on_add)
             .add_observer(QuantumFieldActionButton::on_add)
+            .add_observer(on_quantum_field_player_command)
             .add_observer(on_ui_map_object_focus_changed_trigger)
             .register_db_loader::<BuilderQuantumField>(MapLoadingStage::SpawnMapElements)
             .register_db_saver(BuilderQuantumField::on_game_save)
@@ -166,25 +168,26 @@ impl Loadable for BuilderQuantumField {
             let current_layer_progress: i32 = row.get(2)?;
             let is_expedition_target: bool = row.get(3)?;
             
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            let grid_imprint = ctx.conn.get_grid_imprint(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = QuantumFieldSaveData { 
-                    entity: new_entity,
-                    current_layer,
-                    current_layer_progress,
-                    is_expedition_target,
-                };
-                ctx.commands.entity(new_entity).insert(BuilderQuantumField::new_for_saving(grid_position, grid_imprint, save_data));
-            } else {
-                eprintln!("Warning: QuantumField with old ID {} has no corresponding new entity", old_id);
-            }
             count += 1;
+            let Some(grid_position) = ctx.expect_row(old_id, "grid_coords", ctx.conn.get_grid_coords(old_id))? else { continue; };
+            let Some(grid_imprint) = ctx.expect_row(old_id, "grid_imprints", ctx.conn.get_grid_imprint(old_id))? else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+            let save_data = QuantumFieldSaveData { 
+                entity: new_entity,
+                current_layer,
+                current_layer_progress,
+                is_expedition_target,
+            };
+            ctx.commands.entity(new_entity).insert(BuilderQuantumField::new_for_saving(grid_position, grid_imprint, save_data));
         }
 
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("quantum_fields").map(Some)
+    }
 }
 
 impl BuilderQuantumField {
@@ -461,26 +464,22 @@ impl QuantumFieldActionButton {
     fn on_click(
         _trigger: On<Pointer<Click>>,
         mut commands: Commands,
-        mut stock: ResMut<Stock>,
         display_info_panel: Single<&DisplayInfoPanel>,
         action_button: Single<&mut QuantumFieldActionButton>,
-        mut quantum_fields: Query<&mut QuantumField>,
+        quantum_fields: Query<&GridCoords, With<QuantumField>>,
     ) {
         let focused_entity = display_info_panel.into_inner().current_focus;
+        let Ok(&coords) = quantum_fields.get(focused_entity) else { return; };
         let mut action_button = action_button.into_inner();
         match *action_button {
             QuantumFieldActionButton::SendExpeditions => {
-                commands.entity(focused_entity).insert(ExpeditionTargetMarker);
+                commands.queue(PlayerCommand::SetExpeditionTarget { coords, active: true });
             },
             QuantumFieldActionButton::StopExpeditions => {
-                commands.entity(focused_entity).remove::<ExpeditionTargetMarker>();
+                commands.queue(PlayerCommand::SetExpeditionTarget { coords, active: false });
             },
             QuantumFieldActionButton::PayCost => {
-                let Ok(mut quantum_field) = quantum_fields.get_mut(focused_entity) else { return; };
-                if stock.try_pay_costs(quantum_field.get_current_layer_costs()) {
-                    quantum_field.move_to_next_layer();
-                    commands.trigger(UiMapObjectFocusedTrigger { entity: focused_entity });
-                }
+                commands.queue(PlayerCommand::PayQuantumFieldLayer { coords });
             },
             QuantumFieldActionButton::Hidden => {},
         }
@@ -490,6 +489,38 @@ impl QuantumFieldActionButton {
 #[derive(Component)]
 struct QuantumFieldActionButtonText;
 
+fn on_quantum_field_player_command(
+    trigger: On<PlayerCommand>,
+    mut commands: Commands,
+    mut stock: ResMut<Stock>,
+    display_info_panel: Option<Single<&DisplayInfoPanel>>,
+    quantum_field_coords: Query<(Entity, &GridCoords), With<QuantumField>>,
+    mut quantum_fields: Query<&mut QuantumField>,
+) {
+    match *trigger.event() {
+        PlayerCommand::SetExpeditionTarget { coords, active } => {
+            let Some(entity) = entity_at_coords(&quantum_field_coords, coords) else { return; };
+            if active {
+                commands.entity(entity).insert(ExpeditionTargetMarker);
+            } else {
+                commands.entity(entity).remove::<ExpeditionTargetMarker>();
+            }
+        },
+        PlayerCommand::PayQuantumFieldLayer { coords } => {
+            let Some(entity) = entity_at_coords(&quantum_field_coords, coords) else { return; };
+            let Ok(mut quantum_field) = quantum_fields.get_mut(entity) else { return; };
+            if stock.try_pay_costs(quantum_field.get_current_layer_costs()) {
+                quantum_field.move_to_next_layer();
+                // Refresh the panel if it shows this field
+                if display_info_panel.is_some_and(|panel| panel.current_focus == entity) {
+                    commands.trigger(UiMapObjectFocusedTrigger { entity });
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
 
 fn update_quantum_field_info_panel_system(
     quantum_fields: Query<(&QuantumField, Has<ExpeditionTargetMarker>)>,
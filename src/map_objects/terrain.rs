@@ -0,0 +1,178 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use lib_grid::grids::terrain::TerrainGrid;
+
+use crate::prelude::*;
+use crate::ui::grid_object_placer::GridObjectPlacer;
+
+pub struct TerrainPlugin;
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(MapLoadingStage::LoadResources), TerrainLayer::create)
+            .add_systems(Update, (
+                onclick_paint_system.run_if(in_state(UiInteraction::PlaceGridObject)),
+                TerrainLayer::update_system.run_if(resource_changed::<TerrainGrid>),
+            ))
+            .register_db_loader::<TerrainCells>(MapLoadingStage::LoadResources)
+            .register_db_saver(TerrainCells::on_game_save);
+    }
+}
+
+pub fn terrain_color(terrain_type: TerrainType) -> Color {
+    match terrain_type {
+        TerrainType::Plain => Color::NONE,
+        TerrainType::Road => Color::srgba(0.55, 0.5, 0.4, 0.5),
+        TerrainType::Mud => Color::srgba(0.35, 0.22, 0.1, 0.6),
+        TerrainType::Swamp => Color::srgba(0.2, 0.35, 0.15, 0.6),
+        TerrainType::Chasm => Color::srgba(0.02, 0.02, 0.05, 0.85),
+    }
+}
+
+/// Single sprite covering the map, one pixel per field.
+#[derive(Component)]
+pub struct TerrainLayer;
+impl TerrainLayer {
+    fn create(
+        mut commands: Commands,
+        map_info: Res<MapInfo>,
+        mut images: ResMut<Assets<Image>>,
+        layer: Query<Entity, With<TerrainLayer>>,
+    ) {
+        // First remove old layer if exists
+        if let Ok(layer_entity) = layer.single() {
+            commands.entity(layer_entity).despawn();
+        };
+
+        let mut image = Image::new_fill(
+            Extent3d{
+                width: map_info.grid_width as u32,
+                height: map_info.grid_height as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default()
+        );
+        image.sampler = ImageSampler::nearest();
+
+        commands.spawn((
+            Sprite {
+                image: images.add(image),
+                custom_size: Some(Vec2::new(map_info.world_width, map_info.world_height)),
+                ..default()
+            },
+            Transform::from_xyz(map_info.world_width / 2., map_info.world_height / 2., Z_TERRAIN),
+            TerrainLayer,
+        ));
+    }
+
+    fn update_system(
+        mut images: ResMut<Assets<Image>>,
+        terrain_grid: Res<TerrainGrid>,
+        layer: Single<&Sprite, With<TerrainLayer>>,
+        mut last_imprinted: Local<Option<GridVersion>>,
+    ) {
+        if *last_imprinted == Some(terrain_grid.version) { return; }
+        let Some(image) = images.get_mut(&layer.into_inner().image) else { return; };
+        let Some(data) = image.data.as_mut() else { return; };
+        if data.len() != terrain_grid.grid.len() * 4 { return; }
+        *last_imprinted = Some(terrain_grid.version);
+        // Image rows go top to bottom, grid rows bottom to top
+        for y in 0..terrain_grid.height {
+            for x in 0..terrain_grid.width {
+                let pixel = ((terrain_grid.height - 1 - y) * terrain_grid.width + x) as usize * 4;
+                data[pixel..pixel + 4].copy_from_slice(&terrain_color(terrain_grid[GridCoords { x, y }]).to_srgba().to_u8_array());
+            }
+        }
+    }
+}
+
+fn onclick_paint_system(
+    mut terrain_grid: ResMut<TerrainGrid>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mouse_info: Res<MouseInfo>,
+    grid_object_placer: Single<&GridObjectPlacer>,
+) {
+    let GridObjectPlacer::Terrain(terrain_type) = *grid_object_placer.into_inner() else { return; };
+    let mouse_coords = mouse_info.grid_coords;
+    if mouse_info.is_over_ui || !mouse_coords.is_in_bounds(terrain_grid.bounds()) { return; }
+    if mouse.pressed(MouseButton::Left) {
+        terrain_grid.set_terrain(mouse_coords, terrain_type);
+    } else if mouse.pressed(MouseButton::Right) {
+        terrain_grid.set_terrain(mouse_coords, TerrainType::Plain);
+    }
+}
+
+/// Every field of the map that isn't plain ground.
+#[derive(Default, SSS)]
+pub struct TerrainCells {
+    pub cells: Vec<(GridCoords, TerrainType)>,
+}
+impl TerrainCells {
+    fn on_game_save(
+        mut commands: Commands,
+        terrain_grid: Res<TerrainGrid>,
+    ) {
+        let cells = terrain_grid.iter_non_plain().collect::<Vec<_>>();
+        println!("Saving terrain. {} non-plain fields", cells.len());
+        commands.queue(SaveableBatchCommand::from_single(TerrainCells { cells }));
+    }
+
+    /// Paints the loaded fields into the grid, returns the ones outside of the map.
+    fn apply(self, terrain_grid: &mut TerrainGrid) -> Vec<(Option<i64>, String)> {
+        let mut issues = Vec::new();
+        for (coords, terrain_type) in self.cells {
+            if !coords.is_in_bounds(terrain_grid.bounds()) {
+                issues.push((None, format!("Terrain at ({}, {}) is outside of the map", coords.x, coords.y)));
+                continue;
+            }
+            terrain_grid.set_terrain(coords, terrain_type);
+        }
+        issues
+    }
+}
+impl Saveable for TerrainCells {
+    fn save(self, tx: &rusqlite::Transaction) -> rusqlite::Result<()> {
+        for (coords, terrain_type) in self.cells {
+            tx.execute(
+                "INSERT OR REPLACE INTO terrain (x, y, terrain_type) VALUES (?1, ?2, ?3)",
+                (coords.x, coords.y, terrain_type.as_db_str()),
+            )?;
+        }
+        Ok(())
+    }
+}
+impl Loadable for TerrainCells {
+    /// Maps saved without terrain are plain everywhere.
+    fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
+        let mut terrain_cells = TerrainCells::default();
+        let mut stmt = ctx.conn.prepare("SELECT x, y, terrain_type FROM terrain")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let coords = GridCoords { x: row.get(0)?, y: row.get(1)? };
+            let terrain_type: String = row.get(2)?;
+            let Some(terrain_type) = TerrainType::from_db_str(&terrain_type) else {
+                ctx.report_issue(None, format!("Unknown terrain type '{}' at ({}, {})", terrain_type, coords.x, coords.y));
+                continue;
+            };
+            terrain_cells.cells.push((coords, terrain_type));
+        }
+
+        // The grid is a resource sized from the map info, so the fields are painted once the commands run
+        let (loader, stage) = (ctx.loader_name, ctx.stage.clone());
+        ctx.commands.queue(move |world: &mut World| {
+            let issues = terrain_cells.apply(&mut world.resource_mut::<TerrainGrid>());
+            let mut load_report = world.resource_mut::<LoadReport>();
+            for (row_id, error) in issues {
+                load_report.push(LoadIssue { loader, stage: stage.clone(), row_id, error });
+            }
+        });
+        Ok(LoadResult::Finished)
+    }
+}
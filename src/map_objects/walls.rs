@@ -67,21 +67,24 @@ impl Loadable for BuilderWall {
         let mut rows = stmt.query(ctx.pagination.as_params())?;
 
         let mut batch = Vec::new();
+        // Skipped rows still count towards pagination
+        let mut count = 0;
         while let Some(row) = rows.next()? {
             let old_id: i64 = row.get(0)?;
-            let grid_position = ctx.conn.get_grid_coords(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                batch.push((new_entity, BuilderWall::new_for_saving(grid_position, new_entity)));
-            } else {
-                eprintln!("Warning: Wall with old ID {} has no corresponding new entity", old_id);
-            }
+            count += 1;
+            let Some(grid_position) = ctx.expect_row(old_id, "grid_coords", ctx.conn.get_grid_coords(old_id))? else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+            batch.push((new_entity, BuilderWall::new_for_saving(grid_position, new_entity)));
         }
         
-        let batch_size = batch.len();
         ctx.commands.insert_batch(batch);
         
-        Ok(batch_size.into())
+        Ok(count.into())
+    }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("walls").map(Some)
     }
 }
 impl BuilderWall {
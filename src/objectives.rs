@@ -222,8 +222,12 @@ impl Loadable for BuilderObjective {
             let objective_type_str: String = row.get(2)?;
             let activation_event: String = row.get(3)?;
             let state_str: String = row.get(4)?;
+            count += 1;
 
-            let state = ObjectiveState::from_str(state_str.as_str()).unwrap();
+            let Ok(state) = ObjectiveState::from_str(state_str.as_str()) else {
+                ctx.report_issue(Some(old_id), format!("Unknown objective state '{}'", state_str));
+                continue;
+            };
 
             // Load type-specific data
             let (objective_type, kill_wisps_data) = match objective_type_str.as_str() {
@@ -238,31 +242,32 @@ impl Loadable for BuilderObjective {
                         let started_amount: i64 = kw_row.get(1)?;
                         (ObjectiveType::KillWisps(target_amount as usize), Some((target_amount as usize, started_amount as usize)))
                     } else {
+                        ctx.report_issue(Some(old_id), "Objective has no row in 'objective_kill_wisps'");
                         (ObjectiveType::KillWisps(0), None)
                     }
                 }
                 _ => {
-                    eprintln!("Unknown objective type: {}", objective_type_str);
+                    ctx.report_issue(Some(old_id), format!("Unknown objective type '{}'", objective_type_str));
                     continue;
                 }
             };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
 
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let objective_details = ObjectiveDetails::new(id_name, objective_type, activation_event);
-                let save_data = ObjectiveSaveData {
-                    entity: new_entity,
-                    state,
-                    kill_wisps_data,
-                };
-                ctx.commands.entity(new_entity).insert(BuilderObjective::new_for_saving(objective_details, save_data));
-            } else {
-                eprintln!("Warning: Objective with old ID {} has no corresponding new entity", old_id);
-            }
-            count += 1;
+            let objective_details = ObjectiveDetails::new(id_name, objective_type, activation_event);
+            let save_data = ObjectiveSaveData {
+                entity: new_entity,
+                state,
+                kill_wisps_data,
+            };
+            ctx.commands.entity(new_entity).insert(BuilderObjective::new_for_saving(objective_details, save_data));
         }
 
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("objectives").map(Some)
+    }
 }
 
 #[derive(Component)]
@@ -16,11 +16,11 @@ impl Plugin for EmissionsPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_plugins(Material2dPlugin::<EmissionHeatmapMaterial>::default())
-            .insert_resource(EmissionsOverlayMode::Energy) 
+            .insert_resource(EmissionsOverlayMode::Channel(EmissionsType::Energy))
             .add_systems(OnEnter(MapLoadingStage::LoadResources), EmissionsOverlay::create)
             .add_systems(Update, (
                 update_emissions_overlay_system.run_if(resource_changed::<EmissionsOverlayMode>.or(resource_changed::<EmissionsGrid>)),
-                manage_emissions_overlay_mode_system.run_if(input_just_released(KeyCode::Digit6)), // Switch overlay on/off 
+                manage_emissions_overlay_mode_system.run_if(input_just_released(KeyCode::Digit6)), // Cycle through the channels, then off
             ));
     }
 }
@@ -84,21 +84,27 @@ impl EmissionsOverlay {
     }
 }
 
-/// Keep tracks of which version does the overlay use
+/// Keep tracks of which channel does the overlay show
 #[derive(Resource)]
 pub enum EmissionsOverlayMode {
     None,
-    Energy,
+    Channel(EmissionsType),
 }
 
 pub fn manage_emissions_overlay_mode_system(
     mut emissions_overlay_mode: ResMut<EmissionsOverlayMode>,
 ) {
-    if matches!(*emissions_overlay_mode, EmissionsOverlayMode::None) {
-        *emissions_overlay_mode = EmissionsOverlayMode::Energy;
-    } else {
-        *emissions_overlay_mode = EmissionsOverlayMode::None;
-    }
+    let next_channel = match *emissions_overlay_mode {
+        EmissionsOverlayMode::None => EmissionsType::ALL.first(),
+        EmissionsOverlayMode::Channel(current) => EmissionsType::ALL.iter().skip_while(|channel| **channel != current).nth(1),
+    };
+    *emissions_overlay_mode = match next_channel {
+        Some(channel) => {
+            println!("Emissions overlay: {}", channel.name());
+            EmissionsOverlayMode::Channel(*channel)
+        },
+        None => EmissionsOverlayMode::None,
+    };
 }
 
 pub fn update_emissions_overlay_system(
@@ -107,20 +113,21 @@ pub fn update_emissions_overlay_system(
     emissions_grid: Res<EmissionsGrid>,
     mut emissions_overlay_mode: ResMut<EmissionsOverlayMode>,
     emissions_overlay: Single<(&mut Visibility, &MeshMaterial2d<EmissionHeatmapMaterial>), With<EmissionsOverlay>>,
-    mut last_grid_version: Local<GridVersion>,
+    mut last_imprinted: Local<Option<(EmissionsType, GridVersion)>>,
 ) {
     let (mut visibility, heatmap_material_handle) = emissions_overlay.into_inner();
     match &mut *emissions_overlay_mode {
         EmissionsOverlayMode::None => { 
             *visibility = Visibility::Hidden;
         },
-        EmissionsOverlayMode::Energy => {
+        EmissionsOverlayMode::Channel(channel) => {
             *visibility = Visibility::Inherited;
-            if *last_grid_version != emissions_grid.version.energy {
-                *last_grid_version = emissions_grid.version.energy;
+            let current = (*channel, emissions_grid.version.get(*channel));
+            if *last_imprinted != Some(current) {
+                *last_imprinted = Some(current);
                 let heatmap_material = materials.get_mut(heatmap_material_handle).unwrap();
                 let heatmap_image = images.get_mut(&heatmap_material.heatmap).unwrap();
-                emissions_grid.imprint_into_heatmap(&mut heatmap_image.data.as_mut().unwrap(), EmissionsType::Energy);
+                emissions_grid.imprint_into_heatmap(&mut heatmap_image.data.as_mut().unwrap(), *channel);
             }
         }
     }
@@ -8,10 +8,7 @@ use bevy::{
     shader:: ShaderRef,
     sprite_render::{AlphaMode2d, Material2d, Material2dPlugin, MeshMaterial2d}
 };
-use lib_grid::{
-    grids::tower_ranges::TowerRangesGrid,
-    search::common::{CARDINAL_DIRECTIONS, VISITED_GRID},
-};
+use lib_grid::grids::tower_ranges::TowerRangesGrid;
 
 use crate::prelude::*;
 use crate::ui::{
@@ -91,7 +88,7 @@ impl TowersRangeOverlayConfig {
 /// Secondary mode is temporary override 
 /// - `None`: Mode not active
 /// - `Highlight { tower }`: emphasize the selected tower's range
-/// - `PlacingTower { .. }`: preview of the range flood from the planned building footprint
+/// - `PlacingTower { .. }`: preview of the range around the planned building footprint
 pub enum TowersRangeOverlaySecondaryMode {
     #[default]
     None,
@@ -99,7 +96,8 @@ pub enum TowersRangeOverlaySecondaryMode {
     PlacingTower {
         grid_coords: GridCoords,
         grid_imprint: GridImprint,
-        range: usize,
+        range_shape: TowerRangeShape,
+        range: f32,
     },
 }
 impl TowersRangeOverlaySecondaryMode {
@@ -179,16 +177,12 @@ fn refresh_display_system(
         TowersRangeOverlaySecondaryMode::PlacingTower {
             grid_coords,
             grid_imprint,
+            range_shape,
             range,
         } => {
             if grid_coords.is_in_bounds(tower_ranges_grid.bounds()) {
-                let covered_coords = grid_imprint
-                    .covered_coords(*grid_coords)
-                    .iter()
-                    .copied()
-                    .filter(|coords| coords.is_in_bounds(tower_ranges_grid.bounds()))
-                    .collect::<Vec<_>>();
-                overlay_creator.flood_preview_to_overlay(covered_coords, *range)
+                let covered_coords = range_shape.covered_coords(*grid_coords, *grid_imprint, *range, tower_ranges_grid.bounds());
+                overlay_creator.highlight_preview_to_overlay(covered_coords)
             } else {
                 overlay_creator.generate_buffer_data(&HighlightMode::None)
             }
@@ -227,7 +221,8 @@ fn on_grid_placer_changed_system(
                     overlay_config.secondary_mode = TowersRangeOverlaySecondaryMode::PlacingTower {
                         grid_coords: *grid_coords,
                         grid_imprint: building_info.grid_imprint,
-                        range: building_info.baseline[&ModifierType::AttackRange] as usize,
+                        range_shape: building_info.range_shape.unwrap_or_default(),
+                        range: building_info.baseline[&ModifierType::AttackRange],
                     };
                 }
                 _ => {
@@ -301,57 +296,19 @@ impl<'a> OverlayBufferCreator<'a> {
         TowerRangeCell { signature, cover_count, highlight }
     }
 
-    /// Distance-limited BFS flood from all provided start cells (building footprint).
-    /// Accepts owned `GridCoords` so callers can pass a `Vec<GridCoords>` directly.
+    /// Highlights the provided cells, the range of a planned tower.
     /// Only sets the `highlight` bit; leaves `signature` intact.
-    fn flood_preview_to_overlay(
+    fn highlight_preview_to_overlay(
         &mut self,
-        start_coords: impl IntoIterator<Item = GridCoords>,
-        range: usize,
+        covered_coords: impl IntoIterator<Item = GridCoords>,
     ) {
-        use std::collections::VecDeque;
-
         // Start with base buffer data (all signatures + optional selection)
         self.generate_buffer_data(&HighlightMode::None);
         let buffer_data = self.local_buffer_data.take().unwrap();
-        let bounds = self.grid.bounds();
-
-        VISITED_GRID.with_borrow_mut(|visited_grid| {
-            visited_grid.resize_and_reset(bounds);
-            let mut queue = VecDeque::new();
-
-            // Start flood from all cells to ensure even distance from buildings bigger than one cell
-            start_coords.into_iter().for_each(|coords| {
-                let index = self.grid.index(coords);
-                // Do not alter signature; set highlight flag
-                let mut cell = buffer_data[index];
-                cell.highlight = 1;
-                buffer_data[index] = cell;
-                queue.push_back((0, coords));
-                visited_grid.set_visited(coords);
-            });
-
-            while let Some((distance, coords)) = queue.pop_front() {
-                for (dx, dy) in CARDINAL_DIRECTIONS {
-                    let new_coords = coords.shifted((dx, dy));
-                    if !new_coords.is_in_bounds(bounds) || visited_grid.is_visited(new_coords) {
-                        continue;
-                    }
-                    visited_grid.set_visited(new_coords);
-
-                    let buffer_index = self.grid.index(new_coords);
-                    let mut cell = buffer_data[buffer_index];
-                    cell.highlight = 1;
-                    buffer_data[buffer_index] = cell;
-
-                    let new_distance = distance + 1;
-                    if new_distance < range {
-                        queue.push_back((new_distance, new_coords));
-                    }
-                }
-            }
-        });
-
+        for coords in covered_coords {
+            let index = self.grid.index(coords);
+            buffer_data[index].highlight = 1;
+        }
         self.local_buffer_data = Some(buffer_data);
     }
 }
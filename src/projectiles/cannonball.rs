@@ -5,7 +5,7 @@ use lib_grid::grids::wisps::WispsGrid;
 
 use crate::prelude::*;
 use crate::effects::explosions::BuilderExplosion;
-use crate::projectiles::components::Projectile;
+use crate::projectiles::components::{Projectile, BLAST_RADIUS};
 use crate::wisps::components::Wisp;
 
 pub struct CannonballPlugin;
@@ -76,9 +76,10 @@ impl Loadable for BuilderCannonball {
             let target_y: f32 = row.get(2)?;
             let damage_val: f32 = row.get(3)?;
             let initial_distance: f32 = row.get(4)?;
-            let world_position = ctx.conn.get_world_position(old_id)?;
+            count += 1;
+            let Some(world_position) = ctx.expect_row(old_id, "world_positions", ctx.conn.get_world_position(old_id))? else { continue; };
             
-            let Some(new_entity) = ctx.get_new_entity_for_old(old_id) else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
             let save_data = CannonballSaveData { entity: new_entity, initial_distance };
             ctx.commands.entity(new_entity).insert(BuilderCannonball::new_for_saving(
                 world_position,
@@ -86,10 +87,13 @@ impl Loadable for BuilderCannonball {
                 AttackDamage(damage_val),
                 save_data
             ));
-            count += 1;
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("cannonballs").map(Some)
+    }
 }
 
 impl BuilderCannonball {
@@ -184,7 +188,8 @@ pub fn cannonball_hit_system(
     mut commands: Commands,
     cannonballs: Query<(Entity, &Transform, &CannonballTarget, &AttackDamage), With<Cannonball>>,
     wisps_grid: Res<WispsGrid>,
-    mut wisps: Query<&mut Health, With<Wisp>>,
+    wisps_transforms: Query<&Transform, (With<Wisp>, Without<Cannonball>)>,
+    mut wisps_health: Query<&mut Health, With<Wisp>>,
 ) {
     for (entity, cannonball_transform, target, attack_damage) in cannonballs.iter() {
         if cannonball_transform.translation.xy().distance(target.target_position) > 4. { continue; } // TODO: 1. and 2. are causing cannonballs jitters at landing. Investigate.
@@ -195,12 +200,10 @@ pub fn cannonball_hit_system(
             if !blast_zone_coords.is_in_bounds(wisps_grid.bounds()) { continue; }
 
             commands.spawn(BuilderExplosion(blast_zone_coords));
-
-            let wisps_in_coords = &wisps_grid[blast_zone_coords];
-            for wisp in wisps_in_coords {
-                let Ok(mut health) = wisps.get_mut(*wisp) else { continue }; // May not find wisp if the wisp spawned at the same frame.
-                health.decrease(attack_damage.0);
-            }
+        }
+        for hit in wisps_grid.wisps_in_radius(&wisps_transforms, cannonball_transform.translation.xy(), BLAST_RADIUS) {
+            let Ok(mut health) = wisps_health.get_mut(hit.entity) else { continue }; // May not find wisp if the wisp spawned at the same frame.
+            health.decrease(attack_damage.0);
         }
         commands.entity(entity).despawn();
     }
@@ -3,3 +3,6 @@ use crate::prelude::*;
 #[derive(Component, Default)]
 #[require(MapBound)]
 pub struct Projectile;
+
+/// Radius around the impact point in which explosive projectiles damage wisps, reaching into the neighbouring fields.
+pub const BLAST_RADIUS: f32 = 1.5 * CELL_SIZE;
@@ -71,9 +71,10 @@ impl Loadable for BuilderLaserDart {
             let vector_x: f32 = row.get(2)?;
             let vector_y: f32 = row.get(3)?;
             let damage_val: f32 = row.get(4)?;
-            let world_position = ctx.conn.get_world_position(old_id)?;
+            count += 1;
+            let Some(world_position) = ctx.expect_row(old_id, "world_positions", ctx.conn.get_world_position(old_id))? else { continue; };
             
-            let Some(new_entity) = ctx.get_new_entity_for_old(old_id) else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
             let new_target_wisp = target_wisp_old_id.and_then(|id| ctx.get_new_entity_for_old(id));
             
             let save_data = LaserDartSaveData { entity: new_entity };
@@ -84,10 +85,13 @@ impl Loadable for BuilderLaserDart {
                 AttackDamage(damage_val),
                 save_data
             ));
-            count += 1;
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("laser_darts").map(Some)
+    }
 }
 
 impl BuilderLaserDart {
@@ -164,24 +168,26 @@ pub fn laser_dart_move_system(
 
 pub fn laser_dart_hit_system(
     mut commands: Commands,
-    laser_darts: Query<(Entity, &Transform, &AttackDamage), With<LaserDart>>,
+    laser_darts: Query<(Entity, &Transform, &LaserDartTarget, &AttackDamage), With<LaserDart>>,
     wisps_grid: Res<WispsGrid>,
-    mut wisps: Query<(&mut Health, &Transform), With<Wisp>>,
+    wisps_transforms: Query<&Transform, (With<Wisp>, Without<LaserDart>)>,
+    mut wisps_health: Query<&mut Health, With<Wisp>>,
+    time: Res<Time>,
 ) {
-    for (entity, laser_dart_transform, damage) in laser_darts.iter() {
+    for (entity, laser_dart_transform, target, damage) in laser_darts.iter() {
         let coords = GridCoords::from_transform(&laser_dart_transform);
         if !coords.is_in_bounds(wisps_grid.bounds()) {
             commands.entity(entity).despawn();
             continue;
         }
-        let wisps_in_coords = &wisps_grid[coords];
-        for wisp in wisps_in_coords {
-            let Ok((mut health, wisp_transform)) = wisps.get_mut(*wisp) else { continue }; // May not find wisp if the wisp spawned at the same frame.
-            if laser_dart_transform.translation.xy().distance(wisp_transform.translation.xy()) < 8. {
-                health.decrease(damage.0);
-                commands.entity(entity).despawn();
-                break;
-            }
+        // Check the whole distance travelled this frame, so fast darts don't pass through wisps
+        let position = laser_dart_transform.translation.xy();
+        let previous_position = position - target.target_vector * time.delta_secs() * 600.;
+        for hit in wisps_grid.wisps_along_segment(&wisps_transforms, previous_position, position, 8.) {
+            let Ok(mut health) = wisps_health.get_mut(hit.entity) else { continue }; // May not find wisp if the wisp spawned at the same frame.
+            health.decrease(damage.0);
+            commands.entity(entity).despawn();
+            break;
         }
     }
 }
\ No newline at end of file
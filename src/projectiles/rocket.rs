@@ -4,7 +4,7 @@ use lib_grid::search::common::ALL_DIRECTIONS;
 use lib_grid::grids::wisps::WispsGrid;
 
 use crate::prelude::*;
-use crate::projectiles::components::Projectile;
+use crate::projectiles::components::{Projectile, BLAST_RADIUS};
 use crate::wisps::components::Wisp;
 use crate::effects::explosions::BuilderExplosion;
 
@@ -83,26 +83,29 @@ impl Loadable for BuilderRocket {
             let target_wisp_old_id: Option<i64> = row.get(1)?;
             let rotation_z: f32 = row.get(2)?;
             let damage_val: f32 = row.get(3)?;
-            let world_position = ctx.conn.get_world_position(old_id)?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let new_target_wisp = target_wisp_old_id
-                    .and_then(|id| ctx.get_new_entity_for_old(id))
-                    .unwrap_or(Entity::PLACEHOLDER);
-                
-                let save_data = RocketSaveData { entity: new_entity };
-                ctx.commands.entity(new_entity).insert(BuilderRocket::new_for_saving(
-                    world_position,
-                    Quat::from_rotation_z(rotation_z),
-                    new_target_wisp,
-                    AttackDamage(damage_val),
-                    save_data
-                ));
-            }
             count += 1;
+            let Some(world_position) = ctx.expect_row(old_id, "world_positions", ctx.conn.get_world_position(old_id))? else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+            let new_target_wisp = target_wisp_old_id
+                .and_then(|id| ctx.get_new_entity_for_old(id))
+                .unwrap_or(Entity::PLACEHOLDER);
+            
+            let save_data = RocketSaveData { entity: new_entity };
+            ctx.commands.entity(new_entity).insert(BuilderRocket::new_for_saving(
+                world_position,
+                Quat::from_rotation_z(rotation_z),
+                new_target_wisp,
+                AttackDamage(damage_val),
+                save_data
+            ));
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("rockets").map(Some)
+    }
 }
 
 impl BuilderRocket {
@@ -240,12 +243,10 @@ pub fn rocket_hit_system(
             if !blast_zone_coords.is_in_bounds(wisps_grid.bounds()) { continue; }
 
             commands.spawn(BuilderExplosion(blast_zone_coords));
-
-            let wisps_in_coords = &wisps_grid[blast_zone_coords];
-            for wisp in wisps_in_coords {
-                let Ok(mut health) = wisps_health.get_mut(*wisp) else { continue }; // May not find wisp if the wisp spawned at the same frame.
-                health.decrease(attack_damage.0);
-            }
+        }
+        for hit in wisps_grid.wisps_in_radius(&wisps_transforms, rocket_transform.translation.xy(), BLAST_RADIUS) {
+            let Ok(mut health) = wisps_health.get_mut(hit.entity) else { continue }; // May not find wisp if the wisp spawned at the same frame.
+            health.decrease(attack_damage.0);
         }
         commands.entity(entity).despawn();
     }
@@ -0,0 +1,350 @@
+//! Records player commands with the simulation tick they were issued at and plays them back.
+//!
+//! Recording starts whenever a map finishes loading: the loaded file, including the `GameRng` state, is copied next to
+//! itself, see `replay_base_path()`. The copy keeps the starting point even if the player later saves over the map.
+//! Saving a replay copies that base and adds the recorded ticks and commands, so a replay is a regular save of the
+//! starting point. Playback loads it, feeds the recorded frame deltas to `Time` and re-issues
+//! the commands at their ticks. Player input is ignored until the replay ends.
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::ecs::query::QueryFilter;
+use bevy::input::common_conditions::input_just_released;
+use bevy::time::{TimeSystems, TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use lib_ui::prelude::{LevelUpUpgradeRequestedEvent, UpgradeButtonsLocked};
+
+use crate::prelude::*;
+
+pub struct ReplayPlugin;
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SimulationTick>()
+            .init_resource::<PendingPlayerCommands>()
+            .init_resource::<ReplayRecorder>()
+            .add_systems(OnEnter(MapLoadingStage::Ready), ReplayRecorder::start)
+            .add_systems(First, (
+                ReplayPlayback::drive_time.before(TimeSystems),
+                SimulationTick::advance.after(TimeSystems).run_if(in_state(GameState::Running)),
+            ))
+            .add_systems(PreUpdate, issue_player_commands)
+            .add_systems(Update, (
+                ReplayPlayback::lock_upgrade_buttons,
+                SaveReplaySignal::emit.run_if(input_just_released(KeyCode::F5)),
+                PlayReplaySignal::emit.run_if(input_just_released(KeyCode::F6)),
+            ))
+            .add_observer(SaveReplaySignal::on_trigger)
+            .add_observer(PlayReplaySignal::on_trigger)
+            .add_observer(ReplayPlayback::on_load_game)
+            .add_observer(on_level_up_upgrade_requested)
+            .add_observer(on_level_up_upgrade_command);
+    }
+}
+
+pub const LAST_REPLAY_PATH: &str = "saves/replays/last_replay.dwd";
+
+/// Player action that changes the simulation. Queue it with `commands.queue(...)` instead of acting directly:
+/// it's issued in `PreUpdate`, where it's recorded, and the module owning the action observes it.
+/// Objects are referenced by their grid coords, as entities differ between runs.
+#[derive(Event, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlayerCommand {
+    PlaceBuilding { building_type: BuildingType, coords: GridCoords },
+    /// Queued from the `LevelUpUpgradeRequestedEvent` of lib-ui upgrade lines.
+    LevelUpUpgrade { coords: GridCoords, upgrade_type: UpgradeType },
+    SetDisabledByPlayer { coords: GridCoords, disabled: bool },
+    SetTargetingPriority { coords: GridCoords, priority: TargetingPriority },
+    SetEnergyPriority { coords: GridCoords, priority: EnergyPriority },
+    DestroyBuilding { coords: GridCoords },
+    SetExpeditionTarget { coords: GridCoords, active: bool },
+    PayQuantumFieldLayer { coords: GridCoords },
+}
+impl Command for PlayerCommand {
+    fn apply(self, world: &mut World) {
+        world.resource_mut::<PendingPlayerCommands>().0.push(self);
+    }
+}
+
+/// Finds the object whose origin is at `coords`.
+pub fn entity_at_coords<F: QueryFilter>(objects: &Query<(Entity, &GridCoords), F>, coords: GridCoords) -> Option<Entity> {
+    objects.iter().find(|(_, object_coords)| **object_coords == coords).map(|(entity, _)| entity)
+}
+
+#[derive(Resource, Default)]
+struct PendingPlayerCommands(Vec<PlayerCommand>);
+
+/// Number of frames simulated in `GameState::Running` since the map finished loading.
+#[derive(Resource, Default)]
+pub struct SimulationTick(pub u64);
+impl SimulationTick {
+    fn advance(
+        mut tick: ResMut<SimulationTick>,
+        mut recorder: ResMut<ReplayRecorder>,
+        time: Res<Time>,
+    ) {
+        tick.0 += 1;
+        if recorder.base_path.is_some() {
+            recorder.deltas.push(time.delta());
+        }
+    }
+
+    /// Tick a command issued now belongs to. Commands issued while paused take effect before the next tick.
+    fn for_command(&self, game_state: &GameState) -> u64 {
+        match game_state {
+            GameState::Running => self.0,
+            _ => self.0 + 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RecordedCommand {
+    tick: u64,
+    command: PlayerCommand,
+}
+
+fn issue_player_commands(
+    mut commands: Commands,
+    mut pending: ResMut<PendingPlayerCommands>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<ResMut<ReplayPlayback>>,
+    tick: Res<SimulationTick>,
+    game_state: Res<State<GameState>>,
+) {
+    if let Some(mut playback) = playback {
+        if !pending.0.is_empty() {
+            println!("Ignoring {} player command(s) during replay", pending.0.len());
+            pending.0.clear();
+        }
+        if *game_state.get() != GameState::Running { return; }
+        while playback.commands.front().is_some_and(|recorded| recorded.tick <= tick.0) {
+            let Some(recorded) = playback.commands.pop_front() else { break; };
+            commands.trigger(recorded.command);
+        }
+        return;
+    }
+
+    let command_tick = tick.for_command(game_state.get());
+    for command in pending.0.drain(..) {
+        recorder.record(command_tick, command);
+        commands.trigger(command);
+    }
+}
+
+/// Where the starting point of a replay recorded on the map at `path` is kept.
+pub fn replay_base_path(path: &str) -> String {
+    format!("{path}.replay_base")
+}
+
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    /// Copy of the loaded map the recording starts from, `None` while not recording.
+    base_path: Option<String>,
+    /// Delta of every tick, starting with tick 1.
+    deltas: Vec<Duration>,
+    commands: Vec<RecordedCommand>,
+}
+impl ReplayRecorder {
+    fn record(&mut self, tick: u64, command: PlayerCommand) {
+        if self.base_path.is_none() { return; }
+        self.commands.push(RecordedCommand { tick, command });
+    }
+
+    /// The world was just loaded from `save_name`, so copying the file is enough to keep the starting point.
+    fn start(
+        mut recorder: ResMut<ReplayRecorder>,
+        mut tick: ResMut<SimulationTick>,
+        save_executor: Res<GameSaveExecutor>,
+        playback: Option<Res<ReplayPlayback>>,
+    ) {
+        tick.0 = 0;
+        *recorder = ReplayRecorder::default();
+        if playback.is_some() { return; }
+        let base_path = replay_base_path(&save_executor.save_name);
+        match std::fs::copy(&save_executor.save_name, &base_path) {
+            Ok(_) => recorder.base_path = Some(base_path),
+            Err(e) => eprintln!("Failed to copy '{}' as replay base, not recording: {}", save_executor.save_name, e),
+        }
+    }
+
+    /// Copies the replay base to `path` and adds the recorded ticks and commands to it.
+    fn write(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(base_path) = &self.base_path else { return Err("Nothing recorded".into()); };
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::copy(base_path, path)?;
+        // Drop cached connections that may still point to the overwritten file
+        increment_db_generation();
+
+        let mut conn = rusqlite::Connection::open(path)?;
+        let tx = conn.transaction()?;
+        for (index, delta) in self.deltas.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO replay_ticks (tick, delta_nanos) VALUES (?1, ?2)",
+                (index as i64 + 1, delta.as_nanos() as i64),
+            )?;
+        }
+        for recorded in &self.commands {
+            tx.execute(
+                "INSERT INTO replay_commands (tick, command_json) VALUES (?1, ?2)",
+                (recorded.tick as i64, serde_json::to_string(&recorded.command)?),
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub path: String,
+    /// Delta of every tick, starting with tick 1.
+    deltas: Vec<Duration>,
+    commands: VecDeque<RecordedCommand>,
+}
+impl ReplayPlayback {
+    fn read(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+        let mut stmt = conn.prepare("SELECT delta_nanos FROM replay_ticks ORDER BY tick")?;
+        let deltas = stmt
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .map(|nanos| nanos.map(|nanos| Duration::from_nanos(nanos as u64)))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare("SELECT tick, command_json FROM replay_commands ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        let mut commands = VecDeque::new();
+        while let Some(row) = rows.next()? {
+            let tick: i64 = row.get(0)?;
+            let command_json: String = row.get(1)?;
+            commands.push_back(RecordedCommand { tick: tick as u64, command: serde_json::from_str(&command_json)? });
+        }
+
+        Ok(Self { path: path.to_string(), deltas, commands })
+    }
+
+    /// Simulates every tick with its recorded delta. Ends the playback once all ticks are used.
+    fn drive_time(
+        mut commands: Commands,
+        mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+        playback: Option<Res<ReplayPlayback>>,
+        tick: Res<SimulationTick>,
+        game_state: Res<State<GameState>>,
+    ) {
+        let Some(playback) = playback else { return; };
+        if *game_state.get() != GameState::Running {
+            *time_update_strategy = TimeUpdateStrategy::Automatic;
+            return;
+        }
+        match playback.deltas.get(tick.0 as usize) {
+            Some(delta) => *time_update_strategy = TimeUpdateStrategy::ManualDuration(*delta),
+            None => {
+                println!("Replay '{}' finished after {} ticks", playback.path, tick.0);
+                *time_update_strategy = TimeUpdateStrategy::Automatic;
+                commands.remove_resource::<ReplayPlayback>();
+            }
+        }
+    }
+
+    /// Player commands are ignored during playback, the upgrade buttons show it.
+    fn lock_upgrade_buttons(
+        playback: Option<Res<ReplayPlayback>>,
+        mut locked: ResMut<UpgradeButtonsLocked>,
+    ) {
+        let is_playing = playback.is_some();
+        if locked.0 != is_playing {
+            locked.0 = is_playing;
+        }
+    }
+
+    /// Loading anything else stops the playback.
+    fn on_load_game(
+        trigger: On<LoadGameSignal>,
+        mut commands: Commands,
+        mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+        playback: Option<Res<ReplayPlayback>>,
+    ) {
+        let Some(playback) = playback else { return; };
+        if playback.path == trigger.event().0 { return; }
+        println!("Stopping replay '{}'", playback.path);
+        *time_update_strategy = TimeUpdateStrategy::Automatic;
+        commands.remove_resource::<ReplayPlayback>();
+    }
+}
+
+/// Writes what was recorded since the map loaded into a replay file.
+#[derive(Event)]
+pub struct SaveReplaySignal(pub String);
+impl SaveReplaySignal {
+    fn emit(mut commands: Commands) {
+        commands.trigger(SaveReplaySignal(LAST_REPLAY_PATH.to_string()));
+    }
+
+    fn on_trigger(
+        trigger: On<SaveReplaySignal>,
+        recorder: Res<ReplayRecorder>,
+    ) {
+        let path = &trigger.event().0;
+        if recorder.base_path.is_none() {
+            println!("Nothing recorded, replay not saved");
+            return;
+        }
+        match recorder.write(path) {
+            Ok(()) => println!("Replay saved to '{}' ({} ticks, {} commands)", path, recorder.deltas.len(), recorder.commands.len()),
+            Err(e) => eprintln!("Failed to save replay '{}': {}", path, e),
+        }
+    }
+}
+
+/// Loads a replay file and plays it back.
+#[derive(Event)]
+pub struct PlayReplaySignal(pub String);
+impl PlayReplaySignal {
+    fn emit(mut commands: Commands) {
+        commands.trigger(PlayReplaySignal(LAST_REPLAY_PATH.to_string()));
+    }
+
+    fn on_trigger(
+        trigger: On<PlayReplaySignal>,
+        mut commands: Commands,
+    ) {
+        let path = &trigger.event().0;
+        match ReplayPlayback::read(path) {
+            Ok(playback) => {
+                println!("Playing replay '{}' ({} ticks, {} commands)", path, playback.deltas.len(), playback.commands.len());
+                commands.insert_resource(playback);
+                commands.trigger(LoadGameSignal(path.clone()));
+            }
+            Err(e) => eprintln!("Failed to read replay '{}': {}", path, e),
+        }
+    }
+}
+
+fn on_level_up_upgrade_requested(
+    trigger: On<LevelUpUpgradeRequestedEvent>,
+    mut commands: Commands,
+    objects: Query<&GridCoords>,
+) {
+    let Ok(coords) = objects.get(trigger.event().entity) else { return; };
+    commands.queue(PlayerCommand::LevelUpUpgrade { coords: *coords, upgrade_type: trigger.event().upgrade_type });
+}
+
+fn on_level_up_upgrade_command(
+    trigger: On<PlayerCommand>,
+    mut commands: Commands,
+    mut stock: ResMut<Stock>,
+    objects: Query<(Entity, &GridCoords), With<Upgrades>>,
+    upgrades: Query<&Upgrades>,
+) {
+    let PlayerCommand::LevelUpUpgrade { coords, upgrade_type } = *trigger.event() else { return; };
+    let Some(entity) = entity_at_coords(&objects, coords) else { return; };
+    let Ok(upgrades) = upgrades.get(entity) else { return; };
+    let Some(upgrade_info) = upgrades.upgrades.get(&upgrade_type) else { return; };
+    let Some(level_info) = upgrade_info.static_info.levels.get(upgrade_info.current_level) else { return; };
+    if !stock.try_pay_costs(&level_info.cost) { return; }
+    commands.queue(LevelUpUpgradeMessage { entity, upgrade_type });
+}
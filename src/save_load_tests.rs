@@ -0,0 +1,106 @@
+use strum::IntoEnumIterator;
+
+use lib_core::persistence::{load_world_from_path, save_world_to_path};
+
+use crate::buildings;
+use crate::data_loader;
+use crate::map_objects;
+use crate::map_objects::dark_ore::DarkOre;
+use crate::map_objects::walls::Wall;
+use crate::prelude::*;
+
+const TEST_MAP_PATH: &str = "maps/test_map.dwd";
+
+/// App with only the plugins that register loaders/savers of the test map content. Nothing here needs a window.
+fn headless_app() -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((
+            MinimalPlugins,
+            bevy::state::app::StatesPlugin,
+            AssetPlugin::default(),
+        ))
+        .add_plugins((
+            lib_grid::grids::GridsPlugin,
+            lib_core::LibCorePlugin,
+            lib_inventory::LibInventoryPlugin,
+            data_loader::DataLoaderPlugin,
+        ))
+        .add_plugins((
+            buildings::main_base::MainBasePlugin,
+            buildings::energy_relay::EnergyRelayPlugin,
+            buildings::exploration_center::ExplorationCenterPlugin,
+            buildings::mining_complex::MiningComplexPlugin,
+            buildings::tower_blaster::TowerBlasterPlugin,
+            buildings::tower_cannon::TowerCannonPlugin,
+            buildings::tower_rocket_launcher::TowerRocketLauncherPlugin,
+            buildings::tower_emitter::TowerEmitterPlugin,
+            map_objects::walls::WallPlugin,
+            map_objects::dark_ore::DarkOrePlugin,
+        ));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Everything the round trip has to preserve, sorted by grid coords so the spawn order doesn't matter.
+#[derive(Debug, PartialEq)]
+struct WorldSummary {
+    map_size: (i32, i32),
+    buildings: Vec<(BuildingType, GridCoords, f32)>,
+    walls: Vec<GridCoords>,
+    dark_ores: Vec<(GridCoords, i32)>,
+    stock: Vec<(ResourceType, i32)>,
+}
+impl WorldSummary {
+    fn collect(world: &mut World) -> Self {
+        let map_info = world.resource::<MapInfo>();
+        let map_size = (map_info.grid_width, map_info.grid_height);
+
+        let mut buildings = world.query::<(&BuildingType, &GridCoords, &Health)>()
+            .iter(world)
+            .map(|(building_type, coords, health)| (*building_type, *coords, health.get_current()))
+            .collect::<Vec<_>>();
+        buildings.sort_by_key(|(_, coords, _)| (coords.y, coords.x));
+
+        let mut walls = world.query_filtered::<&GridCoords, With<Wall>>()
+            .iter(world)
+            .copied()
+            .collect::<Vec<_>>();
+        walls.sort_by_key(|coords| (coords.y, coords.x));
+
+        let mut dark_ores = world.query::<(&GridCoords, &DarkOre)>()
+            .iter(world)
+            .map(|(coords, dark_ore)| (*coords, dark_ore.amount))
+            .collect::<Vec<_>>();
+        dark_ores.sort_by_key(|(coords, _)| (coords.y, coords.x));
+
+        let stock = world.resource::<Stock>();
+        let stock = std::iter::once(ResourceType::DarkOre)
+            .chain(EssenceType::iter().map(ResourceType::from))
+            .map(|resource_type| (resource_type, stock.get(resource_type)))
+            .collect();
+
+        Self { map_size, buildings, walls, dark_ores, stock }
+    }
+}
+
+#[test]
+fn test_map_survives_save_and_reload() {
+    let mut app = headless_app();
+    load_world_from_path(app.world_mut(), TEST_MAP_PATH).expect("Failed to load the test map");
+    let loaded = WorldSummary::collect(app.world_mut());
+    assert!(!loaded.buildings.is_empty(), "The test map is expected to contain buildings");
+    assert!(!loaded.dark_ores.is_empty(), "The test map is expected to contain dark ore");
+
+    let save_path = std::env::temp_dir().join(format!("dwd_round_trip_{}.dwd", std::process::id()));
+    let save_path = save_path.to_str().unwrap();
+    save_world_to_path(app.world_mut(), save_path).expect("Failed to save the loaded test map");
+
+    let mut reloaded_app = headless_app();
+    let reload_result = load_world_from_path(reloaded_app.world_mut(), save_path);
+    let _ = std::fs::remove_file(save_path);
+    reload_result.expect("Failed to load the saved test map");
+
+    assert_eq!(loaded, WorldSummary::collect(reloaded_app.world_mut()));
+}
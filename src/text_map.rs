@@ -0,0 +1,418 @@
+//! Human-readable map format (YAML or JSON, picked by file extension) that can be diffed and reviewed in git.
+//!
+//! Importing writes a scratch save holding only `MapInfo` and the starting stock, loads it through the regular
+//! `MapLoadingStage` flow and spawns the map elements as Builder components once `SpawnMapElements` is entered.
+//! Exporting reads the same runtime components the savers use.
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use lib_grid::grids::terrain::TerrainGrid;
+
+use crate::buildings::{
+    energy_relay::BuilderEnergyRelay,
+    exploration_center::BuilderExplorationCenter,
+    main_base::BuilderMainBase,
+    mining_complex::BuilderMiningComplex,
+    tower_blaster::BuilderTowerBlaster,
+    tower_cannon::BuilderTowerCannon,
+    tower_emitter::BuilderTowerEmitter,
+    tower_rocket_launcher::BuilderTowerRocketLauncher,
+};
+use crate::map_objects::{
+    dark_ore::{BuilderDarkOre, DarkOre, DARK_ORE_GRID_IMPRINT},
+    quantum_field::{BuilderQuantumField, QuantumField},
+    terrain::TerrainCells,
+    walls::{BuilderWall, Wall, WALL_GRID_IMPRINT},
+};
+use crate::objectives::{BuilderObjective, ObjectiveDetails, ObjectiveKillWisps, ObjectiveType};
+use crate::prelude::*;
+use crate::wisps::summoning::{BuilderSummoning, SpawnArea, Summoning};
+
+pub struct TextMapPlugin;
+impl Plugin for TextMapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(MapLoadingStage::SpawnMapElements), PendingTextMap::spawn_map_elements)
+            .add_observer(LoadTextMapSignal::on_trigger)
+            .add_observer(ExportTextMapSignal::on_trigger);
+    }
+}
+
+pub const TEXT_MAP_EXTENSIONS: [&str; 3] = ["yaml", "yml", "json"];
+/// Scratch save used to run text maps through the regular loading flow.
+pub const TEXT_MAP_IMPORT_PATH: &str = "saves/text_map_import.db";
+
+pub fn is_text_map_path(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| TEXT_MAP_EXTENSIONS.contains(&extension))
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextMap {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    /// Seed of `GameRng`. Maps without one get a seed derived from their name and size.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Starting stock keyed like the `stock` table: `DarkOre` and essence names.
+    pub stock: BTreeMap<String, i32>,
+    pub walls: Vec<GridCoords>,
+    pub dark_ores: Vec<TextDarkOre>,
+    pub quantum_fields: Vec<TextQuantumField>,
+    /// Fields with anything other than plain ground.
+    pub terrain: Vec<TextTerrain>,
+    pub buildings: Vec<TextBuilding>,
+    pub summonings: Vec<Summoning>,
+    pub objectives: Vec<TextObjective>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextDarkOre {
+    pub x: i32,
+    pub y: i32,
+    pub amount: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextQuantumField {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Non-rectangular footprint. `width` and `height` then hold its bounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<GridImprint>,
+}
+
+impl TextQuantumField {
+    pub fn grid_imprint(&self) -> GridImprint {
+        self.shape.unwrap_or(GridImprint::Rectangle { width: self.width, height: self.height })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextTerrain {
+    pub x: i32,
+    pub y: i32,
+    pub terrain: TerrainType,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextBuilding {
+    pub building_type: BuildingType,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TextObjective {
+    pub id_name: String,
+    pub activation_event: String,
+    #[serde(flatten)]
+    pub kind: TextObjectiveKind,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TextObjectiveKind {
+    ClearQuantumFields,
+    KillWisps { amount: usize },
+}
+
+impl TextMap {
+    pub fn read(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+        if path.ends_with(".json") {
+            Ok(serde_json::from_reader(file)?)
+        } else {
+            Ok(serde_yaml::from_reader(file)?)
+        }
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::File::create(path)?;
+        if path.ends_with(".json") {
+            serde_json::to_writer_pretty(file, self)?;
+        } else {
+            serde_yaml::to_writer(file, self)?;
+        }
+        Ok(())
+    }
+
+    /// Builds the text map from the current world. Lists are sorted so re-exporting an unchanged map gives the same file.
+    pub fn from_world(world: &mut World) -> Self {
+        let map_info = world.resource::<MapInfo>().clone();
+        let stock = world.resource::<Stock>();
+        let mut stock_entries = BTreeMap::from([("DarkOre".to_string(), stock.get(ResourceType::DarkOre))]);
+        for essence_type in EssenceType::iter() {
+            stock_entries.insert(essence_type.as_ref().to_string(), stock.get(ResourceType::Essence(essence_type)));
+        }
+
+        let mut walls = world.query_filtered::<&GridCoords, With<Wall>>()
+            .iter(world)
+            .copied()
+            .collect::<Vec<_>>();
+        walls.sort_by_key(|coords| (coords.y, coords.x));
+
+        let mut dark_ores = world.query::<(&GridCoords, &DarkOre)>()
+            .iter(world)
+            .map(|(coords, dark_ore)| TextDarkOre { x: coords.x, y: coords.y, amount: dark_ore.amount as u32 })
+            .collect::<Vec<_>>();
+        dark_ores.sort_by_key(|dark_ore| (dark_ore.y, dark_ore.x));
+
+        let mut quantum_fields = world.query_filtered::<(&GridCoords, &GridImprint), With<QuantumField>>()
+            .iter(world)
+            .map(|(coords, imprint)| {
+                let (width, height) = imprint.bounds();
+                let shape = (!matches!(imprint, GridImprint::Rectangle { .. })).then_some(*imprint);
+                TextQuantumField { x: coords.x, y: coords.y, width, height, shape }
+            })
+            .collect::<Vec<_>>();
+        quantum_fields.sort_by_key(|quantum_field| (quantum_field.y, quantum_field.x));
+
+        // Already row by row
+        let terrain = world.resource::<TerrainGrid>()
+            .iter_non_plain()
+            .map(|(coords, terrain)| TextTerrain { x: coords.x, y: coords.y, terrain })
+            .collect::<Vec<_>>();
+
+        let mut buildings = world.query::<(&BuildingType, &GridCoords)>()
+            .iter(world)
+            .map(|(building_type, coords)| TextBuilding { building_type: *building_type, x: coords.x, y: coords.y })
+            .collect::<Vec<_>>();
+        buildings.sort_by_key(|building| (building.y, building.x));
+
+        let mut summonings = world.query::<&Summoning>()
+            .iter(world)
+            .cloned()
+            .collect::<Vec<_>>();
+        summonings.sort_by(|a, b| a.id_name.cmp(&b.id_name));
+
+        let mut objectives = world.query::<(&ObjectiveDetails, Option<&ObjectiveKillWisps>)>()
+            .iter(world)
+            .map(|(details, kill_wisps)| TextObjective {
+                id_name: details.id_name.clone(),
+                activation_event: details.activation_event.clone(),
+                kind: match details.objective_type {
+                    ObjectiveType::ClearAllQuantumFields => TextObjectiveKind::ClearQuantumFields,
+                    ObjectiveType::KillWisps(amount) => TextObjectiveKind::KillWisps {
+                        amount: kill_wisps.map_or(amount, |kill_wisps| kill_wisps.target_amount),
+                    },
+                },
+            })
+            .collect::<Vec<_>>();
+        objectives.sort_by(|a, b| a.id_name.cmp(&b.id_name));
+
+        Self {
+            name: map_info.name,
+            width: map_info.grid_width,
+            height: map_info.grid_height,
+            seed: Some(world.resource::<GameRng>().seed()),
+            stock: stock_entries,
+            walls,
+            dark_ores,
+            quantum_fields,
+            terrain,
+            buildings,
+            summonings,
+            objectives,
+        }
+    }
+
+    /// Checks that the map has a size and every object lies fully within it. Building footprints come from the almanach.
+    pub fn validate(&self, almanach: &Almanach) -> Result<(), String> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(format!("Map size {}x{} is not positive", self.width, self.height));
+        }
+        let bounds = (self.width, self.height);
+        let check_imprint = |what: &str, coords: GridCoords, imprint: GridImprint| {
+            match imprint.iter_covered_coords(coords).find(|covered| !covered.is_in_bounds(bounds)) {
+                Some(covered) => Err(format!("{} at ({}, {}) covers ({}, {}), outside of the {}x{} map", what, coords.x, coords.y, covered.x, covered.y, self.width, self.height)),
+                None => Ok(()),
+            }
+        };
+
+        for coords in &self.walls {
+            check_imprint("Wall", *coords, WALL_GRID_IMPRINT)?;
+        }
+        for dark_ore in &self.dark_ores {
+            check_imprint("Dark ore", GridCoords { x: dark_ore.x, y: dark_ore.y }, DARK_ORE_GRID_IMPRINT)?;
+        }
+        for quantum_field in &self.quantum_fields {
+            let grid_imprint = quantum_field.grid_imprint();
+            grid_imprint.validate().map_err(|e| format!("Quantum field at ({}, {}): {}", quantum_field.x, quantum_field.y, e))?;
+            check_imprint("Quantum field", GridCoords { x: quantum_field.x, y: quantum_field.y }, grid_imprint)?;
+        }
+        for terrain in &self.terrain {
+            check_imprint("Terrain", GridCoords { x: terrain.x, y: terrain.y }, GridImprint::default())?;
+        }
+        for building in &self.buildings {
+            let Some(building_info) = almanach.find_building_info(building.building_type) else {
+                return Err(format!("Building {:?} at ({}, {}) is not in the almanach", building.building_type, building.x, building.y));
+            };
+            check_imprint(&building_info.name, GridCoords { x: building.x, y: building.y }, building_info.grid_imprint)?;
+        }
+        for summoning in &self.summonings {
+            let what = format!("Summoning '{}' spawn area", summoning.id_name);
+            match &summoning.area {
+                SpawnArea::Coords { coords } => {
+                    for coords in coords {
+                        check_imprint(&what, *coords, GridImprint::default())?;
+                    }
+                }
+                SpawnArea::Rect { origin, width, height } => {
+                    check_imprint(&what, *origin, GridImprint::Rectangle { width: *width, height: *height })?;
+                }
+                SpawnArea::Edge { .. } | SpawnArea::EdgesAll => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes `TEXT_MAP_IMPORT_PATH` with the map info, the starting stock, the terrain and the seed, everything else is spawned from Builders.
+    fn write_import_save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let map_info = MapInfo {
+            grid_width: self.width,
+            grid_height: self.height,
+            world_width: self.width as f32 * CELL_SIZE,
+            world_height: self.height as f32 * CELL_SIZE,
+            name: self.name.clone(),
+        };
+        let mut stock = Stock::default();
+        for (resource_name, amount) in &self.stock {
+            let resource_type = match resource_name.as_str() {
+                "DarkOre" => ResourceType::DarkOre,
+                essence_name => ResourceType::Essence(EssenceType::from_str(essence_name)
+                    .map_err(|_| format!("Unknown stock resource '{}'", essence_name))?),
+            };
+            stock.set(resource_type, *amount);
+        }
+
+        let mut objects: Vec<Box<dyn SaveableBatch>> = vec![
+            Box::new(SaveableBatchCommand::from_single(map_info)),
+            Box::new(SaveableBatchCommand::from_single(stock)),
+            Box::new(SaveableBatchCommand::from_single(TerrainCells {
+                cells: self.terrain.iter().map(|terrain| (GridCoords { x: terrain.x, y: terrain.y }, terrain.terrain)).collect(),
+            })),
+        ];
+        if let Some(seed) = self.seed {
+            objects.push(Box::new(SaveableBatchCommand::from_single(GameRng::from_seed(seed))));
+        }
+        GameSaveExecutor::write_save(TEXT_MAP_IMPORT_PATH, objects).result.map_err(|e| e.into())
+    }
+}
+
+/// Loads a text map through the regular loading flow.
+#[derive(Event)]
+pub struct LoadTextMapSignal(pub String);
+impl LoadTextMapSignal {
+    fn on_trigger(
+        trigger: On<LoadTextMapSignal>,
+        mut commands: Commands,
+        almanach: Res<Almanach>,
+    ) {
+        let path = &trigger.event().0;
+        // Nothing is touched until the file is known to be valid
+        let text_map = TextMap::read(path)
+            .and_then(|text_map| text_map.validate(&almanach).map(|_| text_map).map_err(|e| e.into()))
+            .and_then(|text_map| text_map.write_import_save().map(|_| text_map));
+        let text_map = match text_map {
+            Ok(text_map) => text_map,
+            Err(e) => {
+                eprintln!("Failed to import text map '{}': {}", path, e);
+                return;
+            }
+        };
+        println!("Importing text map '{}'", path);
+        commands.insert_resource(PendingTextMap(text_map));
+        commands.trigger(LoadGameSignal(TEXT_MAP_IMPORT_PATH.to_string()));
+    }
+}
+
+/// Path in `maps/` to export the map named `map_name` to. Characters that don't belong in a file name are replaced.
+pub fn text_map_export_path(map_name: &str, extension: &str) -> String {
+    let file_name = map_name.trim()
+        .chars()
+        .take(64)
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '_' | '-') { c } else { '_' })
+        .collect::<String>();
+    let file_name = if file_name.trim_matches('_').is_empty() { "unnamed_map".to_string() } else { file_name };
+    format!("maps/{}.{}", file_name, extension)
+}
+
+/// Writes the current map to `path` as a text map.
+#[derive(Event)]
+pub struct ExportTextMapSignal(pub String);
+impl ExportTextMapSignal {
+    fn on_trigger(
+        trigger: On<ExportTextMapSignal>,
+        mut commands: Commands,
+    ) {
+        let path = trigger.event().0.clone();
+        commands.queue(move |world: &mut World| {
+            match TextMap::from_world(world).write(&path) {
+                Ok(()) => println!("Exported text map to '{}'", path),
+                Err(e) => eprintln!("Failed to export text map to '{}': {}", path, e),
+            }
+        });
+    }
+}
+
+/// Text map waiting for `MapLoadingStage::SpawnMapElements` to spawn its elements.
+#[derive(Resource)]
+struct PendingTextMap(TextMap);
+impl PendingTextMap {
+    fn spawn_map_elements(
+        mut commands: Commands,
+        pending_text_map: Option<Res<PendingTextMap>>,
+    ) {
+        let Some(pending_text_map) = pending_text_map else { return; };
+        commands.remove_resource::<PendingTextMap>();
+        let text_map = &pending_text_map.0;
+
+        for coords in &text_map.walls {
+            commands.spawn(BuilderWall::new(*coords));
+        }
+        for dark_ore in &text_map.dark_ores {
+            commands.spawn(BuilderDarkOre::new(GridCoords { x: dark_ore.x, y: dark_ore.y }, dark_ore.amount));
+        }
+        for quantum_field in &text_map.quantum_fields {
+            commands.spawn(BuilderQuantumField::new(GridCoords { x: quantum_field.x, y: quantum_field.y }, quantum_field.grid_imprint()));
+        }
+        for building in &text_map.buildings {
+            let coords = GridCoords { x: building.x, y: building.y };
+            match building.building_type {
+                BuildingType::EnergyRelay => commands.spawn(BuilderEnergyRelay::new(coords)),
+                BuildingType::MainBase => commands.spawn(BuilderMainBase::new(coords)),
+                BuildingType::Tower(TowerType::Blaster) => commands.spawn(BuilderTowerBlaster::new(coords)),
+                BuildingType::Tower(TowerType::Cannon) => commands.spawn(BuilderTowerCannon::new(coords)),
+                BuildingType::Tower(TowerType::RocketLauncher) => commands.spawn(BuilderTowerRocketLauncher::new(coords)),
+                BuildingType::Tower(TowerType::Emitter) => commands.spawn(BuilderTowerEmitter::new(coords)),
+                BuildingType::MiningComplex => commands.spawn(BuilderMiningComplex::new(coords)),
+                BuildingType::ExplorationCenter => commands.spawn(BuilderExplorationCenter::new(coords)),
+            };
+        }
+        for summoning in &text_map.summonings {
+            commands.spawn(BuilderSummoning::new(summoning.clone()));
+        }
+        for objective in &text_map.objectives {
+            let objective_type = match objective.kind {
+                TextObjectiveKind::ClearQuantumFields => ObjectiveType::ClearAllQuantumFields,
+                TextObjectiveKind::KillWisps { amount } => ObjectiveType::KillWisps(amount),
+            };
+            let details = ObjectiveDetails::new(objective.id_name.clone(), objective_type, objective.activation_event.clone());
+            commands.spawn(BuilderObjective::new(details));
+        }
+        println!("Spawned map elements of text map '{}'", text_map.name);
+    }
+}
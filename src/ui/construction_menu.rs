@@ -13,6 +13,7 @@ use crate::buildings::tower_cannon::TOWER_CANNON_BASE_IMAGE;
 use crate::buildings::tower_rocket_launcher::TOWER_ROCKET_LAUNCHER_BASE_IMAGE;
 use crate::map_objects::dark_ore::DARK_ORE_BASE_IMAGES;
 use crate::map_objects::quantum_field::QuantumFieldImprintSelector;
+use crate::map_objects::terrain::terrain_color;
 use crate::ui::grid_object_placer::{GridObjectPlacer, GridObjectPlacerRequest};
 use crate::wisps::components::WispType;
 
@@ -161,6 +162,14 @@ impl ConstructObjectButton{
         }
     }
 
+    /// Terrain has no image, the button shows the terrain's own color instead.
+    pub fn new_admin_terrain(terrain_type: TerrainType) -> Self {
+        Self {
+            object_type: terrain_type.into(),
+            background_color: terrain_color(terrain_type).with_alpha(1.),
+        }
+    }
+
     fn on_add(
         trigger: On<Add, ConstructObjectButton>,
         mut commands: Commands,
@@ -319,6 +328,10 @@ impl SideMenu {
                             ConstructObjectButton::new_admin(GridObjectPlacer::DarkOre),
                             ConstructObjectButton::new_admin(GridObjectPlacer::Wall),
                             ConstructObjectButton::new_admin(GridObjectPlacer::QuantumField(QuantumFieldImprintSelector::default())),
+                            ConstructObjectButton::new_admin_terrain(TerrainType::Road),
+                            ConstructObjectButton::new_admin_terrain(TerrainType::Mud),
+                            ConstructObjectButton::new_admin_terrain(TerrainType::Swamp),
+                            ConstructObjectButton::new_admin_terrain(TerrainType::Chasm),
                         ]
                     )]
                 ),
@@ -43,7 +43,7 @@ impl GridObjectPlacerRequest {
 }
 
 #[derive(Component, Default, Clone, Debug, PartialEq)]
-#[require(GridImprint, GridCoords, Sprite, ZDepth = ZDepth(10.), AutoGridTransformSync)]
+#[require(GridImprint, GridCoords, Transform, Visibility, ZDepth = ZDepth(10.), AutoGridTransformSync)]
 pub enum GridObjectPlacer {
     #[default]
     None,
@@ -52,6 +52,7 @@ pub enum GridObjectPlacer {
     DarkOre,
     QuantumField(QuantumFieldImprintSelector),
     Wisp(WispType),
+    Terrain(TerrainType),
 }
 impl GridObjectPlacer {
     pub fn as_grid_imprint(&self, almanach: &Almanach) -> GridImprint {
@@ -61,6 +62,7 @@ impl GridObjectPlacer {
             GridObjectPlacer::DarkOre => DARK_ORE_GRID_IMPRINT,
             GridObjectPlacer::QuantumField(imprint_selector) => imprint_selector.get(),
             GridObjectPlacer::Wisp(_) => WISP_GRID_IMPRINT,
+            GridObjectPlacer::Terrain(_) => GridImprint::Rectangle { width: 1, height: 1 },
             GridObjectPlacer::None => unreachable!(),
         }
     }
@@ -78,13 +80,16 @@ impl GridObjectPlacer {
         obstacle_grid: Res<ObstacleGrid>,
         energy_supply_grid: Res<EnergySupplyGrid>,
         mouse_info: Res<MouseInfo>,
-        placer: Single<(&mut Sprite, &GridObjectPlacer, &GridImprint, &GridCoords)>,
+        placer: Single<(&GridObjectPlacer, &GridImprint, &GridCoords)>,
+        mut cells: Query<&mut Sprite, With<GridObjectPlacerCell>>,
     ) {
-        let (mut sprite, grid_object_placer, grid_imprint, grid_coords) = placer.into_inner();
+        let (grid_object_placer, grid_imprint, grid_coords) = placer.into_inner();
         let is_imprint_in_bounds = mouse_info.grid_coords.is_imprint_in_bounds(grid_imprint, obstacle_grid.bounds());
         let is_imprint_placable = match &*grid_object_placer {
             GridObjectPlacer::None => false,
             GridObjectPlacer::Building(building_type) => obstacle_grid.query_building_placement(*grid_coords, *building_type, *grid_imprint),
+            // Terrain lies under everything
+            GridObjectPlacer::Terrain(_) => true,
             _ => obstacle_grid.query_imprint_all(*grid_coords, *grid_imprint, |field| field.is_empty()),
         };
     
@@ -96,7 +101,7 @@ impl GridObjectPlacer {
             _ => (false, false)
         };
     
-        sprite.color = if is_imprint_placable && is_imprint_in_bounds {
+        let color = if is_imprint_placable && is_imprint_in_bounds {
             if needs_energy_supply && !is_imprint_powered {
                 Color::srgba(1.0, 1.0, 0.0, 0.2)
             } else {
@@ -105,13 +110,23 @@ impl GridObjectPlacer {
         } else {
             Color::srgba(1.0, 0.0, 0.0, 0.2)
         };
+        cells.iter_mut().for_each(|mut sprite| sprite.color = color);
     }
 }
+
+/// Preview of a single cell covered by the placed imprint, so shapes other than rectangles show their real footprint.
+#[derive(Component)]
+pub struct GridObjectPlacerCell;
 impl From<BuildingType> for GridObjectPlacer {
     fn from(building_type: BuildingType) -> Self {
         GridObjectPlacer::Building(building_type)
     }
 }
+impl From<TerrainType> for GridObjectPlacer {
+    fn from(terrain_type: TerrainType) -> Self {
+        GridObjectPlacer::Terrain(terrain_type)
+    }
+}
 impl From<WispType> for GridObjectPlacer {
     fn from(wisp_type: WispType) -> Self {
         GridObjectPlacer::Wisp(wisp_type)
@@ -166,17 +181,35 @@ fn keyboard_input_system(
 fn on_request_grid_object_placer_system(
     almanach: Res<Almanach>,
     mut ui_interaction_state: ResMut<NextState<UiInteraction>>,
-    placer: Single<(&mut Sprite, &mut GridObjectPlacer, &mut GridImprint)>,
+    mut commands: Commands,
+    placer: Single<(Entity, &mut GridObjectPlacer, &mut GridImprint)>,
     mut placer_request: ResMut<GridObjectPlacerRequest>,
 ) {
     let Some(placer_request) = placer_request.take() else { return; };
-    let (mut sprite, mut grid_object_placer, mut grid_imprint) = placer.into_inner();
+    let (placer_entity, mut grid_object_placer, mut grid_imprint) = placer.into_inner();
     *grid_object_placer = placer_request;
     match &*grid_object_placer {
         GridObjectPlacer::None => panic!("GridObjectPlacer::None should not be possible here"),
         placer => {
             *grid_imprint = placer.as_grid_imprint(&almanach);
-            sprite.custom_size = Some(grid_imprint.world_size());
+            let imprint = *grid_imprint;
+            commands.entity(placer_entity)
+                .despawn_related::<Children>()
+                .with_children(|parent| {
+                    for cell_coords in imprint.iter_covered_coords(GridCoords::default()) {
+                        // Relative to the imprint's center, where the placer itself is positioned
+                        let offset = cell_coords.to_world_position() + Vec2::splat(CELL_SIZE / 2.) - imprint.world_center();
+                        parent.spawn((
+                            GridObjectPlacerCell,
+                            Sprite {
+                                custom_size: Some(Vec2::splat(CELL_SIZE)),
+                                color: Color::NONE,
+                                ..Default::default()
+                            },
+                            Transform::from_translation(offset.extend(0.)),
+                        ));
+                    }
+                });
         }
     }
     ui_interaction_state.set(UiInteraction::PlaceGridObject);
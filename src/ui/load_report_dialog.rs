@@ -0,0 +1,184 @@
+use crate::data_loader::{AlmanachSnapshotDiff, AlmanachSource, ReloadWithAlmanachSource};
+use crate::prelude::*;
+
+pub struct LoadReportDialogPlugin;
+impl Plugin for LoadReportDialogPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, |mut commands: Commands| { commands.spawn(LoadReportDialog); })
+            .add_systems(OnEnter(MapLoadingStage::Ready), LoadReportDialog::show)
+            .add_systems(OnExit(MapLoadingStage::Ready), LoadReportDialog::hide)
+            .add_observer(LoadReportDialog::on_add)
+            .add_observer(LoadReportDecisionButton::on_add)
+            .add_observer(AlmanachSourceButton::on_add)
+            ;
+    }
+}
+
+/// Only the first issues are listed, the rest are in the console output.
+const MAX_LISTED_ISSUES: usize = 15;
+
+#[derive(Component)]
+struct LoadReportDialog;
+impl LoadReportDialog {
+    fn on_add(trigger: On<Add, LoadReportDialog>, mut commands: Commands) {
+        commands.entity(trigger.entity).insert((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor::from(Color::linear_rgba(0.0, 0.0, 0.0, 0.7)),
+            Visibility::Hidden,
+        ));
+    }
+
+    fn show(
+        mut commands: Commands,
+        load_report: Res<LoadReport>,
+        almanach_source: Res<AlmanachSource>,
+        snapshot_diff: Res<AlmanachSnapshotDiff>,
+        dialog: Single<(Entity, &mut Visibility), With<LoadReportDialog>>,
+    ) {
+        if load_report.issues.is_empty() { return; }
+        let (dialog_entity, mut visibility) = dialog.into_inner();
+        *visibility = Visibility::Inherited;
+
+        let mut issues_text = load_report.issues.iter()
+            .take(MAX_LISTED_ISSUES)
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        if load_report.issues.len() > MAX_LISTED_ISSUES {
+            issues_text.push_str(&format!("\n... and {} more", load_report.issues.len() - MAX_LISTED_ISSUES));
+        }
+
+        commands.entity(dialog_entity).despawn_related::<Children>();
+        commands.entity(dialog_entity).with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        max_width: Val::Px(720.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        padding: UiRect::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    BackgroundColor::from(Color::linear_rgba(0.15, 0.15, 0.15, 1.0)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(Text::new(format!("Loading '{}' reported {} issue(s)", load_report.path, load_report.issues.len())));
+                    parent.spawn((
+                        Text::new(issues_text),
+                        TextFont::default().with_font_size(11.),
+                    ));
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(10.0),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(LoadReportDecisionButton(LoadReportDecision::Continue));
+                            parent.spawn(LoadReportDecisionButton(LoadReportDecision::Abort));
+                            if !snapshot_diff.changed_buildings.is_empty() {
+                                let other_source = match *almanach_source {
+                                    AlmanachSource::Snapshot => AlmanachSource::Current,
+                                    AlmanachSource::Current => AlmanachSource::Snapshot,
+                                };
+                                parent.spawn(AlmanachSourceButton(other_source));
+                            }
+                        });
+                });
+        });
+    }
+
+    fn hide(dialog: Single<&mut Visibility, With<LoadReportDialog>>) {
+        *dialog.into_inner() = Visibility::Hidden;
+    }
+}
+
+#[derive(Component)]
+#[require(Button)]
+struct LoadReportDecisionButton(LoadReportDecision);
+impl LoadReportDecisionButton {
+    fn on_add(trigger: On<Add, LoadReportDecisionButton>, mut commands: Commands, buttons: Query<&LoadReportDecisionButton>, load_report: Res<LoadReport>) {
+        let entity = trigger.entity;
+        let (label, color) = match buttons.get(entity).unwrap().0 {
+            LoadReportDecision::Continue => ("Continue anyway", Color::linear_rgba(0.2, 0.6, 0.2, 1.0)),
+            LoadReportDecision::Abort if load_report.restore_point.is_some() => ("Restore previous map", Color::linear_rgba(0.7, 0.15, 0.15, 1.0)),
+            LoadReportDecision::Abort => ("Back to menu", Color::linear_rgba(0.7, 0.15, 0.15, 1.0)),
+        };
+        commands.entity(entity)
+            .insert((
+                Node {
+                    width: Val::Px(220.0),
+                    height: Val::Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor::from(color),
+                children![(
+                    Text::new(label),
+                    TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                )],
+            ))
+            .observe(Self::on_click);
+    }
+
+    fn on_click(
+        trigger: On<Pointer<Click>>,
+        mut commands: Commands,
+        buttons: Query<&LoadReportDecisionButton>,
+        dialog: Single<&mut Visibility, With<LoadReportDialog>>,
+    ) {
+        let Ok(button) = buttons.get(trigger.entity) else { return; };
+        *dialog.into_inner() = Visibility::Hidden;
+        commands.trigger(button.0);
+    }
+}
+
+/// Reloads the save with the other building data when its snapshot differs from the current data.
+#[derive(Component)]
+#[require(Button)]
+struct AlmanachSourceButton(AlmanachSource);
+impl AlmanachSourceButton {
+    fn on_add(trigger: On<Add, AlmanachSourceButton>, mut commands: Commands, buttons: Query<&AlmanachSourceButton>) {
+        let entity = trigger.entity;
+        let label = format!("Reload with {} data", buttons.get(entity).unwrap().0.label());
+        commands.entity(entity)
+            .insert((
+                Node {
+                    width: Val::Px(220.0),
+                    height: Val::Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor::from(Color::linear_rgba(0.2, 0.3, 0.6, 1.0)),
+                children![(
+                    Text::new(label),
+                    TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                )],
+            ))
+            .observe(Self::on_click);
+    }
+
+    fn on_click(
+        trigger: On<Pointer<Click>>,
+        mut commands: Commands,
+        buttons: Query<&AlmanachSourceButton>,
+        dialog: Single<&mut Visibility, With<LoadReportDialog>>,
+    ) {
+        let Ok(button) = buttons.get(trigger.entity) else { return; };
+        *dialog.into_inner() = Visibility::Hidden;
+        commands.trigger(ReloadWithAlmanachSource(button.0));
+    }
+}
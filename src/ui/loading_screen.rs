@@ -0,0 +1,151 @@
+use crate::prelude::*;
+
+pub struct LoadingScreenPlugin;
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, |mut commands: Commands| { commands.spawn(LoadingScreen); })
+            .add_systems(OnEnter(GameState::Loading), LoadingScreen::show)
+            .add_systems(OnExit(GameState::Loading), LoadingScreen::hide)
+            // The load report dialog takes over when loading stops at Ready
+            .add_systems(OnEnter(MapLoadingStage::Ready), LoadingScreen::hide)
+            .add_systems(Update, LoadingScreen::update_progress.run_if(in_state(GameState::Loading)))
+            .add_observer(LoadingScreen::on_add)
+            .add_observer(LoadingProgressBar::on_add)
+            ;
+    }
+}
+
+const STAGES: [(MapLoadingStage, &str); 3] = [
+    (MapLoadingStage::LoadMapInfo, "Map info"),
+    (MapLoadingStage::LoadResources, "Resources"),
+    (MapLoadingStage::SpawnMapElements, "Map elements"),
+];
+
+#[derive(Component)]
+struct LoadingScreen;
+impl LoadingScreen {
+    fn on_add(trigger: On<Add, LoadingScreen>, mut commands: Commands) {
+        commands.entity(trigger.entity)
+            .insert((
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                BackgroundColor::from(Color::linear_rgba(0.0, 0.0, 0.0, 0.9)),
+                Visibility::Hidden,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new("Loading"),
+                    TextFont::default().with_font_size(24.),
+                    LoadingScreenTitle,
+                ));
+                parent.spawn(LoadingProgressBar { stage: None, label: "Overall" });
+                for (stage, label) in STAGES {
+                    parent.spawn(LoadingProgressBar { stage: Some(stage), label });
+                }
+            });
+    }
+
+    fn show(screen: Single<&mut Visibility, With<LoadingScreen>>) {
+        *screen.into_inner() = Visibility::Inherited;
+    }
+
+    fn hide(screen: Single<&mut Visibility, With<LoadingScreen>>) {
+        *screen.into_inner() = Visibility::Hidden;
+    }
+
+    fn update_progress(
+        stage: Res<State<MapLoadingStage>>,
+        progress: Res<LoadingProgress>,
+        map_info: Option<Res<MapInfo>>,
+        mut title: Single<&mut Text, With<LoadingScreenTitle>>,
+        bars: Query<&LoadingProgressBar>,
+        mut fills: Query<(&LoadingProgressFill, &mut Node)>,
+        mut labels: Query<(&LoadingProgressLabel, &mut Text), Without<LoadingScreenTitle>>,
+    ) {
+        // MapInfo still belongs to the previous map until its stage is done
+        title.0 = match (stage.get(), map_info) {
+            (MapLoadingStage::Init | MapLoadingStage::LoadMapInfo, _) | (_, None) => "Loading".to_string(),
+            (_, Some(map_info)) => format!("Loading {}", map_info.name),
+        };
+
+        for (fill, mut node) in fills.iter_mut() {
+            let Ok(bar) = bars.get(fill.0) else { continue; };
+            node.width = Val::Percent(bar.progress(&progress).fraction() * 100.);
+        }
+        for (label, mut text) in labels.iter_mut() {
+            let Ok(bar) = bars.get(label.0) else { continue; };
+            let stage_progress = bar.progress(&progress);
+            text.0 = format!("{} {:.0}% ({}/{})", bar.label, stage_progress.fraction() * 100., stage_progress.loaded.min(stage_progress.total), stage_progress.total);
+        }
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreenTitle;
+
+/// Progress of a single stage, or of the whole load when `stage` is `None`.
+#[derive(Component)]
+struct LoadingProgressBar {
+    stage: Option<MapLoadingStage>,
+    label: &'static str,
+}
+impl LoadingProgressBar {
+    fn on_add(trigger: On<Add, LoadingProgressBar>, mut commands: Commands) {
+        let entity = trigger.entity;
+        commands.entity(entity)
+            .insert(Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(2.0),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(""),
+                    TextFont::default().with_font_size(12.),
+                    LoadingProgressLabel(entity),
+                ));
+                parent.spawn((
+                    Node {
+                        width: Val::Px(320.0),
+                        height: Val::Px(12.0),
+                        ..default()
+                    },
+                    BackgroundColor::from(Color::linear_rgba(0.3, 0.3, 0.3, 1.0)),
+                    children![(
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor::from(Color::linear_rgba(0.2, 0.6, 0.2, 1.0)),
+                        LoadingProgressFill(entity),
+                    )],
+                ));
+            });
+    }
+
+    fn progress(&self, progress: &LoadingProgress) -> StageProgress {
+        match &self.stage {
+            Some(stage) => progress.stage(stage),
+            None => progress.overall(),
+        }
+    }
+}
+
+/// Points to the `LoadingProgressBar` it belongs to.
+#[derive(Component)]
+struct LoadingProgressFill(Entity);
+
+#[derive(Component)]
+struct LoadingProgressLabel(Entity);
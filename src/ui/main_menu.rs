@@ -1,6 +1,10 @@
 use std::fs;
 
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+
 use crate::prelude::*;
+use crate::text_map::{is_text_map_path, LoadTextMapSignal};
 
 pub struct MainMenuPlugin;
 impl Plugin for MainMenuPlugin {
@@ -9,10 +13,20 @@ impl Plugin for MainMenuPlugin {
             .add_systems(Startup, |mut commands: Commands| { commands.spawn(MainMenuRoot); })
             .add_systems(OnEnter(UiInteraction::MainMenu), on_menu_enter_system)
             .add_systems(OnExit(UiInteraction::MainMenu), on_menu_exit_system)
+            .add_systems(PreUpdate, (
+                SlotRename::keyboard_input_system
+                    .after(bevy::input::InputSystems)
+                    .run_if(resource_exists::<SlotRename>),
+            ))
             .add_observer(MainMenuRoot::on_add)
             .add_observer(LoadMapButton::on_add)
             .add_observer(MapListContainer::on_add)
             .add_observer(MapEntryButton::on_add)
+            .add_observer(SaveGameButton::on_add)
+            .add_observer(LoadGameButton::on_add)
+            .add_observer(SaveSlotListContainer::on_add)
+            .add_observer(SaveSlotEntry::on_add)
+            .add_observer(SaveSlotActionButton::on_add)
             ;
     }
 }
@@ -46,6 +60,9 @@ impl MainMenuRoot {
                         ..default()
                     },
                     children![
+                        SaveGameButton,
+                        LoadGameButton,
+                        SaveSlotListContainer,
                         LoadMapButton,
                         MapListContainer::default(),
                     ]
@@ -96,21 +113,23 @@ impl LoadMapButton {
         node.display = Display::Flex;
         commands.entity(container_entity).despawn_related::<Children>();
 
-        // Enumerate maps/*.yaml and create entry buttons
-        let map_names = fs::read_dir("maps")
+        // Enumerate maps/*.dwd and text maps, and create entry buttons
+        let mut map_file_names = fs::read_dir("maps")
             .ok()
             .into_iter()
             .flat_map(|rd| rd.filter_map(|e| e.ok()))
             .filter_map(|e| {
                 let path = e.path();
-                if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("dwd") {
-                    path.file_stem().and_then(|s| s.to_str()).map(|s| s.to_string())
+                let file_name = path.file_name()?.to_str()?.to_string();
+                if path.is_file() && (file_name.ends_with(".dwd") || is_text_map_path(&file_name)) {
+                    Some(file_name)
                 } else { None }
             })
             .collect::<Vec<_>>();
+        map_file_names.sort();
 
         commands.entity(container_entity).with_children(|parent| {
-            for name in map_names {
+            for name in map_file_names {
                 parent.spawn(MapEntryButton { name });
             }
         });
@@ -168,10 +187,313 @@ impl MapEntryButton {
         let entity = trigger.entity;
         let Ok(entry) = entries.get(entity) else { return; };
         println!("Map selected: {}", entry.name);
-        commands.trigger(lib_core::persistence::load::LoadGameSignal(format!("maps/{}.dwd", entry.name.clone())));
+        let path = format!("maps/{}", entry.name);
+        if is_text_map_path(&path) {
+            commands.trigger(LoadTextMapSignal(path));
+        } else {
+            commands.trigger(LoadGameSignal(path));
+        }
+    }
+}
+
+#[derive(Component)]
+#[require(Button)]
+struct SaveGameButton;
+impl SaveGameButton {
+    fn on_add(trigger: On<Add, SaveGameButton>, mut commands: Commands) {
+        commands.entity(trigger.entity)
+            .insert((
+                Node {
+                    width: Val::Px(220.0),
+                    height: Val::Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor::from(Color::linear_rgba(0.2, 0.6, 0.2, 1.0)),
+                children![(
+                    Text::new("Save Game"),
+                    TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                )],
+            ))
+            .observe(Self::on_click);
+    }
+
+    fn on_click(
+        _trigger: On<Pointer<Click>>,
+        mut writer: MessageWriter<SaveGameSignal>,
+        save_slot_list: Single<&mut Node, With<SaveSlotListContainer>>,
+    ) {
+        // Slots are created with a generic name, players can rename them from the list
+        let slot_name = (1..).map(|index| format!("save_{}", index))
+            .find(|slot_name| !std::path::Path::new(&slot_path(slot_name)).exists())
+            .unwrap();
+        println!("Saving game to slot: {}", slot_name);
+        writer.write(SaveGameSignal(slot_path(&slot_name)));
+        // The list is stale now, it gets rebuilt on next opening
+        save_slot_list.into_inner().display = Display::None;
+    }
+}
+
+#[derive(Component)]
+#[require(Button)]
+struct LoadGameButton;
+impl LoadGameButton {
+    fn on_add(trigger: On<Add, LoadGameButton>, mut commands: Commands) {
+        commands.entity(trigger.entity)
+            .insert((
+                Node {
+                    width: Val::Px(220.0),
+                    height: Val::Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor::from(Color::linear_rgba(0.2, 0.2, 0.8, 1.0)),
+                children![(
+                    Text::new("Load Game"),
+                    TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                )],
+            ))
+            .observe(Self::on_click);
+    }
+
+    fn on_click(
+        _trigger: On<Pointer<Click>>,
+        mut commands: Commands,
+        save_slot_list: Single<(Entity, &mut Node), With<SaveSlotListContainer>>,
+    ) {
+        let (container_entity, mut node) = save_slot_list.into_inner();
+        if node.display == Display::Flex {
+            node.display = Display::None;
+            return;
+        }
+        node.display = Display::Flex;
+        SaveSlotListContainer::rebuild(&mut commands, container_entity);
+    }
+}
+
+#[derive(Component)]
+#[require(Node)]
+struct SaveSlotListContainer;
+impl SaveSlotListContainer {
+    fn on_add(trigger: On<Add, SaveSlotListContainer>, mut commands: Commands) {
+        commands.entity(trigger.entity).insert((
+            Node {
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Stretch,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+        ));
+    }
+
+    fn rebuild(commands: &mut Commands, container_entity: Entity) {
+        commands.remove_resource::<SlotRename>();
+        commands.entity(container_entity).despawn_related::<Children>();
+        let slots = list_save_slots();
+        commands.entity(container_entity).with_children(|parent| {
+            if slots.is_empty() {
+                parent.spawn((
+                    Text::new("No saved games"),
+                    TextFont::default().with_font_size(14.),
+                ));
+            }
+            for slot in slots {
+                parent.spawn(SaveSlotEntry { slot });
+            }
+        });
+    }
+}
+
+#[derive(Component)]
+struct SaveSlotEntry { slot: SaveSlot }
+impl SaveSlotEntry {
+    fn on_add(trigger: On<Add, SaveSlotEntry>, mut commands: Commands, entries: Query<&SaveSlotEntry>) {
+        let entity = trigger.entity;
+        let slot = &entries.get(entity).unwrap().slot;
+        let details = match &slot.metadata {
+            Some(metadata) => format!(
+                "{} | played {} | objectives {}/{} | saved {}\n{}",
+                metadata.map_name,
+                format_duration(metadata.playtime as u64),
+                metadata.objectives_completed,
+                metadata.objectives_total,
+                format_saved_at(metadata.saved_at),
+                metadata.stock().iter().map(|(name, amount)| format!("{name}: {amount}")).collect::<Vec<_>>().join(", "),
+            ),
+            None => "No metadata".to_string(),
+        };
+
+        commands.entity(entity)
+            .insert((
+                Node {
+                    width: Val::Px(560.0),
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(6.0),
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor::from(Color::linear_rgba(0.3, 0.3, 0.3, 1.0)),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Node {
+                        flex_grow: 1.,
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    children![
+                        (
+                            Text::new(slot.name.clone()),
+                            TextLayout::new_with_linebreak(LineBreak::NoWrap),
+                            SaveSlotNameText { slot_name: slot.name.clone() },
+                        ),
+                        (
+                            Text::new(details),
+                            TextFont::default().with_font_size(11.),
+                        ),
+                    ],
+                ));
+                for action in [SaveSlotAction::Load, SaveSlotAction::Rename, SaveSlotAction::Delete] {
+                    parent.spawn(SaveSlotActionButton { slot_name: slot.name.clone(), action });
+                }
+            });
     }
 }
 
+#[derive(Component)]
+struct SaveSlotNameText { slot_name: String }
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SaveSlotAction {
+    Load,
+    Rename,
+    Delete,
+}
+
+#[derive(Component)]
+#[require(Button)]
+struct SaveSlotActionButton { slot_name: String, action: SaveSlotAction }
+impl SaveSlotActionButton {
+    fn on_add(trigger: On<Add, SaveSlotActionButton>, mut commands: Commands, buttons: Query<&SaveSlotActionButton>) {
+        let entity = trigger.entity;
+        let (label, color) = match buttons.get(entity).unwrap().action {
+            SaveSlotAction::Load => ("Load", Color::linear_rgba(0.2, 0.2, 0.8, 1.0)),
+            SaveSlotAction::Rename => ("Rename", Color::linear_rgba(0.4, 0.4, 0.4, 1.0)),
+            SaveSlotAction::Delete => ("Delete", Color::linear_rgba(0.7, 0.15, 0.15, 1.0)),
+        };
+        commands.entity(entity)
+            .insert((
+                Node {
+                    width: Val::Px(64.0),
+                    height: Val::Px(28.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor::from(color),
+                children![(
+                    Text::new(label),
+                    TextFont::default().with_font_size(12.),
+                )],
+            ))
+            .observe(Self::on_click);
+    }
+
+    fn on_click(
+        trigger: On<Pointer<Click>>,
+        mut commands: Commands,
+        buttons: Query<&SaveSlotActionButton>,
+        save_slot_list: Single<Entity, With<SaveSlotListContainer>>,
+    ) {
+        let Ok(button) = buttons.get(trigger.entity) else { return; };
+        match button.action {
+            SaveSlotAction::Load => {
+                println!("Save slot selected: {}", button.slot_name);
+                commands.trigger(LoadGameSignal(slot_path(&button.slot_name)));
+            },
+            SaveSlotAction::Rename => {
+                commands.insert_resource(SlotRename { slot_name: button.slot_name.clone(), buffer: button.slot_name.clone() });
+            },
+            SaveSlotAction::Delete => {
+                if let Err(e) = delete_save_slot(&button.slot_name) {
+                    eprintln!("Failed to delete save slot '{}': {}", button.slot_name, e);
+                }
+                SaveSlotListContainer::rebuild(&mut commands, save_slot_list.into_inner());
+            },
+        }
+    }
+}
+
+/// Inline editing of a slot name. While present, keyboard input is consumed so no game shortcuts fire.
+#[derive(Resource)]
+struct SlotRename {
+    slot_name: String,
+    buffer: String,
+}
+impl SlotRename {
+    fn keyboard_input_system(
+        mut commands: Commands,
+        mut slot_rename: ResMut<SlotRename>,
+        mut keyboard_events: MessageReader<KeyboardInput>,
+        mut keys: ResMut<ButtonInput<KeyCode>>,
+        mut name_texts: Query<(&SaveSlotNameText, &mut Text)>,
+        save_slot_list: Single<Entity, With<SaveSlotListContainer>>,
+    ) {
+        for event in keyboard_events.read() {
+            if event.state != ButtonState::Pressed { continue; }
+            match &event.logical_key {
+                Key::Character(characters) => {
+                    slot_rename.buffer.extend(characters.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-')));
+                },
+                Key::Backspace => { slot_rename.buffer.pop(); },
+                Key::Enter => {
+                    if slot_rename.buffer != slot_rename.slot_name {
+                        if let Err(e) = rename_save_slot(&slot_rename.slot_name, &slot_rename.buffer) {
+                            eprintln!("Failed to rename save slot '{}': {}", slot_rename.slot_name, e);
+                        }
+                    }
+                    SaveSlotListContainer::rebuild(&mut commands, save_slot_list.into_inner());
+                    keys.reset_all();
+                    return;
+                },
+                Key::Escape => {
+                    SaveSlotListContainer::rebuild(&mut commands, save_slot_list.into_inner());
+                    keys.reset_all();
+                    return;
+                },
+                _ => {},
+            }
+        }
+        keys.reset_all();
+
+        for (name_text, mut text) in name_texts.iter_mut() {
+            text.0 = if name_text.slot_name == slot_rename.slot_name {
+                format!("{}_", slot_rename.buffer)
+            } else {
+                name_text.slot_name.clone()
+            };
+        }
+    }
+}
+
+fn format_duration(total_seconds: u64) -> String {
+    let (hours, minutes, seconds) = (total_seconds / 3600, total_seconds / 60 % 60, total_seconds % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else {
+        format!("{minutes}m {seconds:02}s")
+    }
+}
+
+fn format_saved_at(saved_at: u64) -> String {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(saved_at);
+    format!("{} ago", format_duration(now.saturating_sub(saved_at)))
+}
+
 fn on_menu_enter_system(
     menu: Single<&mut Visibility, With<MainMenuRoot>>,
     mut next_game_state: ResMut<NextState<GameState>>,
@@ -181,11 +503,13 @@ fn on_menu_enter_system(
 }
 
 fn on_menu_exit_system(
+    mut commands: Commands,
     menu: Single<&mut Visibility, With<MainMenuRoot>>,
     current_game_state: Res<State<GameState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
 ) {
     *menu.into_inner() = Visibility::Hidden;
+    commands.remove_resource::<SlotRename>();
     if matches!(current_game_state.get(), GameState::Paused) {
         next_game_state.set(GameState::Running);
     }
@@ -1,6 +1,8 @@
 mod construction_menu;
 mod objectives_panel;
 mod badges;
+mod load_report_dialog;
+mod loading_screen;
 mod main_menu;
 mod pause_indicator;
 
@@ -23,6 +25,8 @@ impl Plugin for UiPlugin {
                 objectives_panel::ObjectivesPanelPlugin,
                 construction_menu::ConstructionMenuPlugin,
                 main_menu::MainMenuPlugin,
+                load_report_dialog::LoadReportDialogPlugin,
+                loading_screen::LoadingScreenPlugin,
                 pause_indicator::PauseIndicatorPlugin,
             ))
             .insert_resource(UiConfig::default())
@@ -52,15 +52,22 @@ impl Loadable for BuilderExpeditionDrone {
         while let Some(row) = rows.next()? {
             let old_id: i64 = row.get(0)?;
             let target_old_id: i64 = row.get(1)?;
-            let world_position = ctx.conn.get_world_position(old_id)?;
+            count += 1;
+            let Some(world_position) = ctx.expect_row(old_id, "world_positions", ctx.conn.get_world_position(old_id))? else { continue; };
             
-            let Some(new_entity) = ctx.get_new_entity_for_old(old_id) else { continue; };
-            let Some(new_target_entity) = ctx.get_new_entity_for_old(target_old_id) else { continue; }; 
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+            let Some(new_target_entity) = ctx.get_new_entity_for_old(target_old_id) else {
+                ctx.report_issue(Some(old_id), format!("Expedition target {} has no corresponding entity", target_old_id));
+                continue;
+            };
             ctx.commands.entity(new_entity).insert(BuilderExpeditionDrone::new_for_saving(world_position,new_target_entity,new_entity));
-            count += 1;
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("expedition_drones").map(Some)
+    }
 }
 impl BuilderExpeditionDrone {
     pub fn new(world_position: Vec2, target_entity: Entity) -> Self {
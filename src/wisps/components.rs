@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumIter, EnumString};
 
+use lib_grid::grids::emissions::EmissionsWeights;
+
 use crate::prelude::*;
 
 #[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, EnumIter, AsRefStr)]
@@ -11,6 +13,18 @@ pub enum WispType {
     Electric,
 }
 
+impl WispType {
+    /// How the wisp type is drawn to or repelled by each emissions channel.
+    pub fn emissions_weights(&self) -> EmissionsWeights {
+        match self {
+            WispType::Fire => EmissionsWeights { energy: 1., noise: 0.5, fear: -0.5, scent: 0.2 },
+            WispType::Water => EmissionsWeights { energy: 1., noise: 0., fear: -1., scent: 0.5 },
+            WispType::Light => EmissionsWeights { energy: 1.5, noise: 0., fear: -2., scent: 0. },
+            WispType::Electric => EmissionsWeights { energy: 1., noise: 1., fear: -0.5, scent: 0. },
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct WispFireType;
 #[derive(Component)]
@@ -24,7 +38,7 @@ pub struct WispElectricType;
 #[derive(Component, Debug, Default, PartialEq)]
 #[require(WispState, WispChargeAttack, GridPath, MovementSpeed, AttackRange, MaxHealth, MapBound)]
 pub struct Wisp;
-#[derive(Component, Default)]
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
 pub enum WispState {
     #[default]
     JustSpawned,
@@ -33,10 +47,33 @@ pub enum WispState {
     Attacking,
     Stranded(GridVersion), // No target available, waiting for change in obstacle grid
 }
+impl WispState {
+    /// Grid versions don't survive a load, so `Stranded` is stored without one.
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            WispState::JustSpawned => "JustSpawned",
+            WispState::NeedTarget => "NeedTarget",
+            WispState::MovingToTarget => "MovingToTarget",
+            WispState::Attacking => "Attacking",
+            WispState::Stranded(_) => "Stranded",
+        }
+    }
 
-#[derive(Component, Default)]
+    pub fn from_db_str(s: &str, grid_version: GridVersion) -> Option<Self> {
+        match s {
+            "JustSpawned" => Some(WispState::JustSpawned),
+            "NeedTarget" => Some(WispState::NeedTarget),
+            "MovingToTarget" => Some(WispState::MovingToTarget),
+            "Attacking" => Some(WispState::Attacking),
+            "Stranded" => Some(WispState::Stranded(grid_version)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, EnumString, AsRefStr)]
 pub enum WispChargeAttack {
     #[default]
     Charge,
     Backoff,
-}
\ No newline at end of file
+}
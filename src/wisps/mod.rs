@@ -20,9 +20,13 @@ impl Plugin for WispsPlugin {
                 
             ))
             .add_plugins(summoning::SummoningPlugin)
+            .init_resource::<systems::WispFlowFields>()
+            .add_systems(OnExit(MapLoadingStage::LoadMapInfo), systems::WispFlowFields::reset)
+            .add_systems(OnEnter(MapLoadingStage::Ready), spawning::RestoreWispGridVersion::on_map_ready)
             .add_systems(Update, (
                 (
                     systems::move_wisps,
+                    systems::leave_scent_trails,
                     systems::target_wisps,
                     systems::wisp_charge_attack,
                     systems::collide_wisps,
@@ -1,20 +1,27 @@
+use std::collections::VecDeque;
 use std::str::FromStr;
 
+use lib_grid::grids::obstacles::ObstacleGrid;
 use lib_grid::grids::wisps::WispsGrid;
 
 use crate::prelude::*;
 use crate::ui::grid_object_placer::GridObjectPlacer;
 
-use super::components::{Wisp, WispElectricType, WispFireType, WispLightType, WispState, WispType, WispWaterType};
+use super::components::{Wisp, WispChargeAttack, WispElectricType, WispFireType, WispLightType, WispState, WispType, WispWaterType};
 use super::materials::WispMaterial;
 
 pub const WISP_GRID_IMPRINT: GridImprint = GridImprint::Rectangle { width: 1, height: 1 };
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct WispSaveData {
     pub entity: Entity,
     pub health: f32,
     pub world_position: Vec2,
+    pub state: WispState,
+    pub charge_attack: WispChargeAttack,
+    pub path: VecDeque<GridCoords>,
+    /// Whether `path` and a `Stranded` state were up to date with the obstacle grid when saved.
+    pub is_path_current: bool,
 }
 
 #[derive(Component, SSS)]
@@ -33,43 +40,75 @@ impl Saveable for BuilderWisp {
         tx.save_world_position(entity_index, save_data.world_position)?;
         tx.save_grid_coords(entity_index, self.grid_coords)?;
         tx.save_health(entity_index, save_data.health)?;
+        tx.save_grid_path(entity_index, &save_data.path, save_data.is_path_current)?;
 
         let type_str = self.wisp_type.as_ref();
         tx.execute(
             "INSERT OR REPLACE INTO wisps (id, wisp_type) VALUES (?1, ?2)",
             rusqlite::params![entity_index, type_str],
         )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO wisp_states (id, state, charge_attack) VALUES (?1, ?2, ?3)",
+            rusqlite::params![entity_index, save_data.state.as_db_str(), save_data.charge_attack.as_ref()],
+        )?;
         Ok(())
     }
 }
 
 impl Loadable for BuilderWisp {
     fn load(ctx: &mut LoadContext) -> rusqlite::Result<LoadResult> {
-        let mut stmt = ctx.conn.prepare("SELECT id, wisp_type FROM wisps LIMIT ?1 OFFSET ?2")?;
+        let mut stmt = ctx.conn.prepare(
+            "SELECT wisps.id, wisps.wisp_type, wisp_states.state, wisp_states.charge_attack
+             FROM wisps LEFT JOIN wisp_states ON wisp_states.id = wisps.id
+             LIMIT ?1 OFFSET ?2"
+        )?;
         let mut rows = stmt.query(ctx.pagination.as_params())?;
         
         let mut count = 0;
         while let Some(row) = rows.next()? {
             let old_id: i64 = row.get(0)?;
             let type_str: String = row.get(1)?;
+            let state_str: Option<String> = row.get(2)?;
+            let charge_attack_str: Option<String> = row.get(3)?;
+            count += 1;
             
             let Ok(wisp_type) = WispType::from_str(&type_str) else { 
-                eprintln!("Failed to parse WispType '{}'", type_str);
+                ctx.report_issue(Some(old_id), format!("Unknown wisp_type '{}'", type_str));
                 continue; 
             };
 
-            let grid_coords = ctx.conn.get_grid_coords(old_id)?;
-            let health = ctx.conn.get_health(old_id)?;
-            let world_position = ctx.conn.get_world_position(old_id)?;
+            let Some(grid_coords) = ctx.expect_row(old_id, "grid_coords", ctx.conn.get_grid_coords(old_id))? else { continue; };
+            let Some(health) = ctx.expect_row(old_id, "healths", ctx.conn.get_health(old_id))? else { continue; };
+            let Some(world_position) = ctx.expect_row(old_id, "world_positions", ctx.conn.get_world_position(old_id))? else { continue; };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
 
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = WispSaveData { entity: new_entity, health, world_position };
-                ctx.commands.entity(new_entity).insert(BuilderWisp::new_for_saving(wisp_type, grid_coords, save_data));
-            }
-            count += 1;
+            // Saves from before wisp states were stored start over as just spawned
+            let (state, charge_attack, path, is_path_current) = match (state_str, charge_attack_str) {
+                (Some(state_str), Some(charge_attack_str)) => {
+                    // The grid version is set once the obstacle grid is complete, see `RestoreWispGridVersion`
+                    let Some(state) = WispState::from_db_str(&state_str, 0) else {
+                        ctx.report_issue(Some(old_id), format!("Unknown wisp state '{}'", state_str));
+                        continue;
+                    };
+                    let Ok(charge_attack) = WispChargeAttack::from_str(&charge_attack_str) else {
+                        ctx.report_issue(Some(old_id), format!("Unknown wisp charge attack '{}'", charge_attack_str));
+                        continue;
+                    };
+                    let Some((path, is_path_current)) = ctx.expect_row(old_id, "grid_paths", ctx.conn.get_grid_path(old_id))? else { continue; };
+                    (state, charge_attack, path, is_path_current)
+                },
+                _ => (WispState::JustSpawned, WispChargeAttack::Charge, VecDeque::new(), false),
+            };
+
+            let save_data = WispSaveData { entity: new_entity, health, world_position, state, charge_attack, path, is_path_current };
+            ctx.commands.entity(new_entity).insert(BuilderWisp::new_for_saving(wisp_type, grid_coords, save_data));
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("wisps").map(Some)
+    }
 }
 
 impl BuilderWisp {
@@ -82,21 +121,24 @@ impl BuilderWisp {
 
     pub fn on_game_save(
         mut commands: Commands,
-        wisps: Query<(Entity, &WispType, &GridCoords, &Health, &Transform, &WispState), With<Wisp>>,
+        obstacle_grid: Res<ObstacleGrid>,
+        wisps: Query<(Entity, &WispType, &GridCoords, &Health, &Transform, &WispState, &WispChargeAttack, &GridPath), With<Wisp>>,
     ) {
         if wisps.is_empty() { return; }
-        let batch = wisps.iter().map(|(entity, wisp_type, coords, health, transform, wisp_state)| {
-            // TODO: Once the wisps logic is mature, save the full wisp state properly. Right now we are ignoring some states(for exmple, attacking) and simply allow wisp to retarget on spawn, and continue from there.
-            let world_position = if matches!(wisp_state, WispState::Attacking) {
-                coords.to_world_position_centered(WISP_GRID_IMPRINT)
-            } else {
-                transform.translation.xy()
+        let batch = wisps.iter().map(|(entity, wisp_type, coords, health, transform, wisp_state, charge_attack, grid_path)| {
+            // A stranded wisp waiting on an older grid would retarget on the next update anyway
+            let state = match wisp_state {
+                WispState::Stranded(grid_version) if *grid_version != obstacle_grid.version => WispState::NeedTarget,
+                state => *state,
             };
-            
             let save_data = WispSaveData {
                 entity,
                 health: health.get_current(),
-                world_position,
+                world_position: transform.translation.xy(),
+                state,
+                charge_attack: *charge_attack,
+                path: grid_path.path.clone(),
+                is_path_current: !obstacle_grid.path_changed_since(*coords, &grid_path.path, grid_path.grid_version),
             };
             BuilderWisp::new_for_saving(*wisp_type, *coords, save_data)
         }).collect::<SaveableBatchCommand<_>>();
@@ -107,16 +149,27 @@ impl BuilderWisp {
         trigger: On<Add, BuilderWisp>,
         mut commands: Commands,
         mut wisps_grid: ResMut<WispsGrid>,
+        mut game_rng: ResMut<GameRng>,
         builders: Query<&BuilderWisp>,
     ) {
         let entity = trigger.entity;
         let Ok(builder) = builders.get(entity) else { return; };
         
-        let mut rng = nanorand::tls_rng();
+        // Loading must not advance the restored stream. Rotation isn't saved, so loaded wisps just get a new one.
+        let rotation_fraction = match builder.save_data {
+            Some(_) => nanorand::tls_rng().generate::<f32>(),
+            None => game_rng.stream(RngStream::Wisps).generate::<f32>(),
+        };
         let mut entity_commands = commands.entity(entity);
         
         if let Some(save_data) = &builder.save_data {
-             entity_commands.insert(Health::new(save_data.health));
+             entity_commands.insert((
+                Health::new(save_data.health),
+                save_data.state,
+                save_data.charge_attack,
+                GridPath { grid_version: 0, path: save_data.path.clone() },
+                RestoreWispGridVersion { is_path_current: save_data.is_path_current },
+             ));
         }
 
         let translation = if let Some(save_data) = &builder.save_data {
@@ -131,7 +184,7 @@ impl BuilderWisp {
                 builder.grid_coords,
                 Transform {
                     translation,
-                    rotation: Quat::from_rotation_z(rng.generate::<f32>() * 2. * std::f32::consts::PI),
+                    rotation: Quat::from_rotation_z(rotation_fraction * 2. * std::f32::consts::PI),
                     ..default()
                 },
                 Wisp,
@@ -152,6 +205,30 @@ impl BuilderWisp {
     }
 }
 
+/// Grid versions aren't saved. Loaded wisps get the version of the obstacle grid rebuilt during loading,
+/// and those whose path was already outdated when saved look for a new target.
+#[derive(Component)]
+pub struct RestoreWispGridVersion {
+    is_path_current: bool,
+}
+impl RestoreWispGridVersion {
+    pub fn on_map_ready(
+        mut commands: Commands,
+        obstacle_grid: Res<ObstacleGrid>,
+        mut wisps: Query<(Entity, &RestoreWispGridVersion, &mut GridPath, &mut WispState)>,
+    ) {
+        for (entity, restore, mut grid_path, mut wisp_state) in wisps.iter_mut() {
+            grid_path.grid_version = obstacle_grid.version;
+            if let WispState::Stranded(grid_version) = &mut *wisp_state {
+                *grid_version = obstacle_grid.version;
+            } else if matches!(*wisp_state, WispState::MovingToTarget) && !restore.is_path_current {
+                *wisp_state = WispState::NeedTarget;
+            }
+            commands.entity(entity).remove::<RestoreWispGridVersion>();
+        }
+    }
+}
+
 pub fn on_wisp_spawn_attach_material<WispT: Component, MaterialT: Asset + WispMaterial>(
     trigger: On<Add, WispT>,
     mut commands: Commands,
@@ -173,7 +250,7 @@ pub fn on_wisp_spawn_attach_material<WispT: Component, MaterialT: Asset + WispMa
 
 pub fn onclick_spawn_system(
     mut commands: Commands,
-    obstacle_grid: Res<lib_grid::grids::obstacles::ObstacleGrid>,
+    obstacle_grid: Res<ObstacleGrid>,
     wisps_grid: Res<WispsGrid>,
     mouse: Res<ButtonInput<MouseButton>>,
     mouse_info: Res<MouseInfo>,
@@ -46,7 +46,7 @@ impl Default for Summoning {
     }
 }
 impl Summoning {
-    fn get_random_wisp_type(&self, rng: &mut nanorand::tls::TlsWyRand) -> WispType {
+    fn get_random_wisp_type(&self, rng: &mut GameRngStream) -> WispType {
         self.wisp_types[rng.generate_range(0..self.wisp_types.len())]
     }
 }
@@ -65,7 +65,7 @@ impl SpawnArea {
     fn get_random_coord(
         &self,
         obstacle_grid: &ObstacleGrid,
-        rng: &mut nanorand::tls::TlsWyRand,
+        rng: &mut GameRngStream,
     ) -> GridCoords {
         // Nano-rand is off by 1 in i32! 
         match self {
@@ -212,22 +212,31 @@ impl Loadable for BuilderSummoning {
             let next_spawn_time: f32 = row.get(3)?;
             let is_active: i32 = row.get(4)?;
             
-            let summoning: Summoning = serde_json::from_str(&summoning_json)
-                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, Box::new(e)))?;
-            
-            if let Some(new_entity) = ctx.get_new_entity_for_old(old_id) {
-                let save_data = SummoningSaveData {
-                    entity: new_entity,
-                    produced,
-                    next_spawn_time,
-                    is_active: is_active != 0,
-                };
-                ctx.commands.entity(new_entity).insert(BuilderSummoning::new_for_saving(summoning, save_data));
-            }
             count += 1;
+            
+            let summoning: Summoning = match serde_json::from_str(&summoning_json) {
+                Ok(summoning) => summoning,
+                Err(e) => {
+                    ctx.report_issue(Some(old_id), format!("Invalid summoning_json: {}", e));
+                    continue;
+                }
+            };
+            let Some(new_entity) = ctx.expect_new_entity_for_old(old_id) else { continue; };
+
+            let save_data = SummoningSaveData {
+                entity: new_entity,
+                produced,
+                next_spawn_time,
+                is_active: is_active != 0,
+            };
+            ctx.commands.entity(new_entity).insert(BuilderSummoning::new_for_saving(summoning, save_data));
         }
         Ok(count.into())
     }
+
+    fn total_rows(conn: &rusqlite::Connection) -> rusqlite::Result<Option<usize>> {
+        conn.count_rows("summonings").map(Some)
+    }
 }
 impl BuilderSummoning {
     pub fn new(summoning: Summoning) -> Self {
@@ -287,13 +296,14 @@ impl BuilderSummoning {
 fn tick_active_summoning_system(
     time: Res<Time>,
     mut clock: ResMut<SummoningClock>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
     obstacle_grid: Res<ObstacleGrid>,
     mut summoning: Query<(&Summoning, &mut SummoningRuntime), With<SummoningMarkerActive>>,
 ) {
     clock.0 += time.delta_secs();
     let now = clock.0;
-    let mut rng = nanorand::tls_rng();
+    let rng = game_rng.stream(RngStream::Summoning);
 
     for (summoning, mut runtime) in &mut summoning {
         // Check if limit is reached(if set)
@@ -308,8 +318,8 @@ fn tick_active_summoning_system(
                 let to_spawn: i32 = std::cmp::min(bulk_count, remaining);
                 if to_spawn <= 0 { continue; }
                 for _ in 0..(to_spawn as usize) {
-                    let grid_coords = summoning.area.get_random_coord(&obstacle_grid, &mut rng);
-                    let wisp_type = summoning.get_random_wisp_type(&mut rng);
+                    let grid_coords = summoning.area.get_random_coord(&obstacle_grid, rng);
+                    let wisp_type = summoning.get_random_wisp_type(rng);
                     commands.spawn(BuilderWisp::new(wisp_type, grid_coords));
                 }
                 runtime.produced = runtime.produced.saturating_add(to_spawn);
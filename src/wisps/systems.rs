@@ -1,17 +1,99 @@
-use lib_grid::grids::emissions::EmissionsGrid;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use strum::IntoEnumIterator;
+
+use lib_grid::grids::emissions::{EmissionsGrid, EmissionsType};
+use lib_grid::grids::flow_field::FlowFieldGrid;
 use lib_grid::grids::obstacles::{GridStructureType, ObstacleGrid};
+use lib_grid::grids::terrain::TerrainGrid;
 use lib_grid::grids::wisps::WispsGrid;
-use lib_grid::search::pathfinding::path_find_energy_beckon;
+use lib_grid::search::pathfinding::{flood_flow_field, flow_path_cost, path_find_energy_beckon};
 use lib_inventory::stats::StatsWispsKilled;
 
 use crate::effects::wisp_attack::BuilderWispAttackEffect;
 use crate::prelude::*;
 
-use super::components::{Wisp, WispChargeAttack, WispState};
+use super::components::{Wisp, WispChargeAttack, WispState, WispType};
+
+/// Scent left on every field a wisp steps on.
+const SCENT_PER_STEP: f32 = 5.;
+/// A moving wisp switches to the flow field's way once it costs less than this share of its current path.
+/// Keeps wisps from switching back and forth between routes of almost the same cost.
+const CHEAPER_PATH_RATIO: f32 = 0.9;
+
+/// Flow field of every wisp type, each weighing the emissions its own way.
+/// Fields are recomputed when the obstacle or terrain grid changes. To follow the emissions, every field is queued
+/// for a refresh once per `emissions_refresh` and at most one queued field is recomputed per frame, only if a channel
+/// its wisp type reacts to has changed since.
+#[derive(Resource)]
+pub struct WispFlowFields {
+    fields: HashMap<WispType, FlowFieldGrid>,
+    /// Versions of the weighted emissions channels each field was computed with.
+    emissions_versions: HashMap<WispType, [GridVersion; EmissionsType::ALL.len()]>,
+    emissions_refresh: Timer,
+    refresh_queue: VecDeque<WispType>,
+}
+impl Default for WispFlowFields {
+    fn default() -> Self {
+        Self {
+            fields: WispType::iter().map(|wisp_type| (wisp_type, FlowFieldGrid::new_empty())).collect(),
+            emissions_versions: HashMap::default(),
+            emissions_refresh: Timer::from_seconds(1., TimerMode::Repeating),
+            refresh_queue: VecDeque::new(),
+        }
+    }
+}
+impl WispFlowFields {
+    pub fn reset(mut commands: Commands) {
+        commands.insert_resource(WispFlowFields::default());
+    }
+
+    /// Cost for a `wisp_type` wisp of reaching the closest energy supplier from `coords`. Infinite when unreachable.
+    pub fn cost_at(&self, wisp_type: WispType, coords: GridCoords) -> f32 {
+        let Some(flow_field) = self.fields.get(&wisp_type) else { return f32::INFINITY; };
+        if !coords.is_in_bounds(flow_field.bounds()) { return f32::INFINITY; }
+        flow_field[coords].cost
+    }
+
+    /// Versions of the channels `wisp_type` reacts to. Channels it ignores stay at 0, so their changes don't count.
+    fn emissions_versions_for(emissions_grid: &EmissionsGrid, wisp_type: WispType) -> [GridVersion; EmissionsType::ALL.len()] {
+        let weights = wisp_type.emissions_weights();
+        EmissionsType::ALL.map(|emissions_type| if weights.get(emissions_type) != 0. { emissions_grid.version.get(emissions_type) } else { 0 })
+    }
+
+    /// Recomputes the fields that need it, returns the wisp types whose field changed.
+    fn refresh(&mut self, obstacle_grid: &ObstacleGrid, emissions_grid: &EmissionsGrid, terrain_grid: &TerrainGrid, delta: Duration) -> HashSet<WispType> {
+        if self.emissions_refresh.tick(delta).just_finished() {
+            for wisp_type in WispType::iter() {
+                if !self.refresh_queue.contains(&wisp_type) { self.refresh_queue.push_back(wisp_type); }
+            }
+        }
+        let mut to_flood = self.fields.iter()
+            .filter(|(_, flow_field)| !flow_field.is_up_to_date(obstacle_grid.version, terrain_grid.version))
+            .map(|(wisp_type, _)| *wisp_type)
+            .collect::<HashSet<_>>();
+        // Spread the emissions refreshes over frames, skipping fields whose emissions didn't change
+        while let Some(wisp_type) = self.refresh_queue.pop_front() {
+            if to_flood.contains(&wisp_type) { continue; }
+            if self.emissions_versions.get(&wisp_type) != Some(&Self::emissions_versions_for(emissions_grid, wisp_type)) {
+                to_flood.insert(wisp_type);
+                break;
+            }
+        }
+        for wisp_type in to_flood.iter() {
+            let Some(flow_field) = self.fields.get_mut(wisp_type) else { continue; };
+            flood_flow_field(flow_field, obstacle_grid, emissions_grid, terrain_grid, &wisp_type.emissions_weights());
+            self.emissions_versions.insert(*wisp_type, Self::emissions_versions_for(emissions_grid, *wisp_type));
+        }
+        to_flood
+    }
+}
 
 pub fn move_wisps(
     time: Res<Time>,
     mut wisps_grid: ResMut<WispsGrid>,
+    terrain_grid: Res<TerrainGrid>,
     mut wisps: Query<(Entity, &WispState, &Health, &MovementSpeed, &mut Transform, &mut GridPath, &mut GridCoords), With<Wisp>>,
 ) {
     for (entity, wisp_state, health, speed, mut transform, mut grid_path, mut grid_coords) in wisps.iter_mut() {
@@ -21,7 +103,7 @@ pub fn move_wisps(
         let interim_target_world_coords = next_target.to_world_position_centered(GridImprint::default());
         let direction = interim_target_world_coords - curr_world_coords;
         let (sx, sy) = (direction.x.signum(), direction.y.signum());
-        let wisp_speed = speed.0;
+        let wisp_speed = speed.0 * terrain_grid.speed_multiplier_at(*grid_coords);
         transform.translation += Vec3::new(sx * time.delta_secs() * wisp_speed, sy * time.delta_secs() * wisp_speed, 0.);
         // If close enough, remove from path.
         if (transform.translation.truncate().distance(interim_target_world_coords)) < 1. {
@@ -36,18 +118,54 @@ pub fn move_wisps(
     }
 }
 
+pub fn leave_scent_trails(
+    mut emissions_grid: ResMut<EmissionsGrid>,
+    wisps: Query<&GridCoords, (With<Wisp>, Changed<GridCoords>)>,
+) {
+    for coords in wisps.iter() {
+        if !coords.is_in_bounds(emissions_grid.bounds()) { continue; }
+        emissions_grid.add_emissions(*coords, EmissionsType::Scent, SCENT_PER_STEP);
+    }
+}
+
 pub fn target_wisps(
-    mut wisps_query: Query<(&mut WispState, &mut GridPath, &GridCoords), With<Wisp>>,
+    mut wisps_query: Query<(&WispType, &mut WispState, &mut GridPath, &GridCoords), With<Wisp>>,
     obstacle_grid: Res<ObstacleGrid>,
     emissions_grid: Res<EmissionsGrid>,
+    terrain_grid: Res<TerrainGrid>,
+    mut flow_fields: ResMut<WispFlowFields>,
+    time: Res<Time>,
 ) {
-    wisps_query.par_iter_mut().for_each(|(mut wisp_state, mut grid_path, grid_coords)| {
-        // Retarget is needed when grid has changed or there is no target yet.
-        let is_path_outdated = matches!(*wisp_state, WispState::MovingToTarget) && grid_path.grid_version != obstacle_grid.version;
-        let need_retarget = is_path_outdated || matches!(*wisp_state, WispState::NeedTarget | WispState::JustSpawned) || matches!(*wisp_state, WispState::Stranded(ref grid_version) if obstacle_grid.version != *grid_version);
+    // Computed once per obstacle or terrain grid change or emissions refresh and shared by all wisps of a type
+    let reflooded = flow_fields.refresh(&obstacle_grid, &emissions_grid, &terrain_grid, time.delta());
+    let flow_fields = &flow_fields.fields;
+    wisps_query.par_iter_mut().for_each(|(wisp_type, mut wisp_state, mut grid_path, grid_coords)| {
+        let Some(flow_field) = flow_fields.get(wisp_type) else { return; };
+        let is_moving = matches!(*wisp_state, WispState::MovingToTarget);
+        // Retarget is needed when grid has changed along the path or there is no target yet.
+        // Changes elsewhere only move the path's version forward, so they aren't checked again.
+        let is_path_outdated = is_moving && grid_path.grid_version != obstacle_grid.version && {
+            let is_outdated = obstacle_grid.path_changed_since(*grid_coords, &grid_path.path, grid_path.grid_version);
+            if !is_outdated { grid_path.grid_version = obstacle_grid.version; }
+            is_outdated
+        };
+        // A refreshed field may offer a cheaper way than the one being walked, e.g. through a new supplier, a removed wall
+        // or changed emissions. Paths that are still about as good as the field's are kept.
+        let is_path_costlier = is_moving && !is_path_outdated && reflooded.contains(wisp_type)
+            && grid_coords.is_in_bounds(flow_field.bounds())
+            && flow_field[*grid_coords].cost < CHEAPER_PATH_RATIO * flow_path_cost(&obstacle_grid, &emissions_grid, &terrain_grid, &wisp_type.emissions_weights(), &grid_path.path);
+        let need_retarget = is_path_outdated || is_path_costlier || matches!(*wisp_state, WispState::NeedTarget | WispState::JustSpawned) || matches!(*wisp_state, WispState::Stranded(ref grid_version) if obstacle_grid.version != *grid_version);
         if !need_retarget { return; }
 
-        if let Some(path) = path_find_energy_beckon(&obstacle_grid, &emissions_grid, *grid_coords) {
+        // The field doesn't cover walls, so a wisp caught by a freshly placed wall searches on its own.
+        // Anywhere else an unreachable field means the wisp's own search would fail too.
+        // Wisps have no rage or siege behaviour yet. Once they do, it is the state to route through the own search here.
+        let path = if obstacle_grid[*grid_coords].has_wall() {
+            path_find_energy_beckon(&obstacle_grid, &emissions_grid, &terrain_grid, &wisp_type.emissions_weights(), *grid_coords)
+        } else {
+            flow_field.path_from(*grid_coords)
+        };
+        if let Some(path) = path {
             *wisp_state = WispState::MovingToTarget;
             grid_path.grid_version = obstacle_grid.version;
             grid_path.path = path.into();